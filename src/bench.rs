@@ -0,0 +1,88 @@
+//! Micro-benchmarks behind `--bench_mode`.
+//!
+//! The timing loops live here so they can be run from tests with a small
+//! iteration count; `main.rs` only prints the report.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::CrafterError;
+use crate::packet::PacketBuilder;
+
+/// Timings of the borrowed and owned build paths over the same workload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllocReport {
+    /// Number of packets built by each path
+    pub iterations: u32,
+    /// Total time spent in [`PacketBuilder::build_packet`]
+    pub borrowed: Duration,
+    /// Total time spent in [`PacketBuilder::build_packet_owned`]
+    pub owned: Duration,
+}
+
+impl AllocReport {
+    /// Returns the extra time per packet taken by the owned path, in nanoseconds.
+    ///
+    /// Negative when the owned path happened to be faster.
+    pub fn overhead_per_packet_ns(&self) -> f64 {
+        if self.iterations == 0 {
+            return 0.0;
+        }
+        (self.owned.as_nanos() as f64 - self.borrowed.as_nanos() as f64) / self.iterations as f64
+    }
+}
+
+/// Times `iterations` builds of `payload` through one build path.
+///
+/// # Arguments
+///
+/// * `builder` - The configured builder
+/// * `payload` - Payload used for every packet
+/// * `iterations` - Number of packets to build
+/// * `owned` - Use [`PacketBuilder::build_packet_owned`] instead of
+///   [`PacketBuilder::build_packet`]
+///
+/// # Returns
+///
+/// The total elapsed time, or the first build error.
+pub fn time_builds(
+    builder: &mut PacketBuilder,
+    payload: &[u8],
+    iterations: u32,
+    owned: bool,
+) -> Result<Duration, CrafterError> {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        if owned {
+            black_box(builder.build_packet_owned(black_box(payload))?);
+        } else {
+            black_box(builder.build_packet(black_box(payload))?);
+        }
+    }
+    Ok(start.elapsed())
+}
+
+/// Compares the borrowed and owned build paths over the same workload.
+///
+/// Each path first builds one warm-up packet so the builder's buffer is
+/// already allocated when timing starts.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, bench::compare_alloc, packet::PacketBuilder};
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// let report = compare_alloc(&mut builder, b"probe", 10).unwrap();
+/// assert_eq!(report.iterations, 10);
+/// ```
+pub fn compare_alloc(
+    builder: &mut PacketBuilder,
+    payload: &[u8],
+    iterations: u32,
+) -> Result<AllocReport, CrafterError> {
+    builder.build_packet(payload)?;
+    let borrowed = time_builds(builder, payload, iterations, false)?;
+    let owned = time_builds(builder, payload, iterations, true)?;
+    Ok(AllocReport { iterations, borrowed, owned })
+}
@@ -0,0 +1,286 @@
+//! TOML config files with named profiles, and YAML packet specs.
+//!
+//! A config file sets default values for command-line arguments, keyed by
+//! their long names, and can hold several named profiles:
+//!
+//! ```toml
+//! l4_protocol = "udp"
+//!
+//! [profiles.web]
+//! dst_ip = "10.0.0.80"
+//! dest_port = 443
+//!
+//! [profiles.dns]
+//! dst_ip = "10.0.0.53"
+//! dest_port = 53
+//! ```
+//!
+//! A `--spec` file describes one packet layer by layer instead:
+//!
+//! ```yaml
+//! ethernet: { src: "aa:bb:cc:dd:ee:ff", dst: "11:22:33:44:55:66" }
+//! ip: { src: 10.0.0.1, dst: 10.0.0.80 }
+//! l4: { protocol: tcp, dst_port: 443 }
+//! payload: { size: 32 }
+//! ```
+//!
+//! Values of both are turned back into arguments and parsed by clap, so they
+//! are validated exactly like the command line. Precedence, lowest first: the
+//! top-level config values, the selected profile, the spec, the command line.
+
+use std::ffi::OsString;
+
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use serde::Deserialize;
+
+use crate::Args;
+
+/// Parses the command line, merging in `--config`, `--profile` and `--spec` if given.
+///
+/// Without `--config` and `--spec` this is [`clap::Parser::try_parse_from`],
+/// plus recording whether `--dst_mac` was given (see [`Args::dst_mac_given`]).
+/// Otherwise the files' values (see [`config_args`] and [`spec_args`]) are
+/// placed before the real arguments and everything is parsed again, with later occurrences of an
+/// argument replacing earlier ones so the command line wins. Repeatable
+/// arguments such as `--sink` collect values from all sources.
+///
+/// # Arguments
+///
+/// * `argv` - The full argument list, including the program name
+///
+/// # Returns
+///
+/// - `Ok(Args)` with the merged arguments
+/// - `Err(clap::Error)` for a parse error, an unreadable or invalid config
+///   file or spec, or an unknown profile; call `exit()` on it to report it
+///   like clap does
+pub fn parse_args<I, T>(argv: I) -> Result<Args, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    let argv: Vec<OsString> = argv.into_iter().map(Into::into).collect();
+    let matches = Args::command().try_get_matches_from(&argv)?;
+    let args = Args::from_arg_matches(&matches)?;
+    if args.config.is_none() && args.spec.is_none() {
+        return Ok(record_given(args, &matches));
+    }
+    
+    let read = |kind: &str, path: &str| {
+        std::fs::read_to_string(path)
+            .map_err(|e| clap::Error::raw(ErrorKind::Io, format!("Failed to read {} '{}': {}\n", kind, path, e)))
+    };
+    let invalid = |kind: &str, path: &str, e: String| {
+        clap::Error::raw(ErrorKind::InvalidValue, format!("{} '{}': {}\n", kind, path, e))
+    };
+    let mut file_args = Vec::new();
+    if let Some(path) = &args.config {
+        let text = read("config", path)?;
+        file_args.extend(config_args(&text, args.profile.as_deref()).map_err(|e| invalid("Config", path, e))?);
+    }
+    if let Some(path) = &args.spec {
+        let text = read("spec", path)?;
+        file_args.extend(spec_args(&text).map_err(|e| invalid("Spec", path, e))?);
+    }
+    
+    let mut merged: Vec<OsString> = argv.iter().take(1).cloned().collect();
+    merged.extend(file_args.into_iter().map(OsString::from));
+    merged.extend(argv.iter().skip(1).cloned());
+    let matches = Args::command().args_override_self(true).try_get_matches_from(merged)?;
+    Ok(record_given(Args::from_arg_matches(&matches)?, &matches))
+}
+
+/// Records which arguments were given rather than left at their clap defaults.
+fn record_given(mut args: Args, matches: &ArgMatches) -> Args {
+    args.dst_mac_given = matches.value_source("dst_mac") == Some(ValueSource::CommandLine);
+    args
+}
+
+/// Converts a config file into command-line arguments.
+///
+/// The top-level values come first, then those of `[profiles.<profile>]`
+/// if a profile is selected. Each key is the long name of an argument:
+/// `true` becomes a bare flag, `false` is skipped, arrays repeat the
+/// argument once per element and other values become `--key=value`.
+///
+/// # Arguments
+///
+/// * `text` - Contents of the TOML file
+/// * `profile` - Name of the profile to apply, if any
+///
+/// # Returns
+///
+/// - `Ok(Vec<String>)` with the arguments in precedence order
+/// - `Err(String)` if the TOML is invalid, the profile does not exist, or a
+///   value is a nested table or sets `config`/`profile` itself
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::config::config_args;
+///
+/// let text = "verbose = true\n[profiles.dns]\ndest_port = 53\n";
+/// assert_eq!(config_args(text, Some("dns")).unwrap(), ["--verbose", "--dest_port=53"]);
+/// assert!(config_args(text, Some("web")).is_err());
+/// ```
+pub fn config_args(text: &str, profile: Option<&str>) -> Result<Vec<String>, String> {
+    let mut table: toml::Table = text.parse().map_err(|e| format!("Invalid TOML: {}", e))?;
+    let profiles = match table.remove("profiles") {
+        Some(toml::Value::Table(profiles)) => profiles,
+        Some(_) => return Err("'profiles' must be a table".to_string()),
+        None => toml::Table::new(),
+    };
+    
+    let mut args = table_args(&table)?;
+    if let Some(name) = profile {
+        let selected = match profiles.get(name) {
+            Some(toml::Value::Table(selected)) => selected,
+            Some(_) => return Err(format!("Profile '{}' must be a table", name)),
+            None => {
+                let known: Vec<&str> = profiles.keys().map(String::as_str).collect();
+                return Err(format!("Unknown profile '{}' (available: {})", name, known.join(", ")));
+            }
+        };
+        args.extend(table_args(selected)?);
+    }
+    Ok(args)
+}
+
+/// Converts the values of one table into arguments.
+fn table_args(table: &toml::Table) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    for (key, value) in table {
+        if key == "config" || key == "profile" {
+            return Err(format!("'{}' cannot be set from a config file", key));
+        }
+        let values = match value {
+            toml::Value::Array(items) => items.iter().collect(),
+            _ => vec![value],
+        };
+        for value in values {
+            match value {
+                toml::Value::Boolean(true) => args.push(format!("--{}", key)),
+                toml::Value::Boolean(false) => {}
+                toml::Value::String(s) => args.push(format!("--{}={}", key, s)),
+                toml::Value::Integer(n) => args.push(format!("--{}={}", key, n)),
+                toml::Value::Float(x) => args.push(format!("--{}={}", key, x)),
+                _ => return Err(format!("Unsupported value for '{}'", key)),
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// A layered packet description read from a `--spec` YAML file.
+///
+/// Every field is optional; a missing one keeps the argument's default.
+/// Unknown keys are rejected so typos do not go unnoticed.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PacketSpec {
+    /// Ethernet layer
+    pub ethernet: EthernetSpec,
+    /// IPv4 layer
+    pub ip: IpSpec,
+    /// TCP/UDP layer
+    pub l4: L4Spec,
+    /// Payload
+    pub payload: PayloadSpec,
+}
+
+/// Ethernet layer of a [`PacketSpec`].
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct EthernetSpec {
+    /// Source MAC address (`--src_mac`)
+    pub src: Option<String>,
+    /// Destination MAC address (`--dst_mac`)
+    pub dst: Option<String>,
+    /// 802.1Q VLAN ID (`--vlan_id`)
+    pub vlan_id: Option<u16>,
+}
+
+/// IPv4 layer of a [`PacketSpec`].
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct IpSpec {
+    /// Source address (`--src_ip`)
+    pub src: Option<String>,
+    /// Destination address (`--dst_ip`)
+    pub dst: Option<String>,
+    /// DSCP codepoint (`--dscp`)
+    pub dscp: Option<u8>,
+}
+
+/// TCP/UDP layer of a [`PacketSpec`].
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct L4Spec {
+    /// `tcp`, `udp` or `icmp` (`--l4_protocol`)
+    pub protocol: Option<String>,
+    /// Source port (`--src_port`)
+    pub src_port: Option<u16>,
+    /// Destination port (`--dest_port`)
+    pub dst_port: Option<u16>,
+    /// TCP sequence number (`--tcp_seq`)
+    pub seq: Option<u32>,
+}
+
+/// Payload of a [`PacketSpec`].
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PayloadSpec {
+    /// Payload length in bytes (`--payload_size`)
+    pub size: Option<usize>,
+    /// Value of every payload byte (`--payload_pad`)
+    pub pad: Option<u8>,
+}
+
+/// Converts a YAML packet spec into command-line arguments.
+///
+/// Each value set in the [`PacketSpec`] becomes `--<flag>=<value>` for the
+/// argument it stands for, in layer order.
+///
+/// # Arguments
+///
+/// * `text` - Contents of the YAML file
+///
+/// # Returns
+///
+/// - `Ok(Vec<String>)` with the arguments
+/// - `Err(String)` if the YAML is invalid or has an unknown key
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::config::spec_args;
+///
+/// let text = "ip:\n  dst: 10.0.0.80\nl4:\n  protocol: tcp\n  dst_port: 443\n";
+/// assert_eq!(spec_args(text).unwrap(), ["--dst_ip=10.0.0.80", "--l4_protocol=tcp", "--dest_port=443"]);
+/// assert!(spec_args("ip:\n  ttl: 1\n").is_err());
+/// ```
+pub fn spec_args(text: &str) -> Result<Vec<String>, String> {
+    let spec: PacketSpec = serde_yaml::from_str(text).map_err(|e| format!("Invalid spec: {}", e))?;
+    
+    let mut args = Vec::new();
+    let mut push = |flag: &str, value: Option<String>| {
+        if let Some(value) = value {
+            args.push(format!("--{}={}", flag, value));
+        }
+    };
+    push("src_mac", spec.ethernet.src);
+    push("dst_mac", spec.ethernet.dst);
+    push("vlan_id", spec.ethernet.vlan_id.map(|id| id.to_string()));
+    push("src_ip", spec.ip.src);
+    push("dst_ip", spec.ip.dst);
+    push("dscp", spec.ip.dscp.map(|dscp| dscp.to_string()));
+    push("l4_protocol", spec.l4.protocol);
+    push("src_port", spec.l4.src_port.map(|port| port.to_string()));
+    push("dest_port", spec.l4.dst_port.map(|port| port.to_string()));
+    push("tcp_seq", spec.l4.seq.map(|seq| seq.to_string()));
+    push("payload_size", spec.payload.size.map(|size| size.to_string()));
+    push("payload_pad", spec.payload.pad.map(|pad| pad.to_string()));
+    Ok(args)
+}
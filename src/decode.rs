@@ -0,0 +1,279 @@
+//! Packet decoding for detailed debug output.
+//!
+//! This module parses a complete Ethernet frame back into structured,
+//! serializable header information. It is used to enrich the JSON and YAML
+//! debug output with the decoded field values. [`stream`] decodes a PCAP
+//! capture one packet at a time, so large captures can be summarized
+//! without loading them into memory.
+
+use pcap_file::pcap::PcapReader;
+use pnet::packet::ethernet::EthernetPacket;
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::tcp::TcpPacket;
+use pnet::packet::udp::UdpPacket;
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::io::Read;
+
+use crate::packet::ipv4_header_offset;
+
+/// Errors returned when a frame cannot be decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /// The frame is shorter than an Ethernet header (14 bytes)
+    TruncatedEthernet,
+    /// The frame ends inside the IPv4 header
+    TruncatedIpv4,
+    /// The frame ends inside the TCP/UDP header
+    TruncatedL4,
+    /// The PCAP stream could not be read
+    InvalidCapture(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::TruncatedEthernet => write!(f, "Frame is shorter than an Ethernet header"),
+            DecodeError::TruncatedIpv4 => write!(f, "Frame ends inside the IPv4 header"),
+            DecodeError::TruncatedL4 => write!(f, "Frame ends inside the Layer 4 header"),
+            DecodeError::InvalidCapture(reason) => write!(f, "Invalid PCAP capture: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decoded Ethernet (Layer 2) header.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct EthernetInfo {
+    /// Destination MAC address (aa:bb:cc:dd:ee:ff)
+    pub destination: String,
+    /// Source MAC address (aa:bb:cc:dd:ee:ff)
+    pub source: String,
+    /// EtherType value (0x0800 for IPv4)
+    pub ethertype: u16,
+}
+
+/// Decoded IPv4 (Layer 3) header.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct Ipv4Info {
+    /// IP version (4)
+    pub version: u8,
+    /// Header length in 32-bit words
+    pub header_length: u8,
+    /// Differentiated Services Code Point
+    pub dscp: u8,
+    /// Explicit Congestion Notification
+    pub ecn: u8,
+    /// Total length of the IP packet in bytes
+    pub total_length: u16,
+    /// Identification field
+    pub identification: u16,
+    /// 3-bit flags field
+    pub flags: u8,
+    /// 13-bit fragment offset
+    pub fragment_offset: u16,
+    /// Time to live
+    pub ttl: u8,
+    /// Next-level protocol number (6 = TCP, 17 = UDP)
+    pub protocol: u8,
+    /// Header checksum
+    pub checksum: u16,
+    /// Source IPv4 address
+    pub source: String,
+    /// Destination IPv4 address
+    pub destination: String,
+}
+
+/// Decoded Layer 4 header, tagged by protocol.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(tag = "protocol", rename_all = "lowercase")]
+pub enum L4Info {
+    /// TCP header fields
+    Tcp {
+        source_port: u16,
+        destination_port: u16,
+        sequence: u32,
+        acknowledgement: u32,
+        data_offset: u8,
+        flags: u8,
+        window: u16,
+        checksum: u16,
+        urgent_ptr: u16,
+    },
+    /// UDP header fields
+    Udp {
+        source_port: u16,
+        destination_port: u16,
+        length: u16,
+        checksum: u16,
+    },
+}
+
+/// A frame decoded into its layers.
+///
+/// Layers that are not present or not understood (e.g. an IPv6 frame's
+/// `ipv4`, or an ICMP packet's `l4`) are `None`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DecodedPacket {
+    /// Ethernet header
+    pub ethernet: EthernetInfo,
+    /// IPv4 header, if the frame carries IPv4
+    pub ipv4: Option<Ipv4Info>,
+    /// TCP/UDP header, if the IPv4 packet carries one
+    pub l4: Option<L4Info>,
+}
+
+/// Decodes a complete Ethernet frame into its layers.
+///
+/// The IPv4 header is located with [`ipv4_header_offset`], so packets
+/// with an 802.1Q tag or wrapped in PPPoE decode like plain ones.
+///
+/// # Arguments
+///
+/// * `frame` - The complete packet bytes (Ethernet frame)
+///
+/// # Returns
+///
+/// - `Ok(DecodedPacket)` with every layer that could be identified
+/// - `Err(DecodeError)` if the frame is cut short inside a header
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::PacketBuilder, decode::decode_packet};
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// let decoded = decode_packet(builder.build_packet(b"hello").unwrap()).unwrap();
+/// assert_eq!(decoded.ipv4.unwrap().protocol, 17);
+/// ```
+pub fn decode_packet(frame: &[u8]) -> Result<DecodedPacket, DecodeError> {
+    let eth = EthernetPacket::new(frame).ok_or(DecodeError::TruncatedEthernet)?;
+    let ethernet = EthernetInfo {
+        destination: eth.get_destination().to_string(),
+        source: eth.get_source().to_string(),
+        ethertype: eth.get_ethertype().0,
+    };
+    
+    let Some(ip_start) = ipv4_header_offset(frame) else {
+        return Ok(DecodedPacket { ethernet, ipv4: None, l4: None });
+    };
+    
+    let ip_bytes = &frame[ip_start..];
+    let ip = Ipv4Packet::new(ip_bytes).ok_or(DecodeError::TruncatedIpv4)?;
+    let header_len = ip.get_header_length() as usize * 4;
+    if header_len < 20 || ip_bytes.len() < header_len {
+        return Err(DecodeError::TruncatedIpv4);
+    }
+    let ipv4 = Ipv4Info {
+        version: ip.get_version(),
+        header_length: ip.get_header_length(),
+        dscp: ip.get_dscp(),
+        ecn: ip.get_ecn(),
+        total_length: ip.get_total_length(),
+        identification: ip.get_identification(),
+        flags: ip.get_flags(),
+        fragment_offset: ip.get_fragment_offset(),
+        ttl: ip.get_ttl(),
+        protocol: ip.get_next_level_protocol().0,
+        checksum: ip.get_checksum(),
+        source: ip.get_source().to_string(),
+        destination: ip.get_destination().to_string(),
+    };
+    
+    let segment = &ip_bytes[header_len..];
+    let l4 = match ip.get_next_level_protocol() {
+        IpNextHeaderProtocols::Tcp => {
+            let tcp = TcpPacket::new(segment).ok_or(DecodeError::TruncatedL4)?;
+            Some(L4Info::Tcp {
+                source_port: tcp.get_source(),
+                destination_port: tcp.get_destination(),
+                sequence: tcp.get_sequence(),
+                acknowledgement: tcp.get_acknowledgement(),
+                data_offset: tcp.get_data_offset(),
+                flags: tcp.get_flags(),
+                window: tcp.get_window(),
+                checksum: tcp.get_checksum(),
+                urgent_ptr: tcp.get_urgent_ptr(),
+            })
+        }
+        IpNextHeaderProtocols::Udp => {
+            let udp = UdpPacket::new(segment).ok_or(DecodeError::TruncatedL4)?;
+            Some(L4Info::Udp {
+                source_port: udp.get_source(),
+                destination_port: udp.get_destination(),
+                length: udp.get_length(),
+                checksum: udp.get_checksum(),
+            })
+        }
+        _ => None,
+    };
+    
+    Ok(DecodedPacket { ethernet, ipv4: Some(ipv4), l4 })
+}
+
+/// One packet of a capture decoded by [`stream`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PacketSummary {
+    /// 0-based position of the packet in the capture
+    pub index: usize,
+    /// Capture timestamp (seconds since epoch) as floating point
+    pub timestamp: f64,
+    /// Captured length in bytes
+    pub length: usize,
+    /// The decoded headers
+    pub decoded: DecodedPacket,
+}
+
+/// Decodes a PCAP capture lazily, one packet per iteration.
+///
+/// Only the packet being decoded is held in memory, so arbitrarily large
+/// captures can be summarized. A packet that cannot be decoded yields a
+/// `DecodeError` and the stream moves on to the next one; an unreadable
+/// PCAP header or record yields `DecodeError::InvalidCapture` and ends
+/// the stream.
+///
+/// # Arguments
+///
+/// * `reader` - Source of the PCAP bytes, e.g. a `File` or `Cursor`
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::decode::{stream, DecodeError};
+/// use std::io::Cursor;
+///
+/// let mut summaries = stream(Cursor::new(b"not a capture".to_vec()));
+/// assert!(matches!(summaries.next(), Some(Err(DecodeError::InvalidCapture(_)))));
+/// assert!(summaries.next().is_none());
+/// ```
+pub fn stream<R: Read>(reader: R) -> impl Iterator<Item = Result<PacketSummary, DecodeError>> {
+    let invalid = |e: pcap_file::PcapError| DecodeError::InvalidCapture(e.to_string());
+    let (mut reader, mut error) = match PcapReader::new(reader) {
+        Ok(reader) => (Some(reader), None),
+        Err(e) => (None, Some(invalid(e))),
+    };
+    let mut index = 0;
+    
+    std::iter::from_fn(move || {
+        if let Some(e) = error.take() {
+            return Some(Err(e));
+        }
+        let summary = match reader.as_mut()?.next_packet()? {
+            Ok(packet) => decode_packet(&packet.data).map(|decoded| PacketSummary {
+                index,
+                timestamp: packet.timestamp.as_secs_f64(),
+                length: packet.data.len(),
+                decoded,
+            }),
+            Err(e) => Err(invalid(e)),
+        };
+        if let Err(DecodeError::InvalidCapture(_)) = summary {
+            reader = None;
+        }
+        index += 1;
+        Some(summary)
+    })
+}
@@ -0,0 +1,75 @@
+//! Error type for packet construction and post-processing.
+
+use std::fmt;
+
+/// Errors raised while building or post-processing a packet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CrafterError {
+    /// The frame is larger than the configured maximum
+    FrameTooLarge {
+        /// Length of the frame in bytes (without FCS)
+        length: usize,
+        /// Largest allowed frame length in bytes (without FCS)
+        max: usize,
+    },
+    /// The IPv4 total length would not fit in its 16-bit field
+    Ipv4LengthOverflow {
+        /// IP header plus payload length in bytes
+        length: usize,
+    },
+    /// An override names a field that is not supported
+    UnknownOverrideField {
+        /// Dotted field path, e.g. `ipv4.ttl`
+        path: String,
+    },
+    /// An override value does not fit in its field
+    OverrideOutOfRange {
+        /// Dotted field path, e.g. `ipv4.ttl`
+        path: String,
+        /// The rejected value
+        value: u64,
+        /// Width of the field in bits
+        bits: u32,
+    },
+    /// An override targets a header the frame does not contain
+    OverrideTargetMissing {
+        /// Dotted field path, e.g. `tcp.window`
+        path: String,
+    },
+    /// A frame could not be split into its layers
+    MalformedFrame {
+        /// What was wrong with the frame
+        reason: String,
+    },
+}
+
+impl fmt::Display for CrafterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrafterError::FrameTooLarge { length, max } => write!(
+                f,
+                "Frame is {} bytes, exceeding the {}-byte maximum",
+                length, max
+            ),
+            CrafterError::Ipv4LengthOverflow { length } => write!(
+                f,
+                "IPv4 total length {} exceeds the 65535-byte maximum",
+                length
+            ),
+            CrafterError::UnknownOverrideField { path } => {
+                write!(f, "Unknown override field '{}'", path)
+            }
+            CrafterError::OverrideOutOfRange { path, value, bits } => write!(
+                f,
+                "Override value {} for '{}' does not fit in {} bits",
+                value, path, bits
+            ),
+            CrafterError::OverrideTargetMissing { path } => {
+                write!(f, "Cannot override '{}': the packet has no such header", path)
+            }
+            CrafterError::MalformedFrame { reason } => write!(f, "Malformed frame: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CrafterError {}
@@ -1,19 +1,34 @@
 //! Library module exposing internal components for testing
 
+pub mod bench;
+pub mod config;
+pub mod error;
+pub mod fuzz;
 pub mod parsing;
+pub mod payload;
 pub mod packet;
 pub mod output;
+pub mod overrides;
+pub mod decode;
+pub mod run;
+pub mod report;
+pub mod rewrite;
+pub mod send;
+pub mod traceroute;
 
-pub use clap::Parser;
-use clap::ValueEnum;
-use std::net::Ipv4Addr;
+pub use clap::{Parser, ValueEnum};
+pub use error::CrafterError;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::path::Path;
+use std::sync::OnceLock;
 
 /// Layer 4 (transport layer) protocol options for packet construction.
 #[derive(ValueEnum, Clone, Debug, PartialEq)]
 pub enum L4Protocol {
     Tcp,
     Udp,
+    /// ICMP echo request (ping)
+    Icmp,
 }
 
 /// Output format for debug files.
@@ -23,16 +38,202 @@ pub enum DebugFormat {
     Json,
     /// PCAP format readable by Wireshark/tshark
     Pcap,
+    /// YAML format with the decoded packet as a tree
+    Yaml,
+    /// Text in the layout of `tcpdump -tttt -xx`, for diffing against captures
+    Tcpdump,
 }
 
+impl L4Protocol {
+    /// Returns the command-line name of the protocol (e.g. `"tcp"`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            L4Protocol::Tcp => "tcp",
+            L4Protocol::Udp => "udp",
+            L4Protocol::Icmp => "icmp",
+        }
+    }
+}
+
+impl DebugFormat {
+    /// Returns the command-line name of the format (e.g. `"pcap"`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            DebugFormat::Json => "json",
+            DebugFormat::Pcap => "pcap",
+            DebugFormat::Yaml => "yaml",
+            DebugFormat::Tcpdump => "tcpdump",
+        }
+    }
+
+    /// Returns the canonical file extension of the format, without the dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DebugFormat::Json => "json",
+            DebugFormat::Pcap => "pcap",
+            DebugFormat::Yaml => "yaml",
+            DebugFormat::Tcpdump => "txt",
+        }
+    }
+
+    /// Returns the format a file extension (without the dot) stands for.
+    ///
+    /// Matching is case-insensitive, `yml` is accepted for YAML and `txt`
+    /// stands for the tcpdump text format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::DebugFormat;
+    ///
+    /// assert_eq!(DebugFormat::from_extension("PCAP"), Some(DebugFormat::Pcap));
+    /// assert_eq!(DebugFormat::from_extension("yml"), Some(DebugFormat::Yaml));
+    /// assert_eq!(DebugFormat::from_extension("txt"), Some(DebugFormat::Tcpdump));
+    /// assert_eq!(DebugFormat::from_extension("bin"), None);
+    /// ```
+    pub fn from_extension(ext: &str) -> Option<DebugFormat> {
+        match ext.to_ascii_lowercase().as_str() {
+            "json" => Some(DebugFormat::Json),
+            "pcap" => Some(DebugFormat::Pcap),
+            "yaml" | "yml" => Some(DebugFormat::Yaml),
+            "txt" => Some(DebugFormat::Tcpdump),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the names of all supported Layer 4 protocols.
+///
+/// The list is derived from [`L4Protocol`], so it always matches the values
+/// accepted by `--l4_protocol`.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::supported_l4_protocols;
+///
+/// assert!(supported_l4_protocols().contains(&"tcp"));
+/// ```
+pub fn supported_l4_protocols() -> &'static [&'static str] {
+    static NAMES: OnceLock<Vec<&'static str>> = OnceLock::new();
+    NAMES.get_or_init(|| L4Protocol::value_variants().iter().map(L4Protocol::name).collect())
+}
+
+/// Returns the names of all supported debug output formats.
+///
+/// The list is derived from [`DebugFormat`], so it always matches the values
+/// accepted by `--debug_format`.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::supported_debug_formats;
+///
+/// assert!(supported_debug_formats().contains(&"pcap"));
+/// ```
+pub fn supported_debug_formats() -> &'static [&'static str] {
+    static NAMES: OnceLock<Vec<&'static str>> = OnceLock::new();
+    NAMES.get_or_init(|| DebugFormat::value_variants().iter().map(DebugFormat::name).collect())
+}
+
+/// Implementation used to compute IPv4 and TCP/UDP checksums.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ChecksumAlgo {
+    /// The checksum helpers shipped with `pnet`
+    Pnet,
+    /// The crate's own RFC 1071 implementation, see [`packet::internet_checksum`]
+    Manual,
+}
+
+/// How the UDP checksum is filled in over IPv4, where it is optional.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum UdpChecksumMode {
+    /// Send the computed value, leaving a computed 0 as 0 ("no checksum")
+    Compute,
+    /// Send 0, meaning the sender computed no checksum
+    Zero,
+    /// Always send a real checksum, transmitting a computed 0 as 0xFFFF
+    Force,
+}
+
+/// OS-specific TCP option layouts for SYN segments.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum TcpOptionOrder {
+    /// MSS, SACK permitted, Timestamps, NOP, Window Scale 7
+    Linux,
+    /// MSS, NOP, Window Scale 8, NOP, NOP, SACK permitted
+    Windows,
+    /// MSS, NOP, Window Scale 6, NOP, NOP, Timestamps, SACK permitted, EOL
+    Macos,
+}
+
+impl TcpOptionOrder {
+    /// Returns the preset matching an `--os_profile` name, if there is one.
+    pub fn for_os(os: &str) -> Option<Self> {
+        match os.trim().to_lowercase().as_str() {
+            "linux" => Some(TcpOptionOrder::Linux),
+            "windows" => Some(TcpOptionOrder::Windows),
+            "macos" => Some(TcpOptionOrder::Macos),
+            _ => None,
+        }
+    }
+}
+
+/// Micro-benchmarks selectable with `--bench_mode`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum BenchMode {
+    /// Compare borrowed `build_packet` with owned `build_packet_owned`
+    Alloc,
+}
+
+/// Multi-packet conversation presets.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Conversation {
+    /// TCP three-way handshake: SYN, SYN-ACK (reply direction), ACK
+    #[value(name = "syn_synack_ack")]
+    SynSynackAck,
+}
+
+/// IPv6 extension headers that can follow the base IPv6 header.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Ipv6ExtHeader {
+    /// Hop-by-Hop Options header padded with a single PadN option
+    #[value(name = "hopbyhop")]
+    HopByHop,
+    /// Fragment header for an atomic fragment (offset 0, More Fragments clear)
+    Fragment,
+}
+
+/// Bytes given as one hex string on the command line, e.g. `--trailer_hex`.
+///
+/// Wrapping the bytes keeps clap from treating the argument as a list.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HexBytes(pub Vec<u8>);
+
 /// Command-line arguments for the packet crafter.
 #[derive(Parser, Debug)]
 #[command(about = "Packet Crafter")]
 pub struct Args {
+    /// TOML file with default argument values and named [profiles.<name>] sections.
+    #[arg(long = "config")]
+    pub config: Option<String>,
+
+    /// Profile from --config to apply; command-line arguments override its values.
+    #[arg(long = "profile")]
+    pub profile: Option<String>,
+
+    /// YAML packet spec with ethernet/ip/l4/payload sections; command-line arguments override its values.
+    #[arg(long = "spec")]
+    pub spec: Option<String>,
+
     /// Source IPv4 address to place in the IP header.
     #[arg(long = "src_ip", default_value = "192.168.0.1")]
     pub src_ip: Ipv4Addr,
 
+    /// Source IPv4 address for the TCP/UDP pseudo-header only, producing a checksum that fails validation.
+    #[arg(long = "pseudo_src_ip")]
+    pub pseudo_src_ip: Option<Ipv4Addr>,
+
     /// Destination IPv4 address to place in the IP header.
     #[arg(long = "dst_ip", default_value = "192.168.0.254")]
     pub dst_ip: Ipv4Addr,
@@ -41,18 +242,39 @@ pub struct Args {
     #[arg(long = "dest_port", default_value_t = 80)]
     pub dest_port: u16,
 
-    /// Source MAC address to use at the Ethernet layer.
-    #[arg(long = "src_mac", value_parser = parsing::parse_mac, default_value = "aa:bb:cc:dd:ee:ff")]
+    /// Source port number for Layer 4 (TCP/UDP).
+    #[arg(long = "src_port", default_value_t = 12345)]
+    pub src_port: u16,
+
+    /// Use this port as both source and destination port, e.g. 53 -> 53 for reflection tests.
+    #[arg(long = "reflect_port", conflicts_with_all = ["src_port", "dest_port", "flow"])]
+    pub reflect_port: Option<u16>,
+
+    /// Source MAC address to use at the Ethernet layer ("random" or "random:<oui>" to randomize).
+    #[arg(long = "src_mac", value_parser = parsing::parse_mac_spec, default_value = "aa:bb:cc:dd:ee:ff")]
     pub src_mac: [u8; 6],
 
     /// Destination MAC address to use at the Ethernet layer.
     #[arg(long = "dst_mac", value_parser = parsing::parse_mac, default_value = "11:22:33:44:55:66")]
     pub dst_mac: [u8; 6],
 
-    /// Layer 4 protocol to use for the probe (TCP or UDP).
+    /// Whether --dst_mac was given on the command line or in a config file
+    /// rather than defaulted; set by [`config::parse_args`].
+    #[arg(skip)]
+    pub dst_mac_given: bool,
+
+    /// Layer 4 protocol to use for the probe (TCP, UDP or ICMP).
     #[arg(long = "l4_protocol", value_enum, default_value_t = L4Protocol::Udp)]
     pub l4_protocol: L4Protocol,
 
+    /// ICMP echo identifier (defaults to --src_port).
+    #[arg(long = "icmp_id")]
+    pub icmp_id: Option<u16>,
+
+    /// ICMP echo sequence number of the first packet; incremented for each packet built.
+    #[arg(long = "icmp_seq", default_value_t = 1)]
+    pub icmp_seq: u16,
+
     /// Timeout in milliseconds between probe/retry attempts.
     #[arg(long = "timeout_ms", default_value_t = 1000)]
     pub timeout_ms: u64,
@@ -61,33 +283,591 @@ pub struct Args {
     #[arg(long = "debug_file")]
     pub debug_file: Option<String>,
 
-    /// Format for debug output file (json or pcap).
+    /// Format for debug output file (json, pcap, yaml or tcpdump).
     #[arg(long = "debug_format", value_enum)]
     pub debug_format: Option<DebugFormat>,
 
+    /// Infer --debug_format from the --debug_file extension (.json, .pcap, .yaml, .yml or .txt).
+    #[arg(long = "auto_format")]
+    pub auto_format: bool,
+
+    /// Extra output for the built packets (repeatable): pcap:<path>, json:<path>, yaml:<path>, stdout:hex or send:<iface>.
+    #[arg(long = "sink", value_parser = parsing::parse_sink)]
+    pub sinks: Vec<output::Sink>,
+
+    /// Skip writing the debug file if its packets match the hash stored in <debug_file>.hash.
+    #[arg(long = "write_if_changed")]
+    pub write_if_changed: bool,
+
+    /// Also write <debug_file>.sha256 with the hex SHA-256 of each raw packet.
+    #[arg(long = "with_digest")]
+    pub with_digest: bool,
+
+    /// Also write <debug_file>.idx with each packet's byte offset and length in the PCAP.
+    #[arg(long = "index")]
+    pub index: bool,
+
+    /// Write the debug file to a temporary file and rename it into place on success.
+    #[arg(long = "atomic_write")]
+    pub atomic_write: bool,
+
+    /// EtherType (hex, e.g. 0x86dd) written in the Ethernet header while the payload stays IPv4.
+    #[arg(long = "ethertype_override", value_parser = parsing::parse_ethertype)]
+    pub ethertype_override: Option<u16>,
+
     /// Raw 8-bit value to OR into the IPv4 header flags/bitfield.
     #[arg(long = "ip_bitfield", value_parser = parsing::parse_bitfield, default_value = "0")]
     pub ip_bitfield: u8,
+
+    /// Source IPv6 address used for IPv6 packets.
+    #[arg(long = "src_ipv6", default_value = "fe80::1")]
+    pub src_ipv6: Ipv6Addr,
+
+    /// Destination IPv6 address of TCP/UDP packets built over IPv6 (see --ipv6_ext).
+    #[arg(long = "dst_ipv6", default_value = "fe80::2")]
+    pub dst_ipv6: Ipv6Addr,
+
+    /// 20-bit flow label placed in the IPv6 header (decimal or 0x-prefixed hex).
+    #[arg(long = "flow_label", value_parser = parsing::parse_flow_label, default_value = "0")]
+    pub flow_label: u32,
+
+    /// Target IPv6 address; builds an ICMPv6 Neighbor Solicitation for it.
+    #[arg(long = "ns_target")]
+    pub ns_target: Option<Ipv6Addr>,
+
+    /// IPv6 extension header inserted after the IPv6 header; without --ns_target, TCP/UDP packets are built over IPv6.
+    #[arg(long = "ipv6_ext", value_enum)]
+    pub ipv6_ext: Option<Ipv6ExtHeader>,
+
+    /// Send to this IPv4 multicast group: sets --dst_ip, the matching 01:00:5e MAC and TTL 1.
+    #[arg(long = "multicast")]
+    pub multicast: Option<Ipv4Addr>,
+
+    /// Build an IGMPv2 Membership Report for this multicast group, sent to its multicast MAC unless --dst_mac is given.
+    #[arg(long = "igmp_report")]
+    pub igmp_report: Option<Ipv4Addr>,
+
+    /// Build a gratuitous ARP reply announcing --src_ip (sender and target IP both --src_ip), sent to the broadcast MAC.
+    #[arg(long = "arp_gratuitous")]
+    pub arp_gratuitous: bool,
+
+    /// Build an LLDP frame (Chassis ID from --src_mac, Port ID, TTL) to 01:80:c2:00:00:0e.
+    #[arg(long = "lldp")]
+    pub lldp: bool,
+
+    /// Interface name placed in the LLDP Port ID TLV.
+    #[arg(long = "lldp_port_id", default_value = "eth0")]
+    pub lldp_port_id: String,
+
+    /// Seconds placed in the LLDP Time To Live TLV.
+    #[arg(long = "lldp_ttl", default_value_t = 120)]
+    pub lldp_ttl: u16,
+
+    /// Derive the destination MAC from the Layer 3 destination instead of --dst_mac.
+    #[arg(long = "auto_l2")]
+    pub auto_l2: bool,
+
+    /// Read payloads from stdin line by line and build one packet per line.
+    #[arg(long = "stdin_loop")]
+    pub stdin_loop: bool,
+
+    /// Read commands from stdin (`set <field> <value>`, `build`, `write <path>`, `show`, `help`, `quit`) and apply them to a live builder.
+    #[arg(long = "interactive", conflicts_with = "stdin_loop")]
+    pub interactive: bool,
+
+    /// Send the built packet on this network interface (requires raw socket privileges).
+    #[arg(long = "send")]
+    pub send: Option<String>,
+
+    /// Extra send attempts per packet after a failed write or, with --expect_reply, a timeout.
+    #[arg(long = "retries", default_value_t = 0)]
+    pub retries: u32,
+
+    /// Wait up to --timeout_ms for a reply from --dst_ip after each send and retry if none arrives.
+    #[arg(long = "expect_reply")]
+    pub expect_reply: bool,
+
+    /// TCP sequence number to place in the TCP header.
+    #[arg(long = "tcp_seq", default_value_t = 0)]
+    pub tcp_seq: u32,
+
+    /// Value of the 3 reserved bits and the NS bit (low nibble of the TCP data offset byte).
+    #[arg(long = "tcp_reserved", default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=15))]
+    pub tcp_reserved: u8,
+
+    /// Exact TCP checksum (hex, e.g. 0xbeef) written instead of the computed one.
+    #[arg(long = "tcp_checksum_override", value_parser = parsing::parse_checksum)]
+    pub tcp_checksum_override: Option<u16>,
+
+    /// Append two payload bytes chosen so the TCP/UDP checksum comes out to this hex value (e.g. 0x1234).
+    #[arg(long = "checksum_target", value_parser = parsing::parse_checksum, conflicts_with_all = ["tcp_checksum_override", "rst"])]
+    pub checksum_target: Option<u16>,
+
+    /// Build a TCP RST (RST flag only, zero window, empty payload) to reset a connection.
+    #[arg(long = "rst")]
+    pub rst: bool,
+
+    /// Initial size in bytes of the packet construction buffer (grown on demand).
+    #[arg(long = "buffer_size")]
+    pub buffer_size: Option<usize>,
+
+    /// Run a micro-benchmark instead of building packets.
+    #[arg(long = "bench_mode", value_enum)]
+    pub bench_mode: Option<BenchMode>,
+
+    /// Number of packets built per measured path in benchmark mode.
+    #[arg(long = "bench_iterations", default_value_t = 100_000)]
+    pub bench_iterations: u32,
+
+    /// Rewrite the MAC and IPv4 addresses of every packet in this PCAP to the configured ones (requires --out).
+    #[arg(long = "rewrite")]
+    pub rewrite: Option<String>,
+
+    /// Output PCAP for --rewrite.
+    #[arg(long = "out")]
+    pub out: Option<String>,
+
+    /// Print the packet after each layer is added (Ethernet, IPv4, Layer 4, payload) and exit.
+    #[arg(long = "dump_builder")]
+    pub dump_builder: bool,
+
+    /// Print diagnostics, such as warnings about checksums that computed to zero.
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+
+    /// Warn about settings that look unintentional, such as equal ports without --reflect_port.
+    #[arg(long = "strict")]
+    pub strict: bool,
+
+    /// Add a TCP MSS option derived from this path MTU (MTU - 40 for IPv4).
+    #[arg(long = "tcp_mss_from_mtu")]
+    pub tcp_mss_from_mtu: Option<u16>,
+
+    /// Payload size; each packet of a burst starts with a 32-bit big-endian counter (0, 1, 2, ...), zero-padded to this size.
+    #[arg(long = "payload_counter")]
+    pub payload_counter: Option<usize>,
+
+    /// Payload size in bytes, every byte set to --payload_pad (0 by default).
+    #[arg(long = "payload_size")]
+    pub payload_size: Option<usize>,
+
+    /// Byte value (decimal or 0x-prefixed hex) filling a --payload_size payload.
+    #[arg(long = "payload_pad", value_parser = parsing::parse_bitfield)]
+    pub payload_pad: Option<u8>,
+
+    /// Build the packet once as TCP and once as UDP, with otherwise identical parameters.
+    #[arg(long = "both_protocols")]
+    pub both_protocols: bool,
+
+    /// Build one packet per payload size from start to end by step, e.g. "100:1500:100".
+    #[arg(long = "size_sweep", value_parser = parsing::parse_size_sweep)]
+    pub size_sweep: Option<parsing::SizeSweep>,
+
+    /// Keep producing packets (sending them with --send) for this many milliseconds, then report the count.
+    #[arg(long = "duration_ms")]
+    pub duration_ms: Option<u64>,
+
+    /// Spacing between packets in --duration_ms mode, in milliseconds.
+    #[arg(long = "interval_ms", default_value_t = 0)]
+    pub interval_ms: u64,
+
+    /// Number of packets to build in a burst.
+    #[arg(long = "count", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub count: u32,
+
+    /// Print a histogram of frame sizes (64-byte buckets) after building.
+    #[arg(long = "length_histogram")]
+    pub length_histogram: bool,
+
+    /// Read the packet from an xxd/tcpdump -xx hex dump file instead of building it.
+    #[arg(long = "from_hexdump")]
+    pub from_hexdump: Option<String>,
+
+    /// Read the packet from a hex template with {{placeholders}} filled in from the other arguments.
+    #[arg(long = "template")]
+    pub template: Option<String>,
+
+    /// Insert the IPv4 Router Alert option (type 148), as used by IGMP and RSVP.
+    #[arg(long = "router_alert")]
+    pub router_alert: bool,
+
+    /// Insert an IPv4 Timestamp option (type 68, flag 3) asking these routers for timestamps (comma-separated, up to 4).
+    #[arg(long = "ip_timestamp_addrs", value_delimiter = ',')]
+    pub ip_timestamp_addrs: Vec<Ipv4Addr>,
+
+    /// Link speed used to estimate burst transmission time (e.g. 1Gbps, 100Mbps).
+    #[arg(long = "link_speed", value_parser = parsing::parse_link_speed, default_value = "1Gbps")]
+    pub link_speed: u64,
+
+    /// Pad frames shorter than 60 bytes and reject frames larger than the MTU allows.
+    #[arg(long = "enforce_frame_bounds")]
+    pub enforce_frame_bounds: bool,
+
+    /// Maximum IP packet size in bytes used for frame size checks.
+    #[arg(long = "mtu", default_value_t = 1500)]
+    pub mtu: usize,
+
+    /// Use the MTU of the --send interface (from /sys/class/net) instead of --mtu.
+    #[arg(long = "interface_mtu")]
+    pub interface_mtu: bool,
+
+    /// Build an ICMP echo request with this many payload bytes and Don't Fragment set, for path MTU discovery.
+    #[arg(long = "pmtu_probe")]
+    pub pmtu_probe: Option<usize>,
+
+    /// Wrap the IPv4 packet in a PPPoE session header with this session ID (EtherType 0x8864, PPP protocol 0x0021).
+    #[arg(long = "pppoe_session")]
+    pub pppoe_session: Option<u16>,
+
+    /// Tag IPv4 packets with this 802.1Q VLAN ID (EtherType 0x8100); the IPv4 header starts 4 bytes later.
+    #[arg(long = "vlan_id", value_parser = clap::value_parser!(u16).range(0..=4095))]
+    pub vlan_id: Option<u16>,
+
+    /// IPv4 DSCP codepoint (0-63), e.g. 46 for Expedited Forwarding.
+    #[arg(long = "dscp", value_parser = clap::value_parser!(u8).range(0..=63))]
+    pub dscp: Option<u8>,
+
+    /// Set the VLAN tag's 802.1p priority (PCP) from the DSCP class, e.g. DSCP 46 (EF) gives PCP 5.
+    #[arg(long = "map_dscp_to_pcp", requires_all = ["vlan_id", "dscp"])]
+    pub map_dscp_to_pcp: bool,
+
+    /// Raw IPv4 version/IHL byte (decimal or 0x-prefixed hex, normally 0x45) written after the header is built.
+    #[arg(long = "ipv4_vihl", value_parser = parsing::parse_bitfield)]
+    pub ipv4_vihl: Option<u8>,
+
+    /// Mark packets Congestion Experienced: IPv4 ECN = 0b11, as set by congested routers.
+    #[arg(long = "mark_ce")]
+    pub mark_ce: bool,
+
+    /// Set the IPv4 Don't Fragment bit on packets at or near --mtu.
+    #[arg(long = "auto_df")]
+    pub auto_df: bool,
+
+    /// Allow jumbo frames (up to a 9000-byte MTU) when enforcing frame bounds.
+    #[arg(long = "allow_jumbo")]
+    pub allow_jumbo: bool,
+
+    /// Build a DHCP DISCOVER (UDP 68 -> 67, broadcast addresses) with --src_mac as the client.
+    #[arg(long = "dhcp_discover")]
+    pub dhcp_discover: bool,
+
+    /// Generate a multi-packet conversation preset instead of a single packet.
+    #[arg(long = "conversation", value_enum)]
+    pub conversation: Option<Conversation>,
+
+    /// Flow 5-tuple, e.g. "tcp 10.0.0.1:1234 -> 8.8.8.8:443"; overrides protocol, IPs and ports.
+    #[arg(long = "flow", value_parser = parsing::parse_flow)]
+    pub flow: Option<parsing::FlowSpec>,
+
+    /// Trace the path to --dst_ip with increasing TTL probes (requires --send).
+    #[arg(long = "traceroute")]
+    pub traceroute: bool,
+
+    /// Highest TTL probed in traceroute mode.
+    #[arg(long = "max_hops", default_value_t = 30)]
+    pub max_hops: u8,
+
+    /// Print the JSON Schema of the detailed JSON output and exit.
+    #[arg(long = "json_schema")]
+    pub json_schema: bool,
+
+    /// Check the arguments, print OK or FAIL and exit without building anything.
+    #[arg(long = "validate_only")]
+    pub validate_only: bool,
+
+    /// JSON file mapping field paths (e.g. "ipv4.ttl", "tcp.window") to values applied after building.
+    #[arg(long = "overrides")]
+    pub overrides: Option<String>,
+
+    /// Apply --overrides without recomputing any checksum, so changed headers keep their checksums as built.
+    #[arg(long = "raw_overrides")]
+    pub raw_overrides: bool,
+
+    /// Byte-swap this multi-byte header field after building (repeatable), e.g. "tcp.window".
+    #[arg(long = "endian_swap")]
+    pub endian_swap: Vec<String>,
+
+    /// Recompute only the IPv4 header checksum after --overrides and --endian_swap; TCP/UDP checksums are left as they are.
+    #[arg(long = "refix_ip_checksum")]
+    pub refix_ip_checksum: bool,
+
+    /// Cut every built frame to its first N bytes, possibly mid-header, to test how parsers handle short packets.
+    #[arg(long = "truncate_at", conflicts_with_all = ["overrides", "raw_overrides", "endian_swap", "refix_ip_checksum", "fuzz", "enforce_frame_bounds"])]
+    pub truncate_at: Option<usize>,
+
+    /// Randomize these header fields (comma-separated, e.g. "ipv4.ttl,tcp.window"), keeping lengths and checksums valid.
+    #[arg(long = "fuzz", value_delimiter = ',')]
+    pub fuzz: Vec<String>,
+
+    /// Number of fuzzed packets generated with --fuzz.
+    #[arg(long = "fuzz_count", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub fuzz_count: u32,
+
+    /// Seed for --fuzz; the same seed reproduces the same packets.
+    #[arg(long = "seed", default_value_t = 0)]
+    pub seed: u64,
+
+    /// Hex bytes appended after the L4 payload, counted in the IPv4 total length only.
+    #[arg(long = "trailer_hex", value_parser = parsing::parse_hex_arg)]
+    pub trailer_hex: Option<HexBytes>,
+
+    /// UDP checksum handling: compute, zero (no checksum) or force (never 0).
+    #[arg(long = "udp_checksum", value_enum, default_value_t = UdpChecksumMode::Force)]
+    pub udp_checksum: UdpChecksumMode,
+
+    /// Checksum implementation (pnet or manual); both must produce identical packets.
+    #[arg(long = "checksum_algo", value_enum, default_value_t = ChecksumAlgo::Pnet, hide = true)]
+    pub checksum_algo: ChecksumAlgo,
+
+    /// Lay out SYN options in this OS's order (defaults to the --os_profile OS, if known).
+    #[arg(long = "option_order", value_enum)]
+    pub option_order: Option<TcpOptionOrder>,
+
+    /// Use the initial IPv4 TTL of this OS (linux, macos, freebsd, windows, cisco, solaris).
+    #[arg(long = "os_profile")]
+    pub os_profile: Option<String>,
+}
+
+/// Returns the arguments a bare invocation would produce (all defaults).
+impl Default for Args {
+    fn default() -> Self {
+        Args::parse_from(["packet_crafter"])
+    }
 }
 
 impl Args {
+    /// Copies the `--flow` 5-tuple or the `--reflect_port`, if any, into the
+    /// individual fields.
+    ///
+    /// Called once after parsing so the rest of the program only needs to
+    /// look at `l4_protocol`, `src_ip`, `src_port`, `dst_ip` and `dest_port`.
+    pub fn apply_flow(&mut self) {
+        if let Some(flow) = &self.flow {
+            self.l4_protocol = flow.protocol.clone();
+            self.src_ip = flow.src_ip;
+            self.src_port = flow.src_port;
+            self.dst_ip = flow.dst_ip;
+            self.dest_port = flow.dst_port;
+        }
+        if let Some(port) = self.reflect_port {
+            self.src_port = port;
+            self.dest_port = port;
+        }
+    }
+
+    /// Returns the `--strict` warnings for settings that are valid but
+    /// probably unintended.
+    ///
+    /// Currently this flags a source port equal to the destination port
+    /// when `--reflect_port` was not used to ask for it.
+    pub fn strict_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.reflect_port.is_none() && self.src_port == self.dest_port {
+            warnings.push(format!(
+                "source and destination port are both {}; use --reflect_port if this is intended",
+                self.src_port
+            ));
+        }
+        warnings
+    }
+
+    /// Returns the payload placed after the Layer 4 header.
+    ///
+    /// This is a DHCP DISCOVER message with `--dhcp_discover`, a
+    /// `--pmtu_probe`-sized run of counting bytes (0, 1, 2, ... wrapping at
+    /// 256) for path MTU probes, `--payload_size` copies of `--payload_pad`,
+    /// otherwise the fixed probe payload.
+    pub fn payload(&self) -> Vec<u8> {
+        if self.dhcp_discover {
+            payload::dhcp::encode_discover(self.src_mac)
+        } else if let Some(size) = self.pmtu_probe {
+            (0..size).map(|i| i as u8).collect()
+        } else if let Some(size) = self.payload_size {
+            vec![self.payload_pad.unwrap_or(0); size]
+        } else {
+            b"probe packet".to_vec()
+        }
+    }
+
+    /// Returns the payload of packet `index` of a burst.
+    ///
+    /// With `--payload_counter`, this is `index` as a big-endian u32
+    /// followed by zeros up to the requested size, so receivers can spot
+    /// loss and reordering. Otherwise every packet gets [`Self::payload`].
+    pub fn burst_payload(&self, index: u32) -> Vec<u8> {
+        match self.payload_counter {
+            Some(size) => {
+                let mut payload = index.to_be_bytes().to_vec();
+                payload.resize(size, 0);
+                payload
+            }
+            None => self.payload(),
+        }
+    }
+
+    /// Returns the debug file format to use.
+    ///
+    /// This is `--debug_format` if given. Otherwise, with `--auto_format`,
+    /// it is inferred from the `--debug_file` extension (see
+    /// [`DebugFormat::from_extension`]).
+    pub fn resolved_debug_format(&self) -> Option<DebugFormat> {
+        self.debug_format.clone().or_else(|| {
+            let file = self.debug_file.as_ref().filter(|_| self.auto_format)?;
+            let ext = Path::new(file).extension()?.to_str()?;
+            DebugFormat::from_extension(ext)
+        })
+    }
+
     /// Validates the consistency of command-line arguments.
     pub fn validate(&self) -> Result<(), String> {
-        let format = &self.debug_format;
+        if self.rst && self.l4_protocol != L4Protocol::Tcp {
+            return Err("--rst requires --l4_protocol=tcp".to_string());
+        }
+        if self.tcp_checksum_override.is_some() && self.l4_protocol != L4Protocol::Tcp {
+            return Err("--tcp_checksum_override requires --l4_protocol=tcp".to_string());
+        }
+        match (self.checksum_target, &self.l4_protocol) {
+            (Some(_), L4Protocol::Icmp) => return Err("--checksum_target requires --l4_protocol=tcp or udp".to_string()),
+            (Some(0), L4Protocol::Udp) => return Err("--checksum_target 0 means \"no checksum\" in UDP and cannot be reached".to_string()),
+            (Some(0xffff), L4Protocol::Tcp) => return Err("--checksum_target 0xffff cannot be reached in TCP (one's complement sums never fold to 0)".to_string()),
+            (Some(_), L4Protocol::Udp) if self.udp_checksum == UdpChecksumMode::Zero => {
+                return Err("--checksum_target cannot be combined with --udp_checksum=zero".to_string());
+            }
+            _ => {}
+        }
+        if self.conversation.is_some() && self.l4_protocol != L4Protocol::Tcp {
+            return Err("--conversation requires --l4_protocol=tcp".to_string());
+        }
+        if (self.expect_reply || self.retries > 0) && self.send.is_none() {
+            return Err("--expect_reply and --retries require --send=<interface>".to_string());
+        }
+        if self.interface_mtu && self.send.is_none() {
+            return Err("--interface_mtu requires --send=<interface>".to_string());
+        }
+        if self.ip_timestamp_addrs.len() > packet::MAX_TIMESTAMP_ADDRS {
+            return Err(format!(
+                "--ip_timestamp_addrs takes at most {} addresses, got {}",
+                packet::MAX_TIMESTAMP_ADDRS,
+                self.ip_timestamp_addrs.len()
+            ));
+        }
+        if let Some(field) = self.endian_swap.iter().find(|f| !overrides::SWAPPABLE_FIELDS.contains(&f.as_str())) {
+            return Err(format!(
+                "--endian_swap does not support '{}' (expected one of: {})",
+                field,
+                overrides::SWAPPABLE_FIELDS.join(", ")
+            ));
+        }
+        if let Some(field) = self.fuzz.iter().find(|f| !fuzz::FUZZABLE_FIELDS.contains(&f.as_str())) {
+            return Err(format!(
+                "--fuzz does not support '{}' (expected one of: {})",
+                field,
+                fuzz::FUZZABLE_FIELDS.join(", ")
+            ));
+        }
+        let multi_packet = self.count > 1 || self.conversation.is_some() || self.both_protocols || self.size_sweep.is_some() || self.stdin_loop;
+        if self.duration_ms.is_some() && multi_packet {
+            return Err("--duration_ms cannot be combined with --count or other multi-packet modes".to_string());
+        }
+        if !self.fuzz.is_empty() && multi_packet {
+            return Err("--fuzz varies a single packet and cannot be combined with multi-packet modes".to_string());
+        }
+        if let Some(size) = self.payload_counter {
+            if size < 4 {
+                return Err(format!("--payload_counter size must be at least 4 bytes, got {}", size));
+            }
+            if self.dhcp_discover || self.pmtu_probe.is_some() {
+                return Err("--payload_counter cannot be combined with --dhcp_discover or --pmtu_probe".to_string());
+            }
+        }
+        if self.payload_pad.is_some() && self.payload_size.is_none() {
+            return Err("--payload_pad requires --payload_size".to_string());
+        }
+        if self.payload_size.is_some() && (self.dhcp_discover || self.pmtu_probe.is_some() || self.payload_counter.is_some()) {
+            return Err("--payload_size cannot be combined with --dhcp_discover, --pmtu_probe or --payload_counter".to_string());
+        }
+        if self.lldp && !(1..=payload::lldp::MAX_PORT_ID_LENGTH).contains(&self.lldp_port_id.len()) {
+            return Err(format!(
+                "--lldp_port_id must be 1 to {} bytes long",
+                payload::lldp::MAX_PORT_ID_LENGTH
+            ));
+        }
+        if self.with_digest && self.debug_file.is_none() {
+            return Err("--with_digest requires --debug_file".to_string());
+        }
+        if self.index && self.resolved_debug_format() != Some(DebugFormat::Pcap) {
+            return Err("--index requires a PCAP --debug_file".to_string());
+        }
+        if self.pppoe_session.is_some() && (self.ns_target.is_some() || self.lldp || self.arp_gratuitous) {
+            return Err("--pppoe_session only wraps IPv4 packets and cannot be combined with --ns_target, --lldp or --arp_gratuitous".to_string());
+        }
+        if self.vlan_id.is_some() && (self.ns_target.is_some() || self.lldp || self.arp_gratuitous) {
+            return Err("--vlan_id only tags IPv4 packets and cannot be combined with --ns_target, --lldp or --arp_gratuitous".to_string());
+        }
+        if self.ipv6_ext.is_some() && self.ns_target.is_none() && (
+            self.l4_protocol == L4Protocol::Icmp
+                || self.pmtu_probe.is_some()
+                || self.igmp_report.is_some()
+                || self.lldp
+                || self.arp_gratuitous
+                || self.checksum_target.is_some()
+                || self.pppoe_session.is_some()
+                || self.vlan_id.is_some()
+        ) {
+            return Err("--ipv6_ext without --ns_target builds plain TCP or UDP over IPv6 and cannot be combined with ICMP, --pmtu_probe, --igmp_report, --lldp, --arp_gratuitous, --checksum_target, --pppoe_session or --vlan_id".to_string());
+        }
+        // The frame-level helpers find the IPv4 header by its EtherType
+        let decoded_format = |format: &DebugFormat| format != &DebugFormat::Pcap;
+        let decoded_output = self.resolved_debug_format().as_ref().is_some_and(decoded_format)
+            || self.sinks.iter().any(|sink| matches!(sink, output::Sink::File { format, .. } if decoded_format(format)));
+        if self.ethertype_override.is_some_and(|ethertype| ethertype != 0x0800)
+            && (self.overrides.is_some() || !self.endian_swap.is_empty() || self.refix_ip_checksum || decoded_output)
+        {
+            return Err("--ethertype_override hides the IPv4 header, so it cannot be combined with --overrides, --endian_swap, --refix_ip_checksum or a json, yaml or tcpdump debug file; use pcap instead".to_string());
+        }
+        if self.raw_overrides && self.overrides.is_none() {
+            return Err("--raw_overrides requires --overrides".to_string());
+        }
+        if self.rewrite.is_some() != self.out.is_some() {
+            return Err("--rewrite and --out must be used together".to_string());
+        }
+        if self.write_if_changed && self.debug_file.is_none() {
+            return Err("--write_if_changed requires --debug_file".to_string());
+        }
+        if self.both_protocols && self.count > 1 {
+            return Err("--both_protocols cannot be combined with --count".to_string());
+        }
+        if self.profile.is_some() && self.config.is_none() {
+            return Err("--profile requires --config=<file.toml>".to_string());
+        }
+        if self.traceroute && self.send.is_none() {
+            return Err("--traceroute requires --send=<interface>".to_string());
+        }
+        if let Some(group) = self.multicast {
+            if !group.is_multicast() {
+                return Err(format!("--multicast group {} is not a multicast address", group));
+            }
+        }
+        if let Some(group) = self.igmp_report {
+            if !group.is_multicast() {
+                return Err(format!("--igmp_report group {} is not a multicast address", group));
+            }
+        }
+        if self.dhcp_discover && self.l4_protocol != L4Protocol::Udp {
+            return Err("--dhcp_discover requires --l4_protocol=udp".to_string());
+        }
+        
+        let format = self.resolved_debug_format();
         let file = &self.debug_file;
 
-        match (format, file) {
+        match (&format, file) {
             (Some(fmt), Some(f)) => {
                 let path = Path::new(f);
-                let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+                let extension = path.extension().and_then(|e| e.to_str());
+                let expected = fmt.extension();
 
-                let expected = match fmt {
-                    DebugFormat::Json => "json",
-                    DebugFormat::Pcap => "pcap",
-                };
-
-                match extension.as_deref() {
-                    Some(ext) if ext == expected => Ok(()),
+                match extension {
+                    Some(ext) if DebugFormat::from_extension(ext).as_ref() == Some(fmt) => Ok(()),
                     Some(ext) => Err(format!(
                         "Debug format is '{:?}' but file has '.{}' extension. Expected '.{}'",
                         fmt, ext, expected
@@ -107,6 +887,12 @@ impl Args {
                     fmt
                 ))
             }
+            (None, Some(f)) if self.auto_format => {
+                Err(format!(
+                    "Cannot infer the debug format of '{}'. Expected a .json, .pcap, .yaml, .yml or .txt extension",
+                    f
+                ))
+            }
             (None, Some(f)) => {
                 Err(format!(
                     "Debug file '{}' specified but no debug format provided. Use --debug_format",
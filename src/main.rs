@@ -19,6 +19,9 @@
 //!
 //! # Generate debug output in JSON format
 //! cargo run -- --debug_file=./debug.json --debug_format=json
+//!
+//! # Generate the decoded packet as YAML
+//! cargo run -- --debug_file=./debug.yaml --debug_format=yaml
 //! ```
 //!
 //! ## Features
@@ -27,11 +30,23 @@
 //! - Supports custom MAC addresses for source and destination
 //! - Configurable Layer 4 protocol (TCP or UDP)
 //! - Optional dry-run mode for testing without sending packets
-//! - Debug output in PCAP or JSON format
+//! - Debug output in PCAP, JSON or YAML format
+//! - Streaming mode building one packet per stdin line
 //! - IPv4 bitfield manipulation for flags/fragmentation offset
 
-use clap::Parser;
-use packet_crafter::{Args, DebugFormat, packet::PacketBuilder, output::{write_pcap, write_json}};
+use packet_crafter::{config, Args, BenchMode, DebugFormat, packet::PacketBuilder, parsing::{parse_hexdump, expand_template}, run::{build_both_protocols, build_burst, build_from_lines, build_size_sweep, run_for_duration, run_repl, run_validate_only, SystemClock}};
+use packet_crafter::output::{write_pcap, write_json, write_yaml, write_pcap_session, write_json_session, write_yaml_session, write_tcpdump, write_atomic, write_digest_sidecar, write_if_changed, write_index, pcap_packet_offsets, render_json_schema, Sink};
+use packet_crafter::bench::compare_alloc;
+use packet_crafter::fuzz::fuzz_frames;
+use packet_crafter::overrides::{apply_overrides, apply_overrides_raw, load_overrides, refix_ipv4_checksum, swap_field_endianness};
+use packet_crafter::rewrite::rewrite_pcap;
+use packet_crafter::send::{has_raw_socket_privilege, read_interface_mtu, send_packet, send_with_retries};
+use packet_crafter::traceroute::{traceroute, DatalinkTransport};
+use std::time::Duration;
+use packet_crafter::decode::{decode_packet, L4Info};
+use packet_crafter::packet::{enforce_bounds, frames_hash, would_warn_zero_checksum};
+use packet_crafter::report::{length_histogram, wire_bytes, estimate_wire_time, HISTOGRAM_BUCKET_WIDTH};
+use std::net::Ipv4Addr;
 use std::path::Path;
 
 /// Main entry point for the packet crafter.
@@ -40,36 +55,483 @@ use std::path::Path;
 /// 1. Parses command-line arguments
 /// 2. Validates argument consistency
 /// 3. Constructs a network packet based on the provided parameters
-/// 4. Optionally writes the packet to a debug file (PCAP or JSON format)
+/// 4. Optionally writes the packet to a debug file (PCAP, JSON or YAML format)
+/// 5. Optionally sends the packet on a network interface
 ///
 /// The program exits with status code 0 on success, or non-zero on error.
 ///
 /// # Exit Codes
 ///
 /// - `0`: Success - packet was constructed and optionally written
-/// - `1`: Error - validation failed or file write failed
+/// - `1`: Error - validation failed, file write failed or sending failed
 fn main() {
-    let args = Args::parse();
+    let mut args = config::parse_args(std::env::args_os()).unwrap_or_else(|e| e.exit());
+    args.apply_flow();
+    
+    if args.json_schema {
+        match render_json_schema() {
+            Ok(schema) => println!("{}", schema),
+            Err(e) => {
+                eprintln!("Failed to render JSON schema: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    
+    if args.validate_only {
+        std::process::exit(run_validate_only(&args));
+    }
     
     if let Err(e) = args.validate() {
         eprintln!("Error: {}", e);
         std::process::exit(1);
     }
     
+    if args.strict {
+        for warning in args.strict_warnings() {
+            eprintln!("Warning: {}", warning);
+        }
+    }
+    
+    let sends = args.send.is_some() || args.sinks.iter().any(|sink| matches!(sink, Sink::Send { .. }));
+    if sends && !has_raw_socket_privilege() {
+        eprintln!("Error: sending needs raw socket privileges; run as root or grant CAP_NET_RAW (e.g. setcap cap_net_raw+ep <binary>)");
+        std::process::exit(1);
+    }
+    
+    if let (true, Some(interface)) = (args.interface_mtu, &args.send) {
+        match read_interface_mtu(interface, |p| std::fs::read_to_string(p)) {
+            Some(mtu) => args.mtu = mtu as usize,
+            None => eprintln!("Warning: could not read the MTU of '{}', using {}", interface, args.mtu),
+        }
+    }
+    
+    if let (Some(input), Some(output)) = (&args.rewrite, &args.out) {
+        match rewrite_pcap(Path::new(input), Path::new(output), &args) {
+            Ok(count) => println!("Rewrote {} packets from '{}' to '{}'", count, input, output),
+            Err(e) => {
+                eprintln!("Failed to rewrite '{}': {}", input, e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    
     let mut builder = PacketBuilder::from(&args);
-    let payload = b"probe packet";
-    let packet = builder.build_packet(payload);
-    
-    if let (Some(format), Some(file_path)) = (&args.debug_format, &args.debug_file) {
-        let path = Path::new(file_path);
-        let result = match format {
-            DebugFormat::Pcap => write_pcap(path, packet),
-            DebugFormat::Json => write_json(path, packet),
+    
+    if args.dump_builder {
+        run_dump_builder(&args, &mut builder);
+        return;
+    }
+    
+    if let Some(BenchMode::Alloc) = args.bench_mode {
+        run_alloc_bench(&args, &mut builder);
+        return;
+    }
+    
+    if let (true, Some(interface)) = (args.traceroute, &args.send) {
+        run_traceroute(&args, &mut builder, interface);
+        return;
+    }
+    
+    if let Some(duration_ms) = args.duration_ms {
+        run_timed(&args, &mut builder, duration_ms);
+        return;
+    }
+    
+    if args.interactive {
+        if let Err(e) = run_repl(&mut builder, args.payload(), std::io::stdin().lock(), &mut std::io::stdout()) {
+            eprintln!("Interactive mode failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    
+    let mut packets = if let Some(dump_path) = &args.from_hexdump {
+        let parsed = std::fs::read_to_string(dump_path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| parse_hexdump(&text));
+        match parsed {
+            Ok(packet) => vec![packet],
+            Err(e) => {
+                eprintln!("Failed to read hex dump '{}': {}", dump_path, e);
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(template_path) = &args.template {
+        let expanded = std::fs::read_to_string(template_path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| expand_template(&text, &args));
+        match expanded {
+            Ok(packet) => vec![packet],
+            Err(e) => {
+                eprintln!("Failed to expand template '{}': {}", template_path, e);
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(conversation) = args.conversation {
+        match builder.build_conversation(conversation) {
+            Ok(packets) => packets,
+            Err(e) => {
+                eprintln!("Failed to build conversation: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if args.both_protocols {
+        match build_both_protocols(&mut builder, &args.payload()) {
+            Ok(packets) => packets,
+            Err(e) => {
+                eprintln!("Failed to build packets: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(sweep) = &args.size_sweep {
+        match build_size_sweep(&mut builder, sweep) {
+            Ok(packets) => packets,
+            Err(e) => {
+                eprintln!("Failed to build size sweep: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if args.stdin_loop {
+        match build_from_lines(&mut builder, std::io::stdin().lock()) {
+            Ok(packets) => packets,
+            Err(e) => {
+                eprintln!("Failed to build packets from stdin: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        match build_burst(&mut builder, &args) {
+            Ok(packets) => packets,
+            Err(e) => {
+                eprintln!("Failed to build packet: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+    if !args.fuzz.is_empty() {
+        packets = match fuzz_frames(&packets[0], &args.fuzz, args.fuzz_count as usize, args.seed) {
+            Ok(packets) => packets,
+            Err(e) => {
+                eprintln!("Failed to fuzz packet: {}", e);
+                std::process::exit(1);
+            }
+        };
+    }
+    let session = args.stdin_loop || args.conversation.is_some() || args.both_protocols || args.size_sweep.is_some() || args.count > 1 || !args.fuzz.is_empty();
+    
+    if let Some(overrides_path) = &args.overrides {
+        let overrides = match load_overrides(Path::new(overrides_path)) {
+            Ok(overrides) => overrides,
+            Err(e) => {
+                eprintln!("Failed to read overrides '{}': {}", overrides_path, e);
+                std::process::exit(1);
+            }
+        };
+        let apply = if args.raw_overrides { apply_overrides_raw } else { apply_overrides };
+        for (index, packet) in packets.iter_mut().enumerate() {
+            if let Err(e) = apply(packet, &overrides) {
+                eprintln!("Error: packet {}: {}", index, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    for field in &args.endian_swap {
+        for (index, packet) in packets.iter_mut().enumerate() {
+            if let Err(e) = swap_field_endianness(packet, field) {
+                eprintln!("Error: packet {}: {}", index, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    if args.refix_ip_checksum {
+        for packet in packets.iter_mut() {
+            refix_ipv4_checksum(packet);
+        }
+    }
+    
+    if args.enforce_frame_bounds {
+        for (index, packet) in packets.iter_mut().enumerate() {
+            if let Err(e) = enforce_bounds(packet, args.mtu, args.allow_jumbo) {
+                eprintln!("Error: packet {}: {}", index, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    if args.verbose {
+        for (index, packet) in packets.iter().enumerate() {
+            warn_zero_checksums(index, packet);
+        }
+    }
+    
+    if args.length_histogram {
+        print_length_histogram(&packets);
+    }
+    
+    if args.count > 1 {
+        print_burst_summary(&packets, args.link_speed);
+    }
+    
+    if let (Some(format), Some(file_path)) = (args.resolved_debug_format(), &args.debug_file) {
+        let write = |path: &Path| write_debug_file(&format, path, &packets, session, args.atomic_write);
+        let result = if args.write_if_changed {
+            write_if_changed(Path::new(file_path), frames_hash(&packets), write).map(|written| {
+                if !written {
+                    println!("Debug file '{}' is up to date", file_path);
+                }
+            })
+        } else {
+            write(Path::new(file_path))
         };
-        
         if let Err(e) = result {
             eprintln!("Failed to write debug file: {}", e);
             std::process::exit(1);
         }
+        if args.with_digest {
+            if let Err(e) = write_digest_sidecar(Path::new(file_path), &packets) {
+                eprintln!("Failed to write digest: {}", e);
+                std::process::exit(1);
+            }
+        }
+        if args.index {
+            if let Err(e) = write_index(Path::new(file_path), &pcap_packet_offsets(&packets)) {
+                eprintln!("Failed to write index: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    if let Some(interface) = &args.send {
+        if args.expect_reply || args.retries > 0 {
+            send_with_outcomes(&args, builder.dst_ip(), interface, &packets);
+        } else {
+            send_all(interface, &packets);
+        }
+    }
+    
+    for sink in &args.sinks {
+        match sink {
+            Sink::File { format, path } => {
+                if let Err(e) = write_debug_file(format, Path::new(path), &packets, session, args.atomic_write) {
+                    eprintln!("Failed to write '{}': {}", path, e);
+                    std::process::exit(1);
+                }
+            }
+            Sink::StdoutHex => {
+                for packet in &packets {
+                    println!("{}", hex::encode(packet));
+                }
+            }
+            Sink::Send { interface } => send_all(interface, &packets),
+        }
+    }
+}
+
+/// Writes the packets to a debug file, as a session if there may be several.
+fn write_debug_file(
+    format: &DebugFormat,
+    path: &Path,
+    packets: &[Vec<u8>],
+    session: bool,
+    atomic: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let write = |path: &Path| match (format, packets, session) {
+        (DebugFormat::Pcap, [packet], false) => write_pcap(path, packet),
+        (DebugFormat::Json, [packet], false) => write_json(path, packet),
+        (DebugFormat::Yaml, [packet], false) => write_yaml(path, packet),
+        (DebugFormat::Pcap, _, _) => write_pcap_session(path, packets),
+        (DebugFormat::Json, _, _) => write_json_session(path, packets),
+        (DebugFormat::Yaml, _, _) => write_yaml_session(path, packets),
+        (DebugFormat::Tcpdump, _, _) => write_tcpdump(path, packets),
+    };
+    if atomic {
+        write_atomic(path, write)
+    } else {
+        write(path)
+    }
+}
+
+/// Sends every packet on `interface`, exiting on the first failure.
+fn send_all(interface: &str, packets: &[Vec<u8>]) {
+    for packet in packets {
+        if let Err(e) = send_packet(interface, packet) {
+            eprintln!("Failed to send packet on '{}': {}", interface, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Sends every packet with retries and prints one outcome line per packet.
+///
+/// Exits with status 1 if a packet could not be written at all, or if
+/// --expect_reply was given and a packet got no reply.
+fn send_with_outcomes(args: &Args, target: Ipv4Addr, interface: &str, packets: &[Vec<u8>]) {
+    let mut transport = match DatalinkTransport::open(interface, target) {
+        Ok(transport) => transport,
+        Err(e) => {
+            eprintln!("Failed to open '{}': {}", interface, e);
+            std::process::exit(1);
+        }
+    };
+    let timeout = Duration::from_millis(args.timeout_ms);
+    
+    let mut missing_reply = false;
+    for (index, packet) in packets.iter().enumerate() {
+        match send_with_retries(&mut transport, packet, args.retries, args.expect_reply, timeout) {
+            Ok(outcome) => {
+                println!(
+                    "packet {}: attempts={} bytes_sent={} reply_seen={} elapsed_ms={}",
+                    index,
+                    outcome.attempts,
+                    outcome.bytes_sent,
+                    outcome.reply_seen,
+                    outcome.elapsed.as_millis()
+                );
+                missing_reply |= args.expect_reply && !outcome.reply_seen;
+            }
+            Err(e) => {
+                eprintln!("Failed to send packet on '{}': {}", interface, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if missing_reply {
+        std::process::exit(1);
+    }
+}
+
+/// Prints a warning if the packet's IPv4 or TCP/UDP checksum is zero.
+fn warn_zero_checksums(index: usize, packet: &[u8]) {
+    let Ok(decoded) = decode_packet(packet) else {
+        return;
+    };
+    let Some(ipv4) = decoded.ipv4 else {
+        return;
+    };
+    let l4_checksum = match decoded.l4 {
+        Some(L4Info::Tcp { checksum, .. }) | Some(L4Info::Udp { checksum, .. }) => checksum,
+        None => return,
+    };
+    
+    if would_warn_zero_checksum(ipv4.checksum, l4_checksum) {
+        eprintln!(
+            "Warning: packet {} has a zero checksum (IPv4 0x{:04x}, L4 0x{:04x}); some tools treat 0 as \"no checksum\"",
+            index, ipv4.checksum, l4_checksum
+        );
+    }
+}
+
+/// Prints the frame-size histogram of a burst, one line per non-empty bucket.
+fn print_length_histogram(packets: &[Vec<u8>]) {
+    println!("Frame length histogram ({} packets):", packets.len());
+    for (bucket, count) in length_histogram(packets) {
+        let low = bucket * HISTOGRAM_BUCKET_WIDTH;
+        let high = low + HISTOGRAM_BUCKET_WIDTH - 1;
+        println!("  {:>5}-{:<5} {:>6}", low, high, count);
+    }
+}
+
+/// Prints frame and byte totals for a burst with its estimated wire time.
+fn print_burst_summary(packets: &[Vec<u8>], link_bps: u64) {
+    let frame_bytes: usize = packets.iter().map(|p| p.len()).sum();
+    let on_wire: usize = packets.iter().map(|p| wire_bytes(p.len())).sum();
+    let duration = estimate_wire_time(on_wire as u64 * 8, link_bps);
+    
+    println!("Burst summary:");
+    println!("  frames:        {}", packets.len());
+    println!("  frame bytes:   {}", frame_bytes);
+    println!("  wire bytes:    {} (incl. padding, FCS, preamble, IFG)", on_wire);
+    println!("  wire time:     {:?} at {} bps", duration, link_bps);
+}
+
+/// Prints the packet as it grows, one hex line per added layer.
+fn run_dump_builder(args: &Args, builder: &mut PacketBuilder) {
+    match builder.build_with_snapshots(&args.payload()) {
+        Ok(snapshots) => {
+            let stages = ["Ethernet", "+ IPv4", "+ Layer 4", "+ payload"];
+            for (stage, snapshot) in stages.iter().zip(&snapshots) {
+                println!("{:<10} ({:>4} bytes): {}", stage, snapshot.len(), hex::encode(snapshot));
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to build packet: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs traceroute mode and prints one line per hop.
+fn run_traceroute(args: &Args, builder: &mut PacketBuilder, interface: &str) {
+    let target = builder.dst_ip();
+    let result = DatalinkTransport::open(interface, target).and_then(|mut transport| {
+        traceroute(
+            builder,
+            &mut transport,
+            &args.payload(),
+            args.max_hops,
+            Duration::from_millis(args.timeout_ms),
+        )
+    });
+    
+    match result {
+        Ok(path) => {
+            println!("traceroute to {}, {} hops max", target, args.max_hops);
+            for hop in path {
+                match hop.responder {
+                    Some(addr) => println!("{:>3}  {}", hop.ttl, addr),
+                    None => println!("{:>3}  *", hop.ttl),
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Traceroute failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Produces packets for `duration_ms` milliseconds at `--interval_ms` spacing,
+/// sending each one with `--send`, then prints how many were produced.
+fn run_timed(args: &Args, builder: &mut PacketBuilder, duration_ms: u64) {
+    let result = run_for_duration(
+        &mut SystemClock::start(),
+        Duration::from_millis(duration_ms),
+        Duration::from_millis(args.interval_ms),
+        |index| -> Result<(), Box<dyn std::error::Error>> {
+            let packet = builder.build_packet(&args.burst_payload(index as u32))?;
+            if let Some(interface) = &args.send {
+                send_packet(interface, packet)?;
+            }
+            Ok(())
+        },
+    );
+    
+    match result {
+        Ok(produced) => println!("Produced {} packets in {} ms", produced, duration_ms),
+        Err(e) => {
+            eprintln!("Timed run failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs the `--bench_mode alloc` comparison and prints the timings.
+fn run_alloc_bench(args: &Args, builder: &mut PacketBuilder) {
+    match compare_alloc(builder, &args.payload(), args.bench_iterations) {
+        Ok(report) => {
+            println!("Allocation benchmark ({} packets per path):", report.iterations);
+            println!("  borrowed (build_packet):       {:?}", report.borrowed);
+            println!("  owned (build_packet_owned):    {:?}", report.owned);
+            println!("  owned overhead:                {:.1} ns/packet", report.overhead_per_packet_ns());
+        }
+        Err(e) => {
+            eprintln!("Benchmark failed: {}", e);
+            std::process::exit(1);
+        }
     }
 }
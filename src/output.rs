@@ -1,28 +1,102 @@
 //! Output formatting and file writing for debug modes.
 //!
 //! This module provides functions to write constructed packets to files
-//! in two formats: PCAP (for Wireshark analysis) and JSON (for structured
-//! inspection).
+//! in four formats: PCAP (for Wireshark analysis), JSON (for structured
+//! inspection), YAML (for human review) and tcpdump-style text (for
+//! diffing against a capture), plus a compact binary bundle for storing
+//! many packets with no metadata.
 
 use pcap_file::pcap::{PcapHeader, PcapWriter, PcapPacket};
+use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Structured packet information for JSON output.
+use crate::DebugFormat;
+use crate::decode::{decode_packet, EthernetInfo, Ipv4Info, L4Info};
+
+/// A destination for built packets, as given with `--sink`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sink {
+    /// Write a debug file in the given format
+    File {
+        /// Output format
+        format: DebugFormat,
+        /// Path of the file to create
+        path: String,
+    },
+    /// Print each packet to stdout as one line of hex
+    StdoutHex,
+    /// Send the packets on a network interface
+    Send {
+        /// Interface name
+        interface: String,
+    },
+}
+
+/// Structured packet information for JSON and YAML output.
 ///
-/// Contains metadata and hex-encoded packet data suitable for
-/// machine-readable inspection and debugging.
-#[derive(Serialize, Deserialize, Debug)]
-struct PacketInfo {
+/// Contains metadata, hex-encoded packet data and the decoded headers,
+/// suitable for machine-readable inspection and debugging.
+///
+/// Fields are serialized in declaration order. Any map-valued field added
+/// here must use an ordered map (`BTreeMap` or `serde_json::Map`, which is
+/// `BTreeMap`-backed without the `preserve_order` feature) so the rendered
+/// output stays byte-stable for snapshot testing.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct PacketInfo {
+    /// 0-based position of the packet in a multi-packet session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
     /// Unix timestamp (seconds since epoch) as floating point
-    timestamp: f64,
+    pub timestamp: f64,
     /// Total packet length in bytes
-    length: usize,
+    pub length: usize,
     /// Hex-encoded packet data (e.g., "aabbccdd...")
-    data: String,
+    pub data: String,
+    /// Decoded Ethernet header, if the frame could be decoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ethernet: Option<EthernetInfo>,
+    /// Decoded IPv4 header, if present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipv4: Option<Ipv4Info>,
+    /// Decoded TCP/UDP header, if present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l4: Option<L4Info>,
+}
+
+impl PacketInfo {
+    /// Builds the record for `packet`, decoding its headers where possible.
+    ///
+    /// Frames that cannot be decoded still produce a record; only the
+    /// decoded header fields are left empty.
+    pub fn new(packet: &[u8], timestamp: f64) -> Self {
+        let decoded = decode_packet(packet).ok();
+        Self {
+            index: None,
+            timestamp,
+            length: packet.len(),
+            data: hex::encode(packet),
+            ethernet: decoded.as_ref().map(|d| d.ethernet.clone()),
+            ipv4: decoded.as_ref().and_then(|d| d.ipv4.clone()),
+            l4: decoded.and_then(|d| d.l4),
+        }
+    }
+}
+
+/// Builds the records of a multi-packet session, numbered from 0.
+fn session_records(packets: &[Vec<u8>], timestamp: f64) -> Vec<PacketInfo> {
+    packets
+        .iter()
+        .enumerate()
+        .map(|(index, packet)| PacketInfo {
+            index: Some(index),
+            ..PacketInfo::new(packet, timestamp)
+        })
+        .collect()
 }
 
 /// Writes a packet to a PCAP file.
@@ -81,12 +155,323 @@ pub fn write_pcap(path: &Path, packet: &[u8]) -> Result<(), Box<dyn std::error::
     Ok(())
 }
 
+/// Writes several packets to a single PCAP file.
+///
+/// Produces the same file layout as [`write_pcap`], with one record per
+/// packet in the order given. Records share the capture timestamp.
+///
+/// # Arguments
+///
+/// * `path` - The file path where the PCAP file will be created
+/// * `packets` - The complete packets (Ethernet frames) to record
+///
+/// # Returns
+///
+/// - `Ok(())` on success
+/// - `Err(Box<dyn std::error::Error>)` if file creation or writing fails
+pub fn write_pcap_session(path: &Path, packets: &[Vec<u8>]) -> Result<(), Box<dyn std::error::Error>> {
+    let file = File::create(path)?;
+    
+    let pcap_header = PcapHeader {
+        datalink: pcap_file::DataLink::ETHERNET,
+        endianness: pcap_file::Endianness::Big,
+        ..Default::default()
+    };
+    
+    let mut pcap_writer = PcapWriter::with_header(file, pcap_header)?;
+    
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap();
+    
+    for packet in packets {
+        let pcap_packet = PcapPacket {
+            timestamp: now,
+            orig_len: packet.len() as u32,
+            data: packet.as_slice().into(),
+        };
+        pcap_writer.write_packet(&pcap_packet)?;
+    }
+    
+    Ok(())
+}
+
+/// Size of the PCAP global header, in bytes.
+pub const PCAP_GLOBAL_HEADER_LENGTH: usize = 24;
+
+/// Size of the header preceding each PCAP packet record, in bytes.
+pub const PCAP_RECORD_HEADER_LENGTH: usize = 16;
+
+/// Estimates how many `frame_len`-byte packets fit in a PCAP file of at
+/// most `budget_bytes`.
+///
+/// Accounts for the 24-byte global header and the 16-byte record header
+/// written before each packet, so the result can be passed to `--count`
+/// to stay under a file-size cap. A budget smaller than the global header
+/// fits no packets.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::output::estimate_packet_count;
+///
+/// // 24 + 10 * (16 + 42) = 604 bytes
+/// assert_eq!(estimate_packet_count(42, 604), 10);
+/// assert_eq!(estimate_packet_count(42, 603), 9);
+/// ```
+pub fn estimate_packet_count(frame_len: usize, budget_bytes: usize) -> usize {
+    budget_bytes.saturating_sub(PCAP_GLOBAL_HEADER_LENGTH) / (PCAP_RECORD_HEADER_LENGTH + frame_len)
+}
+
+/// Returns the lowercase hex SHA-256 of `bytes`.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::output::sha256_hex;
+///
+/// assert_eq!(
+///     sha256_hex(b"abc"),
+///     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+/// );
+/// ```
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Returns the path of the digest sidecar for `path`: `<path>.sha256`.
+pub fn digest_sidecar_path(path: &Path) -> std::path::PathBuf {
+    sidecar_path(path, ".sha256")
+}
+
+/// Returns the path of the hash sidecar used by [`write_if_changed`]: `<path>.hash`.
+pub fn hash_sidecar_path(path: &Path) -> std::path::PathBuf {
+    sidecar_path(path, ".hash")
+}
+
+/// Appends `suffix` to the file name of `path`.
+fn sidecar_path(path: &Path, suffix: &str) -> std::path::PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    name.into()
+}
+
+/// Writes a file only if the packets it holds changed since the last write.
+///
+/// `hash` identifies the packets, e.g. from
+/// [`PacketBuilder::build_and_hash`](crate::packet::PacketBuilder::build_and_hash)
+/// or [`frames_hash`](crate::packet::frames_hash). If `path` exists and its
+/// `<path>.hash` sidecar holds the same hash, nothing is written, so
+/// timestamps in the output do not churn files kept in version control.
+/// Otherwise `write` is called and the sidecar is updated.
+///
+/// # Arguments
+///
+/// * `path` - The file to write
+/// * `hash` - Hash of the packets to be written
+/// * `write` - Writes the file at the path it is given
+///
+/// # Returns
+///
+/// - `Ok(true)` if the file was written, `Ok(false)` if it was up to date
+/// - `Err(Box<dyn std::error::Error>)` if `write` or the sidecar write fails
+pub fn write_if_changed<F>(path: &Path, hash: u64, write: F) -> Result<bool, Box<dyn std::error::Error>>
+where
+    F: FnOnce(&Path) -> Result<(), Box<dyn std::error::Error>>,
+{
+    let sidecar = hash_sidecar_path(path);
+    let hash = format!("{:016x}", hash);
+    let stored = std::fs::read_to_string(&sidecar).unwrap_or_default();
+    if path.exists() && stored.trim() == hash {
+        return Ok(false);
+    }
+    
+    write(path)?;
+    std::fs::write(&sidecar, format!("{}\n", hash))?;
+    Ok(true)
+}
+
+/// Writes a `<path>.sha256` sidecar next to a debug file.
+///
+/// The sidecar holds one line per packet with the hex SHA-256 of the raw
+/// packet bytes (not of the debug file), in the order given, so the packets
+/// can be checked independently of the output format.
+///
+/// # Arguments
+///
+/// * `path` - The debug file the sidecar belongs to
+/// * `packets` - The complete packets written to it
+///
+/// # Returns
+///
+/// - `Ok(())` on success
+/// - `Err(Box<dyn std::error::Error>)` if file creation or writing fails
+pub fn write_digest_sidecar(path: &Path, packets: &[Vec<u8>]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = File::create(digest_sidecar_path(path))?;
+    for packet in packets {
+        writeln!(file, "{}", sha256_hex(packet))?;
+    }
+    Ok(())
+}
+
+/// Returns the path of the capture index for `path`: `<path>.idx`.
+pub fn index_sidecar_path(path: &Path) -> std::path::PathBuf {
+    sidecar_path(path, ".idx")
+}
+
+/// Returns where each packet's bytes start in a PCAP written by
+/// [`write_pcap`] or [`write_pcap_session`], with the packet length.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::output::pcap_packet_offsets;
+///
+/// let packets = vec![vec![0u8; 42], vec![0u8; 60]];
+/// assert_eq!(pcap_packet_offsets(&packets), [(40, 42), (98, 60)]);
+/// ```
+pub fn pcap_packet_offsets(packets: &[Vec<u8>]) -> Vec<(u64, usize)> {
+    let mut offset = PCAP_GLOBAL_HEADER_LENGTH as u64;
+    packets
+        .iter()
+        .map(|packet| {
+            let data_offset = offset + PCAP_RECORD_HEADER_LENGTH as u64;
+            offset = data_offset + packet.len() as u64;
+            (data_offset, packet.len())
+        })
+        .collect()
+}
+
+/// Writes a capture index to `<path>.idx`.
+///
+/// Each line holds the byte offset of one packet's data within the PCAP
+/// at `path` and its length, as decimal numbers separated by a space, so
+/// tools can seek straight to a packet instead of parsing every record.
+///
+/// # Arguments
+///
+/// * `path` - The PCAP file the index belongs to
+/// * `offsets` - `(offset, length)` per packet, see [`pcap_packet_offsets`]
+///
+/// # Returns
+///
+/// - `Ok(())` on success
+/// - `Err(Box<dyn std::error::Error>)` if file creation or writing fails
+pub fn write_index(path: &Path, offsets: &[(u64, usize)]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = File::create(index_sidecar_path(path))?;
+    for (offset, length) in offsets {
+        writeln!(file, "{} {}", offset, length)?;
+    }
+    Ok(())
+}
+
+/// Magic bytes at the start of a packet bundle file.
+pub const BUNDLE_MAGIC: [u8; 4] = *b"PKTB";
+
+/// Writes packets to a compact binary bundle file.
+///
+/// Layout, all integers big-endian:
+/// - 4-byte magic ([`BUNDLE_MAGIC`])
+/// - u32 record count
+/// - per record: u32 length, then that many packet bytes
+///
+/// # Arguments
+///
+/// * `path` - The file path where the bundle will be created
+/// * `packets` - The complete packets (Ethernet frames) to store
+///
+/// # Returns
+///
+/// - `Ok(())` on success
+/// - `Err(Box<dyn std::error::Error>)` if file creation or writing fails
+pub fn write_bundle(path: &Path, packets: &[Vec<u8>]) -> Result<(), Box<dyn std::error::Error>> {
+    let total: usize = packets.iter().map(|p| 4 + p.len()).sum();
+    let mut data = Vec::with_capacity(8 + total);
+    data.extend_from_slice(&BUNDLE_MAGIC);
+    data.extend_from_slice(&u32::try_from(packets.len())?.to_be_bytes());
+    for packet in packets {
+        data.extend_from_slice(&u32::try_from(packet.len())?.to_be_bytes());
+        data.extend_from_slice(packet);
+    }
+    
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+/// Reads the packets from a bundle written by [`write_bundle`].
+///
+/// # Returns
+///
+/// - `Ok(Vec<Vec<u8>>)` with the packets in file order
+/// - `Err(Box<dyn std::error::Error>)` if the file cannot be read, the magic
+///   does not match, a record runs past the end of the file or bytes are
+///   left over after the last record
+pub fn read_bundle(path: &Path) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error>> {
+    let data = std::fs::read(path)?;
+    if data.get(..4) != Some(&BUNDLE_MAGIC[..]) {
+        return Err(format!("'{}' is not a packet bundle (bad magic)", path.display()).into());
+    }
+    
+    let mut rest = &data[4..];
+    let take_u32 = |rest: &mut &[u8]| -> Result<u32, String> {
+        let (head, tail) = rest.split_first_chunk::<4>().ok_or("Truncated bundle")?;
+        *rest = tail;
+        Ok(u32::from_be_bytes(*head))
+    };
+    
+    let count = take_u32(&mut rest)?;
+    let mut packets = Vec::new();
+    for _ in 0..count {
+        let len = take_u32(&mut rest)? as usize;
+        if rest.len() < len {
+            return Err("Truncated bundle".into());
+        }
+        let (packet, tail) = rest.split_at(len);
+        packets.push(packet.to_vec());
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        return Err(format!("{} trailing bytes after the last bundle record", rest.len()).into());
+    }
+    
+    Ok(packets)
+}
+
+/// Writes several packets to a JSON file as an array of records.
+///
+/// Each element has the same shape as the document written by [`write_json`],
+/// plus an `index` field giving its 0-based position in `packets`.
+///
+/// # Arguments
+///
+/// * `path` - The file path where the JSON file will be created
+/// * `packets` - The complete packets (Ethernet frames) to record
+///
+/// # Returns
+///
+/// - `Ok(())` on success
+/// - `Err(Box<dyn std::error::Error>)` if file creation or writing fails
+pub fn write_json_session(path: &Path, packets: &[Vec<u8>]) -> Result<(), Box<dyn std::error::Error>> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap();
+    
+    let records = session_records(packets, now.as_secs_f64());
+    
+    let json = serde_json::to_string_pretty(&records)?;
+    let mut file = File::create(path)?;
+    file.write_all(json.as_bytes())?;
+    
+    Ok(())
+}
+
 /// Writes a packet to a JSON file with metadata.
 ///
 /// Creates a JSON file containing:
 /// - `timestamp`: Unix timestamp as floating-point seconds
 /// - `length`: Total packet size in bytes
 /// - `data`: Hex-encoded packet data
+/// - `ethernet`, `ipv4`, `l4`: Decoded header fields (when decodable)
 ///
 /// The JSON is formatted with pretty printing for readability.
 ///
@@ -117,15 +502,254 @@ pub fn write_json(path: &Path, packet: &[u8]) -> Result<(), Box<dyn std::error::
         .duration_since(UNIX_EPOCH)
         .unwrap();
     
-    let packet_info = PacketInfo {
-        timestamp: now.as_secs_f64(),
-        length: packet.len(),
-        data: hex::encode(packet),
-    };
-    
-    let json = serde_json::to_string_pretty(&packet_info)?;
+    let json = render_json(packet, now.as_secs_f64())?;
     let mut file = File::create(path)?;
     file.write_all(json.as_bytes())?;
     
     Ok(())
 }
+
+/// Renders a packet as the pretty-printed JSON document written by [`write_json`].
+///
+/// The timestamp is supplied by the caller, so rendering the same packet with
+/// the same timestamp always yields an identical string.
+///
+/// # Arguments
+///
+/// * `packet` - The complete packet bytes (Ethernet frame)
+/// * `timestamp` - Unix timestamp (seconds since epoch) to record
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::output::render_json;
+///
+/// let a = render_json(&[0xaa, 0xbb], 1.5).unwrap();
+/// let b = render_json(&[0xaa, 0xbb], 1.5).unwrap();
+/// assert_eq!(a, b);
+/// ```
+pub fn render_json(packet: &[u8], timestamp: f64) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&PacketInfo::new(packet, timestamp))
+}
+
+/// Renders the JSON Schema of the detailed packet document ([`PacketInfo`]).
+///
+/// The schema is derived from the structs themselves, so it always matches
+/// what [`write_json`] and [`write_json_session`] emit. This backs the
+/// `--json_schema` flag.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::output::render_json_schema;
+///
+/// let schema: serde_json::Value = serde_json::from_str(&render_json_schema().unwrap()).unwrap();
+/// assert!(schema["properties"]["ipv4"].is_object());
+/// ```
+pub fn render_json_schema() -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&schemars::schema_for!(PacketInfo))
+}
+
+/// Writes a packet to a YAML file with metadata and decoded headers.
+///
+/// The document has the same structure as the JSON output (see
+/// [`PacketInfo`]), rendered as a YAML tree for easier reading in reviews.
+///
+/// # Arguments
+///
+/// * `path` - The file path where the YAML file will be created
+/// * `packet` - The complete packet bytes (Ethernet frame)
+///
+/// # Returns
+///
+/// - `Ok(())` on success
+/// - `Err(Box<dyn std::error::Error>)` if file creation or writing fails
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use packet_crafter::output::write_yaml;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let packet = vec![0xaa, 0xbb, 0xcc, 0xdd];
+/// write_yaml(Path::new("debug.yaml"), &packet)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_yaml(path: &Path, packet: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap();
+    
+    let yaml = serde_yaml::to_string(&PacketInfo::new(packet, now.as_secs_f64()))?;
+    let mut file = File::create(path)?;
+    file.write_all(yaml.as_bytes())?;
+    
+    Ok(())
+}
+
+/// Writes several packets to a YAML file as a sequence of records.
+///
+/// Each element has the same shape as the document written by [`write_yaml`],
+/// plus an `index` field giving its 0-based position in `packets`.
+///
+/// # Arguments
+///
+/// * `path` - The file path where the YAML file will be created
+/// * `packets` - The complete packets (Ethernet frames) to record
+///
+/// # Returns
+///
+/// - `Ok(())` on success
+/// - `Err(Box<dyn std::error::Error>)` if file creation or writing fails
+pub fn write_yaml_session(path: &Path, packets: &[Vec<u8>]) -> Result<(), Box<dyn std::error::Error>> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap();
+    
+    let records = session_records(packets, now.as_secs_f64());
+    
+    let yaml = serde_yaml::to_string(&records)?;
+    let mut file = File::create(path)?;
+    file.write_all(yaml.as_bytes())?;
+    
+    Ok(())
+}
+
+/// Renders a packet in the text layout of `tcpdump -tttt -xx`.
+///
+/// The first line holds the timestamp (UTC, `YYYY-MM-DD HH:MM:SS.ffffff`),
+/// a one-line summary of the decoded headers and the frame length, e.g.
+/// `... IP 10.0.0.1.1234 > 10.0.0.2.80: UDP, length 46`. It is followed by
+/// the whole frame as hex lines of 16 bytes, each prefixed with its offset.
+///
+/// # Arguments
+///
+/// * `packet` - The complete packet bytes (Ethernet frame)
+/// * `timestamp` - Capture time in seconds since the Unix epoch
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::PacketBuilder, output::render_tcpdump};
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// let text = render_tcpdump(builder.build_packet(b"hi").unwrap(), 0.0);
+/// assert!(text.starts_with("1970-01-01 00:00:00.000000 IP "));
+/// assert!(text.lines().nth(1).unwrap().starts_with("\t0x0000:  "));
+/// ```
+pub fn render_tcpdump(packet: &[u8], timestamp: f64) -> String {
+    let mut text = format!("{} {}, length {}\n", format_utc(timestamp), tcpdump_summary(packet), packet.len());
+    for (line, chunk) in packet.chunks(16).enumerate() {
+        let words: Vec<String> = chunk.chunks(2).map(hex::encode).collect();
+        text.push_str(&format!("\t0x{:04x}:  {}\n", line * 16, words.join(" ")));
+    }
+    text
+}
+
+/// Writes packets to a text file in the layout of [`render_tcpdump`].
+///
+/// Packets are written one after the other, so the file can be diffed
+/// against the output of `tcpdump -tttt -xx -r capture.pcap`.
+///
+/// # Arguments
+///
+/// * `path` - The file path where the text file will be created
+/// * `packets` - The complete packets (Ethernet frames) to record
+///
+/// # Returns
+///
+/// - `Ok(())` on success
+/// - `Err(Box<dyn std::error::Error>)` if file creation or writing fails
+pub fn write_tcpdump(path: &Path, packets: &[Vec<u8>]) -> Result<(), Box<dyn std::error::Error>> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap();
+    
+    let mut file = File::create(path)?;
+    for packet in packets {
+        file.write_all(render_tcpdump(packet, now.as_secs_f64()).as_bytes())?;
+    }
+    
+    Ok(())
+}
+
+/// Summarizes the headers of a frame the way tcpdump's first line does.
+fn tcpdump_summary(packet: &[u8]) -> String {
+    let decoded = match decode_packet(packet) {
+        Ok(decoded) => decoded,
+        Err(e) => return format!("[|{}]", e),
+    };
+    let Some(ip) = decoded.ipv4 else {
+        return format!("ethertype 0x{:04x}", decoded.ethernet.ethertype);
+    };
+    match decoded.l4 {
+        Some(L4Info::Tcp { source_port, destination_port, .. }) => {
+            format!("IP {}.{} > {}.{}: TCP", ip.source, source_port, ip.destination, destination_port)
+        }
+        Some(L4Info::Udp { source_port, destination_port, .. }) => {
+            format!("IP {}.{} > {}.{}: UDP", ip.source, source_port, ip.destination, destination_port)
+        }
+        None => format!("IP {} > {}: ip-proto-{}", ip.source, ip.destination, ip.protocol),
+    }
+}
+
+/// Formats seconds since the Unix epoch as `YYYY-MM-DD HH:MM:SS.ffffff` in UTC.
+fn format_utc(timestamp: f64) -> String {
+    let micros = (timestamp * 1e6).round() as i64;
+    chrono::DateTime::from_timestamp_micros(micros)
+        .unwrap_or_default()
+        .format("%Y-%m-%d %H:%M:%S%.6f")
+        .to_string()
+}
+
+/// Runs `write` against a temporary file and renames it onto `path` on success.
+///
+/// The temporary file lives in the same directory as `path` so the final
+/// rename stays on one filesystem and replaces the destination in a single
+/// step. If `write` fails, the temporary file is removed and `path` is left
+/// untouched, so an interrupted write never leaves a partial debug file.
+///
+/// # Arguments
+///
+/// * `path` - The final destination of the file
+/// * `write` - One of the writers in this module (or any function taking a
+///   path), called with the temporary path
+///
+/// # Returns
+///
+/// - `Ok(())` once the file has been renamed into place
+/// - `Err(Box<dyn std::error::Error>)` if `write` or the rename fails
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use packet_crafter::output::{write_atomic, write_pcap};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let packet = vec![0xaa, 0xbb, 0xcc, 0xdd];
+/// write_atomic(Path::new("debug.pcap"), |tmp| write_pcap(tmp, &packet))?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_atomic<F>(path: &Path, write: F) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: FnOnce(&Path) -> Result<(), Box<dyn std::error::Error>>,
+{
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("'{}' does not name a file", path.display()))?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+    
+    let result = write(&tmp_path).and_then(|()| Ok(std::fs::rename(&tmp_path, path)?));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    
+    result
+}
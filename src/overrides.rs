@@ -0,0 +1,451 @@
+//! Per-field overrides applied to built packets.
+//!
+//! An override table maps dotted field paths such as `ipv4.ttl` or
+//! `tcp.window` to numeric values. It is loaded from a JSON object with
+//! `--overrides` and gives access to header fields that have no flag of
+//! their own. [`swap_field_endianness`] uses the same paths to write a
+//! multi-byte field in the wrong byte order. Both run after the packet's
+//! checksums were computed and repair the checksums of the headers they
+//! change; [`apply_overrides_raw`] leaves every checksum as built, and
+//! [`refix_ipv4_checksum`] can then repair the IPv4 header checksum alone
+//! without touching TCP/UDP.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::Path;
+
+use pnet::packet::ethernet::{EtherType, MutableEthernetPacket};
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use pnet::packet::ipv4::{Ipv4Packet, MutableIpv4Packet};
+use pnet::packet::tcp::MutableTcpPacket;
+use pnet::packet::udp::MutableUdpPacket;
+
+use crate::CrafterError;
+use crate::packet::{ipv4_header_offset, layer_ranges};
+
+/// Override table: dotted field path to value, applied in path order.
+pub type Overrides = BTreeMap<String, u64>;
+
+/// Field paths accepted by [`apply_overrides`].
+pub const SUPPORTED_FIELDS: &[&str] = &[
+    "ethernet.ethertype",
+    "ipv4.dscp",
+    "ipv4.ecn",
+    "ipv4.total_length",
+    "ipv4.identification",
+    "ipv4.flags",
+    "ipv4.fragment_offset",
+    "ipv4.ttl",
+    "ipv4.protocol",
+    "ipv4.checksum",
+    "tcp.src_port",
+    "tcp.dst_port",
+    "tcp.seq",
+    "tcp.ack",
+    "tcp.data_offset",
+    "tcp.flags",
+    "tcp.window",
+    "tcp.urgent_ptr",
+    "tcp.checksum",
+    "udp.src_port",
+    "udp.dst_port",
+    "udp.length",
+    "udp.checksum",
+];
+
+/// Multi-byte field paths accepted by [`swap_field_endianness`].
+pub const SWAPPABLE_FIELDS: &[&str] = &[
+    "ethernet.ethertype",
+    "ipv4.total_length",
+    "ipv4.identification",
+    "ipv4.checksum",
+    "tcp.src_port",
+    "tcp.dst_port",
+    "tcp.seq",
+    "tcp.ack",
+    "tcp.window",
+    "tcp.checksum",
+    "tcp.urgent_ptr",
+    "udp.src_port",
+    "udp.dst_port",
+    "udp.length",
+    "udp.checksum",
+];
+
+/// Loads an override table from a JSON object such as `{"ipv4.ttl": 10}`.
+///
+/// # Arguments
+///
+/// * `path` - Path of the JSON file
+///
+/// # Returns
+///
+/// - `Ok(Overrides)` with the parsed table
+/// - `Err(Box<dyn std::error::Error>)` if the file cannot be read or is not
+///   an object of non-negative integers
+pub fn load_overrides(path: &Path) -> Result<Overrides, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+/// Applies an override table to a built Ethernet/IPv4 frame.
+///
+/// Fields are written in path order. The header layout (IPv4 header length
+/// and Layer 4 protocol) is read before any override is applied, so
+/// overriding `ipv4.protocol` does not change which `tcp.*` or `udp.*`
+/// fields are accepted.
+///
+/// Afterwards the IPv4 header checksum is recomputed if an `ipv4.*` field
+/// changed, and the TCP/UDP checksum if a `tcp.*` or `udp.*` field changed.
+/// A checksum that is itself overridden is left as given, and a UDP
+/// checksum of 0 ("no checksum") stays 0.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to modify in place
+/// * `overrides` - Field paths (see [`SUPPORTED_FIELDS`]) and their values
+///
+/// # Errors
+///
+/// - `CrafterError::UnknownOverrideField` for a path not in [`SUPPORTED_FIELDS`]
+/// - `CrafterError::OverrideOutOfRange` for a value wider than its field
+/// - `CrafterError::OverrideTargetMissing` if the frame has no such header
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::PacketBuilder, overrides::{apply_overrides, Overrides}};
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// let mut frame = builder.build_packet(b"probe").unwrap().to_vec();
+///
+/// let overrides = Overrides::from([("ipv4.ttl".to_string(), 10)]);
+/// apply_overrides(&mut frame, &overrides).unwrap();
+/// assert_eq!(frame[14 + 8], 10);
+/// ```
+pub fn apply_overrides(frame: &mut [u8], overrides: &Overrides) -> Result<(), CrafterError> {
+    write_overrides(frame, overrides, true)
+}
+
+/// Applies an override table without repairing any checksum.
+///
+/// Like [`apply_overrides`], but the IPv4 and TCP/UDP checksums keep the
+/// values computed when the packet was built, so they no longer match the
+/// headers they cover once a covered field changes.
+///
+/// # Errors
+///
+/// The same as [`apply_overrides`].
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::PacketBuilder, overrides::{apply_overrides_raw, Overrides}};
+///
+/// let mut frame = PacketBuilder::from(&Args::default()).build_packet(b"probe").unwrap().to_vec();
+/// let checksum = [frame[24], frame[25]];
+///
+/// apply_overrides_raw(&mut frame, &Overrides::from([("ipv4.ttl".to_string(), 10)])).unwrap();
+/// assert_eq!(frame[14 + 8], 10);
+/// assert_eq!([frame[24], frame[25]], checksum);
+/// ```
+pub fn apply_overrides_raw(frame: &mut [u8], overrides: &Overrides) -> Result<(), CrafterError> {
+    write_overrides(frame, overrides, false)
+}
+
+/// Writes the fields of an override table, then repairs the checksums of
+/// the changed headers if `repair` is set.
+fn write_overrides(frame: &mut [u8], overrides: &Overrides, repair: bool) -> Result<(), CrafterError> {
+    let layout = Layout::of(frame);
+    let mut ipv4_changed = false;
+    let mut ipv4_checksum_set = false;
+    let mut l4_changed = false;
+    let mut l4_checksum_set = false;
+
+    for (path, &value) in overrides {
+        let (layer, field) = path.split_once('.').ok_or_else(|| unknown_field(path))?;
+        match layer {
+            "ethernet" => {
+                let mut eth = MutableEthernetPacket::new(frame).ok_or_else(|| target_missing(path))?;
+                match field {
+                    "ethertype" => eth.set_ethertype(EtherType(fit(path, value, 16)? as u16)),
+                    _ => return Err(unknown_field(path)),
+                }
+            }
+            "ipv4" => {
+                let start = layout.ipv4.ok_or_else(|| target_missing(path))?;
+                let mut ip = MutableIpv4Packet::new(&mut frame[start..]).ok_or_else(|| target_missing(path))?;
+                set_ipv4_field(&mut ip, path, field, value)?;
+                ipv4_changed |= field != "checksum";
+                ipv4_checksum_set |= field == "checksum";
+            }
+            "tcp" => {
+                let range = match &layout.l4 {
+                    Some((protocol, range)) if *protocol == IpNextHeaderProtocols::Tcp => range.clone(),
+                    _ => return Err(target_missing(path)),
+                };
+                let mut tcp = MutableTcpPacket::new(&mut frame[range]).ok_or_else(|| target_missing(path))?;
+                set_tcp_field(&mut tcp, path, field, value)?;
+                l4_changed |= field != "checksum";
+                l4_checksum_set |= field == "checksum";
+            }
+            "udp" => {
+                let range = match &layout.l4 {
+                    Some((protocol, range)) if *protocol == IpNextHeaderProtocols::Udp => range.clone(),
+                    _ => return Err(target_missing(path)),
+                };
+                let mut udp = MutableUdpPacket::new(&mut frame[range]).ok_or_else(|| target_missing(path))?;
+                set_udp_field(&mut udp, path, field, value)?;
+                l4_changed |= field != "checksum";
+                l4_checksum_set |= field == "checksum";
+            }
+            _ => return Err(unknown_field(path)),
+        }
+    }
+
+    if repair && l4_changed && !l4_checksum_set {
+        layout.recompute_l4_checksum(frame);
+    }
+    if repair && ipv4_changed && !ipv4_checksum_set {
+        layout.recompute_ipv4_checksum(frame);
+    }
+    Ok(())
+}
+
+/// Byte-swaps one multi-byte header field of a built Ethernet/IPv4 frame.
+///
+/// The field is located with [`layer_ranges`], like [`apply_overrides`]
+/// does, so the IPv4 header may carry options. As with [`apply_overrides`], the IPv4 or TCP/UDP checksum of the
+/// changed header is then recomputed, unless the swapped field is that
+/// checksum; a UDP checksum of 0 stays 0.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to modify in place
+/// * `path` - Field path from [`SWAPPABLE_FIELDS`]
+///
+/// # Errors
+///
+/// - `CrafterError::UnknownOverrideField` for a path not in [`SWAPPABLE_FIELDS`]
+/// - `CrafterError::OverrideTargetMissing` if the frame has no such header
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, L4Protocol, packet::PacketBuilder, overrides::swap_field_endianness};
+///
+/// let args = Args { l4_protocol: L4Protocol::Tcp, ..Args::default() };
+/// let mut builder = PacketBuilder::from(&args);
+/// let mut frame = builder.build_packet(b"probe").unwrap().to_vec();
+/// let window = [frame[48], frame[49]];
+///
+/// swap_field_endianness(&mut frame, "tcp.window").unwrap();
+/// assert_eq!([frame[48], frame[49]], [window[1], window[0]]);
+/// ```
+pub fn swap_field_endianness(frame: &mut [u8], path: &str) -> Result<(), CrafterError> {
+    let (offset, width) = swap_location(path).ok_or_else(|| unknown_field(path))?;
+    let layout = Layout::of(frame);
+    
+    let (layer, field) = path.split_once('.').ok_or_else(|| unknown_field(path))?;
+    let start = match (layer, &layout.l4) {
+        ("ethernet", _) => Some(0),
+        ("ipv4", _) => layout.ipv4,
+        ("tcp", Some((protocol, range))) if *protocol == IpNextHeaderProtocols::Tcp => Some(range.start),
+        ("udp", Some((protocol, range))) if *protocol == IpNextHeaderProtocols::Udp => Some(range.start),
+        _ => None,
+    };
+    let start = start.ok_or_else(|| target_missing(path))?;
+    if frame.len() < start + offset + width {
+        return Err(target_missing(path));
+    }
+    frame[start + offset..start + offset + width].reverse();
+    
+    match layer {
+        "ipv4" if field != "checksum" => layout.recompute_ipv4_checksum(frame),
+        "tcp" | "udp" if field != "checksum" => layout.recompute_l4_checksum(frame),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Recomputes only the IPv4 header checksum of a frame.
+///
+/// Overrides can leave the header checksum wrong on purpose, with
+/// [`apply_overrides_raw`] or an explicit `ipv4.checksum`. This makes the
+/// IPv4 header internally consistent again while leaving the TCP/UDP
+/// checksum as it is, stale or not. Frames without an IPv4 header are
+/// unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::PacketBuilder, overrides::{apply_overrides, refix_ipv4_checksum, Overrides}};
+///
+/// let mut frame = PacketBuilder::from(&Args::default()).build_packet(b"probe").unwrap().to_vec();
+/// let valid = [frame[24], frame[25]];
+///
+/// apply_overrides(&mut frame, &Overrides::from([("ipv4.checksum".to_string(), 0)])).unwrap();
+/// refix_ipv4_checksum(&mut frame);
+/// assert_eq!([frame[24], frame[25]], valid);
+/// ```
+pub fn refix_ipv4_checksum(frame: &mut [u8]) {
+    Layout::of(frame).recompute_ipv4_checksum(frame);
+}
+
+/// Recomputes the IPv4 and TCP/UDP checksums of a frame whose addresses
+/// or other covered fields were changed; a UDP checksum of 0 stays 0.
+pub(crate) fn recompute_checksums(frame: &mut [u8]) {
+    let layout = Layout::of(frame);
+    layout.recompute_l4_checksum(frame);
+    layout.recompute_ipv4_checksum(frame);
+}
+
+/// Returns the offset within its header and width in bytes of a swappable field.
+fn swap_location(path: &str) -> Option<(usize, usize)> {
+    let location = match path {
+        "ethernet.ethertype" => (12, 2),
+        "ipv4.total_length" => (2, 2),
+        "ipv4.identification" => (4, 2),
+        "ipv4.checksum" => (10, 2),
+        "tcp.src_port" | "udp.src_port" => (0, 2),
+        "tcp.dst_port" | "udp.dst_port" => (2, 2),
+        "tcp.seq" => (4, 4),
+        "tcp.ack" => (8, 4),
+        "tcp.window" => (14, 2),
+        "tcp.checksum" => (16, 2),
+        "tcp.urgent_ptr" => (18, 2),
+        "udp.length" => (4, 2),
+        "udp.checksum" => (6, 2),
+        _ => return None,
+    };
+    Some(location)
+}
+
+/// Where the IPv4 header and Layer 4 segment of a frame were found before
+/// it was modified.
+///
+/// There is no parsing here: the IPv4 header comes from
+/// [`ipv4_header_offset`] and the segment from [`layer_ranges`], so a
+/// frame whose Layer 4 header cannot be split out (e.g. IGMP) still has
+/// its IPv4 header.
+struct Layout {
+    /// Offset of the IPv4 header
+    ipv4: Option<usize>,
+    /// Layer 4 protocol and segment, up to the end of the IPv4 packet
+    l4: Option<(IpNextHeaderProtocol, Range<usize>)>,
+}
+
+impl Layout {
+    /// Locates the IPv4 header and Layer 4 segment in `frame`.
+    fn of(frame: &[u8]) -> Self {
+        let l4 = layer_ranges(frame).ok().map(|ranges| {
+            (IpNextHeaderProtocol(frame[ranges.ipv4.start + 9]), ranges.l4.start..ranges.payload.end)
+        });
+        Layout { ipv4: ipv4_header_offset(frame), l4 }
+    }
+
+    /// Recomputes the IPv4 header checksum.
+    fn recompute_ipv4_checksum(&self, frame: &mut [u8]) {
+        let Some(start) = self.ipv4 else {
+            return;
+        };
+        if let Some(mut ip) = MutableIpv4Packet::new(&mut frame[start..]) {
+            let checksum = pnet::packet::ipv4::checksum(&ip.to_immutable());
+            ip.set_checksum(checksum);
+        }
+    }
+
+    /// Recomputes the TCP or UDP checksum over the pseudo-header and segment.
+    fn recompute_l4_checksum(&self, frame: &mut [u8]) {
+        let (Some(start), Some((protocol, range))) = (self.ipv4, &self.l4) else {
+            return;
+        };
+        let Some(ip) = Ipv4Packet::new(&frame[start..]) else {
+            return;
+        };
+        let (src, dst) = (ip.get_source(), ip.get_destination());
+        let segment = &mut frame[range.clone()];
+
+        if *protocol == IpNextHeaderProtocols::Tcp {
+            if let Some(mut tcp) = MutableTcpPacket::new(segment) {
+                let checksum = pnet::packet::tcp::ipv4_checksum(&tcp.to_immutable(), &src, &dst);
+                tcp.set_checksum(checksum);
+            }
+        } else if *protocol == IpNextHeaderProtocols::Udp {
+            let length = segment
+                .get(4..6)
+                .map_or(0, |bytes| u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+                .min(segment.len());
+            if let Some(mut udp) = MutableUdpPacket::new(&mut segment[..length]) {
+                if udp.get_checksum() != 0 {
+                    let checksum = pnet::packet::udp::ipv4_checksum(&udp.to_immutable(), &src, &dst);
+                    udp.set_checksum(checksum);
+                }
+            }
+        }
+    }
+}
+
+/// Sets one IPv4 header field.
+fn set_ipv4_field(ip: &mut MutableIpv4Packet, path: &str, field: &str, value: u64) -> Result<(), CrafterError> {
+    match field {
+        "dscp" => ip.set_dscp(fit(path, value, 6)? as u8),
+        "ecn" => ip.set_ecn(fit(path, value, 2)? as u8),
+        "total_length" => ip.set_total_length(fit(path, value, 16)? as u16),
+        "identification" => ip.set_identification(fit(path, value, 16)? as u16),
+        "flags" => ip.set_flags(fit(path, value, 3)? as u8),
+        "fragment_offset" => ip.set_fragment_offset(fit(path, value, 13)? as u16),
+        "ttl" => ip.set_ttl(fit(path, value, 8)? as u8),
+        "protocol" => ip.set_next_level_protocol(IpNextHeaderProtocol(fit(path, value, 8)? as u8)),
+        "checksum" => ip.set_checksum(fit(path, value, 16)? as u16),
+        _ => return Err(unknown_field(path)),
+    }
+    Ok(())
+}
+
+/// Sets one TCP header field.
+fn set_tcp_field(tcp: &mut MutableTcpPacket, path: &str, field: &str, value: u64) -> Result<(), CrafterError> {
+    match field {
+        "src_port" => tcp.set_source(fit(path, value, 16)? as u16),
+        "dst_port" => tcp.set_destination(fit(path, value, 16)? as u16),
+        "seq" => tcp.set_sequence(fit(path, value, 32)? as u32),
+        "ack" => tcp.set_acknowledgement(fit(path, value, 32)? as u32),
+        "data_offset" => tcp.set_data_offset(fit(path, value, 4)? as u8),
+        "flags" => tcp.set_flags(fit(path, value, 8)? as u8),
+        "window" => tcp.set_window(fit(path, value, 16)? as u16),
+        "urgent_ptr" => tcp.set_urgent_ptr(fit(path, value, 16)? as u16),
+        "checksum" => tcp.set_checksum(fit(path, value, 16)? as u16),
+        _ => return Err(unknown_field(path)),
+    }
+    Ok(())
+}
+
+/// Sets one UDP header field.
+fn set_udp_field(udp: &mut MutableUdpPacket, path: &str, field: &str, value: u64) -> Result<(), CrafterError> {
+    match field {
+        "src_port" => udp.set_source(fit(path, value, 16)? as u16),
+        "dst_port" => udp.set_destination(fit(path, value, 16)? as u16),
+        "length" => udp.set_length(fit(path, value, 16)? as u16),
+        "checksum" => udp.set_checksum(fit(path, value, 16)? as u16),
+        _ => return Err(unknown_field(path)),
+    }
+    Ok(())
+}
+
+/// Checks that `value` fits in a field of `bits` bits.
+fn fit(path: &str, value: u64, bits: u32) -> Result<u64, CrafterError> {
+    if value >> bits != 0 {
+        return Err(CrafterError::OverrideOutOfRange { path: path.to_string(), value, bits });
+    }
+    Ok(value)
+}
+
+/// Returns the error for a field path that is not supported.
+fn unknown_field(path: &str) -> CrafterError {
+    CrafterError::UnknownOverrideField { path: path.to_string() }
+}
+
+/// Returns the error for a field whose header is not in the frame.
+fn target_missing(path: &str) -> CrafterError {
+    CrafterError::OverrideTargetMissing { path: path.to_string() }
+}
@@ -5,18 +5,79 @@
 //! all protocol headers correctly formatted.
 
 use pnet::packet::ethernet::{MutableEthernetPacket, EtherTypes};
-use pnet::packet::ipv4::{MutableIpv4Packet, checksum as ipv4_checksum};
-use pnet::packet::tcp::MutableTcpPacket;
-use pnet::packet::udp::MutableUdpPacket;
+use pnet::packet::ipv4::{Ipv4Flags, Ipv4Packet, MutableIpv4Packet, checksum as ipv4_checksum};
+use pnet::packet::tcp::{MutableTcpPacket, TcpFlags, TcpPacket};
+use pnet::packet::udp::{MutableUdpPacket, UdpPacket};
+use pnet::packet::ipv6::MutableIpv6Packet;
+use pnet::packet::arp::{ArpHardwareTypes, ArpOperations, MutableArpPacket};
+use pnet::packet::icmp::{IcmpCode, IcmpPacket, IcmpTypes};
+use pnet::packet::icmp::echo_request::MutableEchoRequestPacket;
+use pnet::packet::icmpv6::{Icmpv6Packet, Icmpv6Types};
+use pnet::packet::icmpv6::ndp::MutableNeighborSolicitPacket;
 use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
-use std::net::Ipv4Addr;
+use pnet::packet::ethernet::EtherType;
+use pnet::packet::Packet;
+use pnet::util::MacAddr;
+use serde::{Serialize, Deserialize};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::ops::Range;
 
-use crate::{Args, L4Protocol};
+use crate::{Args, ChecksumAlgo, Conversation, CrafterError, Ipv6ExtHeader, L4Protocol, TcpOptionOrder, UdpChecksumMode};
+use crate::parsing;
+use crate::payload::{dhcp, igmp, lldp};
 
-/// Packet builder for constructing raw network packets.
-///
-/// This struct provides methods to build complete Ethernet frames with IPv4 and
-/// TCP/UDP packets.
+/// Minimum Ethernet frame length in bytes, excluding the FCS.
+pub const MIN_FRAME_LENGTH: usize = 60;
+
+/// Largest MTU accepted when jumbo frames are allowed.
+pub const JUMBO_MTU: usize = 9000;
+
+/// Share of the MTU, in percent, from which `--auto_df` sets Don't Fragment.
+pub const AUTO_DF_THRESHOLD_PERCENT: usize = 90;
+
+/// TCP receive window advertised by crafted segments.
+const DEFAULT_TCP_WINDOW: u16 = 64240;
+
+/// MSS advertised by option order presets when `--tcp_mss_from_mtu` is not given.
+const DEFAULT_PRESET_MSS: u16 = 1460;
+
+/// TSval placed in the Timestamps option of option order presets.
+const PRESET_TSVAL: u32 = 0x0001_e240;
+
+/// TTL of `--multicast` packets, the default multicast TTL of socket APIs,
+/// which keeps them on the local network.
+pub const MULTICAST_TTL: u8 = 1;
+
+/// ECN codepoint Congestion Experienced (0b11), set by `--mark_ce`.
+pub const ECN_CE: u8 = 0b11;
+
+/// Length of an Ethernet/IPv4 ARP packet.
+const ARP_PACKET_LENGTH: usize = 28;
+
+/// Length of the PPPoE session header (6 bytes) plus the PPP protocol field
+/// (2 bytes) placed before the IPv4 header with `--pppoe_session`.
+pub const PPPOE_HEADER_LENGTH: usize = 8;
+
+/// Length of an 802.1Q VLAN tag placed after the MAC addresses with `--vlan_id`.
+pub const VLAN_TAG_LENGTH: usize = 4;
+
+/// PPPoE version 1, type 1, packed into one byte.
+const PPPOE_VERSION_TYPE: u8 = 0x11;
+
+/// PPP protocol number of IPv4.
+const PPP_PROTOCOL_IPV4: u16 = 0x0021;
+
+/// Initial sequence number used by the simulated server in conversations.
+pub const SERVER_ISN: u32 = 0x2f1e_9a3b;
+
+/// IPv4 option type of the Timestamp option.
+const IP_OPTION_TIMESTAMP: u8 = 68;
+
+/// Timestamp option flag for "timestamps of prespecified addresses only".
+const TIMESTAMP_FLAG_PRESPECIFIED: u8 = 3;
+
+/// Most prespecified addresses that fit in the 40 bytes of IPv4 options.
+pub const MAX_TIMESTAMP_ADDRS: usize = 4;
 
 /// Builder for constructing raw network packets.
 ///
@@ -30,7 +91,7 @@ use crate::{Args, L4Protocol};
 /// +----------------+
 /// | Ethernet (14B) |  Layer 2: MAC addresses, EtherType
 /// +----------------+
-/// | IPv4 (20B)     |  Layer 3: IP addresses, protocol
+/// | IPv4 (20B+)    |  Layer 3: IP addresses, protocol, options
 /// +----------------+
 /// | TCP/UDP        |  Layer 4: Ports, checksums
 /// | (20B / 8B)     |
@@ -38,13 +99,18 @@ use crate::{Args, L4Protocol};
 /// | Payload        |  Application data
 /// +----------------+
 /// ```
+#[derive(Clone)]
 pub struct PacketBuilder {
     /// Source IPv4 address
     src_ip: Ipv4Addr,
+    /// Source address used in the L4 pseudo-header instead of `src_ip`
+    pseudo_src_ip: Option<Ipv4Addr>,
     /// Destination IPv4 address
     dst_ip: Ipv4Addr,
     /// Destination port number (TCP/UDP)
     dest_port: u16,
+    /// Source port number (TCP/UDP)
+    src_port: u16,
     /// Source MAC address (Ethernet layer)
     src_mac: [u8; 6],
     /// Destination MAC address (Ethernet layer)
@@ -53,35 +119,316 @@ pub struct PacketBuilder {
     l4_protocol: L4Protocol,
     /// IPv4 header flags/fragment offset bitfield
     ip_bitfield: u8,
-    /// Internal buffer for packet construction (1500 bytes for standard MTU)
+    /// IPv4 time to live
+    ttl: u8,
+    /// IPv4 DSCP codepoint
+    dscp: u8,
+    /// IPv4 ECN codepoint
+    ecn: u8,
+    /// Raw version/IHL byte written over the built IPv4 header's first byte
+    ipv4_vihl: Option<u8>,
+    /// PPPoE session ID; wraps IPv4 packets in a PPPoE session header
+    pppoe_session: Option<u16>,
+    /// 802.1Q VLAN ID; tags IPv4 packets
+    vlan_id: Option<u16>,
+    /// 802.1p priority code point carried in the VLAN tag
+    vlan_pcp: u8,
+    /// Set Don't Fragment on packets close to the MTU
+    auto_df: bool,
+    /// Set Don't Fragment on every packet
+    force_df: bool,
+    /// Maximum IP packet size, used by `auto_df`
+    mtu: usize,
+    /// Source IPv6 address (IPv6 packets only)
+    src_ipv6: Ipv6Addr,
+    /// Destination IPv6 address (IPv6 TCP/UDP packets only)
+    dst_ipv6: Ipv6Addr,
+    /// IPv6 flow label (IPv6 packets only)
+    flow_label: u32,
+    /// Neighbor Solicitation target; switches the builder to ICMPv6 NS
+    ns_target: Option<Ipv6Addr>,
+    /// Extension header placed after the IPv6 header (IPv6 packets only)
+    ipv6_ext: Option<Ipv6ExtHeader>,
+    /// Derive the destination MAC from the Layer 3 destination
+    auto_l2: bool,
+    /// IGMP group to report; switches the builder to an IGMPv2 report
+    igmp_group: Option<Ipv4Addr>,
+    /// Build ICMP echo requests instead of TCP/UDP
+    icmp_echo: bool,
+    /// ICMP echo identifier
+    icmp_id: u16,
+    /// ICMP echo sequence number of the next echo request
+    icmp_seq: u16,
+    /// Encoded LLDPDU; switches the builder to LLDP frames
+    lldpdu: Option<Vec<u8>>,
+    /// Build gratuitous ARP replies for `src_ip` instead of IP packets
+    arp_gratuitous: bool,
+    /// TCP sequence number
+    tcp_seq: u32,
+    /// TCP acknowledgement number
+    tcp_ack: u32,
+    /// TCP flags byte (SYN by default)
+    tcp_flags: u8,
+    /// TCP receive window
+    tcp_window: u16,
+    /// Build a TCP RST: drops the payload
+    rst: bool,
+    /// Low nibble of TCP byte 12: the three reserved bits and the NS bit
+    tcp_reserved: u8,
+    /// Checksum written into TCP headers instead of the computed one
+    tcp_checksum_override: Option<u16>,
+    /// L4 checksum reached by appending two adjustment bytes to the payload
+    checksum_target: Option<u16>,
+    /// TCP Maximum Segment Size option value
+    tcp_mss: Option<u16>,
+    /// OS-specific option layout for SYN segments
+    tcp_option_order: Option<TcpOptionOrder>,
+    /// EtherType written in place of 0x0800 in front of IPv4 packets
+    ethertype_override: Option<u16>,
+    /// Insert the IPv4 Router Alert option
+    router_alert: bool,
+    /// Prespecified addresses for an IPv4 Timestamp option (flag 3)
+    ip_timestamp_addrs: Vec<Ipv4Addr>,
+    /// Bytes appended after the L4 segment, inside the IPv4 packet
+    trailer: Vec<u8>,
+    /// Implementation used for the IPv4 and L4 checksums
+    checksum_algo: ChecksumAlgo,
+    /// How the UDP checksum field is filled in
+    udp_checksum: UdpChecksumMode,
+    /// Number of bytes built packets are cut to, possibly mid-header
+    truncate_at: Option<usize>,
+    /// Callback tapping every built packet; clones of the builder start without one
+    on_built: OnBuilt,
+    /// Internal buffer for packet construction, grown on demand
     buffer: Vec<u8>,
 }
 
+/// Callback invoked with the bytes of every built packet, see
+/// [`PacketBuilder::set_on_built`].
+pub type BuiltCallback = Box<dyn FnMut(&[u8]) + Send>;
+
+/// Slot holding the registered [`BuiltCallback`].
+///
+/// Cloning yields an empty slot, so a callback is never shared between builders.
+#[derive(Default)]
+struct OnBuilt(Option<BuiltCallback>);
+
+impl Clone for OnBuilt {
+    fn clone(&self) -> Self {
+        OnBuilt(None)
+    }
+}
+
+/// Cuts a built frame to `truncate_at` bytes, if set, and hands it to the
+/// `on_built` callback.
+///
+/// Every packet a builder hands out goes through here, including the
+/// segments [`PacketBuilder::build_conversation`] builds on clones, which
+/// have no callback of their own.
+fn finish_packet<'a>(packet: &'a [u8], truncate_at: Option<usize>, on_built: &mut OnBuilt) -> &'a [u8] {
+    let packet = &packet[..truncate_at.map_or(packet.len(), |length| length.min(packet.len()))];
+    if let Some(callback) = &mut on_built.0 {
+        callback(packet);
+    }
+    packet
+}
+
+/// Snapshot of a builder's addressing inputs, as serialized by
+/// [`PacketBuilder::config_json`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BuilderConfig {
+    /// Source MAC address (aa:bb:cc:dd:ee:ff)
+    pub src_mac: String,
+    /// Destination MAC address (aa:bb:cc:dd:ee:ff)
+    pub dst_mac: String,
+    /// Source IPv4 address
+    pub src_ip: Ipv4Addr,
+    /// Destination IPv4 address
+    pub dst_ip: Ipv4Addr,
+    /// Layer 4 protocol name (`tcp`, `udp` or `icmp`)
+    pub l4_protocol: String,
+    /// Source port
+    pub src_port: u16,
+    /// Destination port
+    pub dest_port: u16,
+    /// IPv4 header flags/fragment offset bitfield
+    pub ip_bitfield: u8,
+    /// IPv4 time to live
+    pub ttl: u8,
+}
+
+/// Byte ranges of each layer within an Ethernet/IPv4 frame.
+///
+/// Returned by [`layer_ranges`]. The ranges are contiguous: each one starts
+/// where the previous one ends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerRanges {
+    /// Ethernet header
+    pub ethernet: Range<usize>,
+    /// IPv4 header, including options
+    pub ipv4: Range<usize>,
+    /// TCP, UDP or ICMP header, including TCP options
+    pub l4: Range<usize>,
+    /// Layer 4 payload, up to the end of the IPv4 packet
+    pub payload: Range<usize>,
+}
+
 /// Converts command-line arguments into a `PacketBuilder`.
 ///
 /// Creates a new `PacketBuilder` initialized with all parameters from the
-/// parsed command-line arguments. The internal buffer is pre-allocated
-/// with 1500 bytes (standard Ethernet MTU).
+/// parsed command-line arguments. With `--dhcp_discover`, the addressing is
+/// replaced by the DHCP client form: `0.0.0.0:68` to `255.255.255.255:67`,
+/// destination MAC `ff:ff:ff:ff:ff:ff`. With `--igmp_report`, the packet
+/// goes to the group address and its multicast MAC (see
+/// [`ipv4_multicast_mac`]) with TTL 1 and the Router Alert option, as RFC 2236
+/// requires. `--multicast` addresses the packet to a group the same way,
+/// with TTL [`MULTICAST_TTL`]. With `--pmtu_probe`, the builder produces ICMP echo requests
+/// with Don't Fragment set. With `--lldp`, it produces LLDP frames sent to
+/// the nearest-bridge address `01:80:c2:00:00:0e`. With `--arp_gratuitous`,
+/// it produces gratuitous ARP replies sent to the broadcast MAC. The TTL is the `--os_profile`
+/// initial TTL, or 64 without one. The internal buffer is pre-allocated
+/// with `--buffer_size` bytes when given; otherwise it starts empty and is
+/// grown to the exact frame size on the first build.
 impl From<&Args> for PacketBuilder {
     fn from(args: &Args) -> Self {
-        Self {
+        let mut builder = Self {
             src_ip: args.src_ip,
+            pseudo_src_ip: args.pseudo_src_ip,
             dst_ip: args.dst_ip,
             dest_port: args.dest_port,
+            src_port: args.src_port,
             src_mac: args.src_mac,
             dst_mac: args.dst_mac,
             l4_protocol: args.l4_protocol.clone(),
             ip_bitfield: args.ip_bitfield,
-            buffer: vec![0u8; 1500],
+            ttl: args.os_profile.as_deref().map_or(64, parsing::initial_ttl_for_os),
+            dscp: args.dscp.unwrap_or(0),
+            ecn: if args.mark_ce { ECN_CE } else { 0 },
+            ipv4_vihl: args.ipv4_vihl,
+            pppoe_session: args.pppoe_session,
+            vlan_id: args.vlan_id,
+            vlan_pcp: match (args.map_dscp_to_pcp, args.dscp) {
+                (true, Some(dscp)) => dscp_to_pcp(dscp),
+                _ => 0,
+            },
+            auto_df: args.auto_df,
+            force_df: args.pmtu_probe.is_some(),
+            mtu: args.mtu,
+            src_ipv6: args.src_ipv6,
+            dst_ipv6: args.dst_ipv6,
+            flow_label: args.flow_label,
+            ns_target: args.ns_target,
+            ipv6_ext: args.ipv6_ext,
+            auto_l2: args.auto_l2,
+            igmp_group: args.igmp_report,
+            icmp_echo: args.pmtu_probe.is_some(),
+            icmp_id: args.icmp_id.unwrap_or(args.src_port),
+            icmp_seq: args.icmp_seq,
+            arp_gratuitous: args.arp_gratuitous,
+            lldpdu: args
+                .lldp
+                .then(|| lldp::encode_lldpdu(args.src_mac, &args.lldp_port_id, args.lldp_ttl)),
+            tcp_seq: args.tcp_seq,
+            tcp_ack: 0,
+            tcp_flags: if args.rst { TcpFlags::RST } else { TcpFlags::SYN },
+            tcp_window: if args.rst { 0 } else { DEFAULT_TCP_WINDOW },
+            rst: args.rst,
+            tcp_reserved: args.tcp_reserved,
+            tcp_checksum_override: args.tcp_checksum_override,
+            checksum_target: args.checksum_target,
+            tcp_mss: args.tcp_mss_from_mtu.map(|mtu| mss_from_mtu(mtu, false)),
+            tcp_option_order: args
+                .option_order
+                .or_else(|| args.os_profile.as_deref().and_then(TcpOptionOrder::for_os)),
+            ethertype_override: args.ethertype_override,
+            router_alert: args.router_alert,
+            ip_timestamp_addrs: args.ip_timestamp_addrs.clone(),
+            trailer: args.trailer_hex.clone().unwrap_or_default().0,
+            checksum_algo: args.checksum_algo,
+            udp_checksum: args.udp_checksum,
+            truncate_at: args.truncate_at,
+            on_built: OnBuilt::default(),
+            buffer: vec![0u8; args.buffer_size.unwrap_or(0)],
+        };
+        if args.dhcp_discover {
+            builder.src_ip = Ipv4Addr::UNSPECIFIED;
+            builder.dst_ip = Ipv4Addr::BROADCAST;
+            builder.src_port = dhcp::CLIENT_PORT;
+            builder.dest_port = dhcp::SERVER_PORT;
+            builder.dst_mac = [0xff; 6];
+        }
+        if args.lldp {
+            builder.dst_mac = lldp::NEAREST_BRIDGE_MAC;
         }
+        if args.arp_gratuitous {
+            builder.dst_mac = [0xff; 6];
+        }
+        if let Some(group) = args.multicast {
+            builder.dst_ip = group;
+            builder.dst_mac = ipv4_multicast_mac(group);
+            builder.ttl = MULTICAST_TTL;
+        }
+        if let Some(group) = args.igmp_report {
+            builder.dst_ip = group;
+            if !args.dst_mac_given {
+                builder.dst_mac = ipv4_multicast_mac(group);
+            }
+            builder.ttl = 1;
+            builder.router_alert = true;
+        }
+        builder
+    }
+}
+
+/// Builds Ethernet frames with a raw payload and no IP layer, see
+/// [`PacketBuilder::l2_only`].
+pub struct L2Only<'a> {
+    /// Builder supplying the MAC addresses and buffer
+    builder: &'a mut PacketBuilder,
+    /// EtherType written in the Ethernet header
+    ethertype: EtherType,
+}
+
+impl<'a> L2Only<'a> {
+    /// Builds an Ethernet frame carrying `payload`: 14 + `payload.len()` bytes.
+    pub fn build(self, payload: &[u8]) -> &'a [u8] {
+        let total_length = 14 + payload.len();
+        self.builder.build_ethernet_header_with_type(total_length, self.ethertype);
+        self.builder.buffer[14..total_length].copy_from_slice(payload);
+        &self.builder.buffer[..total_length]
     }
 }
 
 impl PacketBuilder {
+    /// Creates a builder with default arguments and a pre-allocated buffer.
+    ///
+    /// The buffer still grows if a packet needs more than `capacity` bytes,
+    /// so the capacity only controls the initial allocation.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - Number of bytes to pre-allocate for the internal buffer
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::packet::PacketBuilder;
+    ///
+    /// let mut builder = PacketBuilder::with_capacity(9018);
+    /// let packet = builder.build_packet(&[0u8; 8000]).unwrap();
+    /// assert_eq!(packet.len(), 14 + 20 + 8 + 8000);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut builder = Self::from(&Args::default());
+        builder.buffer = vec![0u8; capacity];
+        builder
+    }
+
     /// Builds a complete network packet with the given payload.
     ///
     /// Constructs a full packet including Ethernet, IPv4, and TCP/UDP headers
     /// based on the configured protocol. All checksums are computed correctly.
+    /// With `--truncate_at`, only the first bytes of the packet are returned.
     ///
     /// # Arguments
     ///
@@ -89,9 +436,11 @@ impl PacketBuilder {
     ///
     /// # Returns
     ///
-    /// A byte slice containing the complete packet ready for transmission.
-    /// The slice references the internal buffer and is only valid until the
-    /// next call to `build_packet`.
+    /// - `Ok(&[u8])` - The complete packet ready for transmission. The slice
+    ///   references the internal buffer and is only valid until the next call
+    ///   to `build_packet`.
+    /// - `Err(CrafterError::Ipv4LengthOverflow)` if the payload does not fit
+    ///   in the 16-bit IPv4 total-length field
     ///
     /// # Examples
     ///
@@ -102,24 +451,461 @@ impl PacketBuilder {
     /// # let args = Args::parse();
     /// let mut builder = PacketBuilder::from(&args);
     /// let probe_data = b"Hello, network!";
-    /// let packet = builder.build_packet(probe_data);
+    /// let packet = builder.build_packet(probe_data).unwrap();
     ///
     /// // packet now contains: Ethernet + IPv4 + TCP/UDP + probe_data
     /// ```
-    pub fn build_packet(&mut self, payload: &[u8]) -> &[u8] {
-        match self.l4_protocol {
-            L4Protocol::Udp => self.build_udp(payload),
-            L4Protocol::Tcp => self.build_tcp(payload),
+    pub fn build_packet(&mut self, payload: &[u8]) -> Result<&[u8], CrafterError> {
+        // Every frame builder returns a prefix of the buffer
+        let length = self.build_frame(payload)?.len();
+        Ok(finish_packet(&self.buffer[..length], self.truncate_at, &mut self.on_built))
+    }
+
+    /// Registers a callback invoked with the bytes of every packet built.
+    ///
+    /// The callback runs at the end of each successful [`Self::build_packet`]
+    /// call, including the calls made by the multi-packet helpers, and for
+    /// each segment of [`Self::build_conversation`], so a consumer can tap
+    /// the packets (e.g. to feed a live sink) without changing how they are
+    /// returned. It sees the packets after `--truncate_at`. The callback
+    /// must be `Send` so the builder stays `Send`. Clones of the builder,
+    /// such as [`Self::reversed`], start without a callback. A new call
+    /// replaces it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::{Args, packet::PacketBuilder};
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// let total = Arc::new(AtomicUsize::new(0));
+    /// let counter = Arc::clone(&total);
+    ///
+    /// let mut builder = PacketBuilder::from(&Args::default());
+    /// builder.set_on_built(Box::new(move |packet| {
+    ///     counter.fetch_add(packet.len(), Ordering::Relaxed);
+    /// }));
+    /// builder.build_packet(b"one").unwrap();
+    /// builder.build_packet(b"two").unwrap();
+    /// assert_eq!(total.load(Ordering::Relaxed), 2 * (14 + 20 + 8 + 3));
+    /// ```
+    pub fn set_on_built(&mut self, callback: BuiltCallback) {
+        self.on_built = OnBuilt(Some(callback));
+    }
+
+    /// Dispatches to the builder of the configured packet type.
+    fn build_frame(&mut self, payload: &[u8]) -> Result<&[u8], CrafterError> {
+        if let Some(target) = self.ns_target {
+            return Ok(self.build_neighbor_solicit(target));
+        }
+        if self.lldpdu.is_some() {
+            return Ok(self.build_lldp());
+        }
+        if self.arp_gratuitous {
+            return Ok(self.build_arp_gratuitous());
+        }
+        if let Some(group) = self.igmp_group {
+            return self.build_igmp_report(group);
+        }
+        if self.icmp_echo {
+            return self.build_icmp_echo(payload);
+        }
+        if let (Some(ext), L4Protocol::Udp | L4Protocol::Tcp) = (self.ipv6_ext, &self.l4_protocol) {
+            return Ok(self.build_ipv6_l4(ext, payload));
+        }
+        match (&self.l4_protocol, self.checksum_target) {
+            (L4Protocol::Udp | L4Protocol::Tcp, Some(target)) => self.build_with_checksum_target(payload, target),
+            (L4Protocol::Udp, None) => self.build_udp(payload),
+            (L4Protocol::Tcp, None) => self.build_tcp(payload),
+            (L4Protocol::Icmp, _) => self.build_icmp_echo(payload),
+        }
+    }
+
+    /// Builds a TCP/UDP packet whose L4 checksum is `target`.
+    ///
+    /// Two bytes are appended to the payload: the packet is first built with
+    /// them zeroed, then rebuilt with the word from [`checksum_adjustment`].
+    /// If the payload has an odd length, the word straddles two checksum
+    /// words, so its bytes are swapped.
+    fn build_with_checksum_target(&mut self, payload: &[u8], target: u16) -> Result<&[u8], CrafterError> {
+        let checksum_offset = self.ipv4_offset() + self.ipv4_header_length()
+            + if self.l4_protocol == L4Protocol::Tcp { 16 } else { 6 };
+        let mut adjusted = payload.to_vec();
+        adjusted.extend_from_slice(&[0, 0]);
+
+        let packet = if self.l4_protocol == L4Protocol::Tcp { self.build_tcp(&adjusted)? } else { self.build_udp(&adjusted)? };
+        let current = u16::from_be_bytes([packet[checksum_offset], packet[checksum_offset + 1]]);
+        let word = checksum_adjustment(current, target).to_be_bytes();
+        let end = adjusted.len();
+        adjusted[end - 2..].copy_from_slice(&if payload.len().is_multiple_of(2) { word } else { [word[1], word[0]] });
+
+        if self.l4_protocol == L4Protocol::Tcp { self.build_tcp(&adjusted) } else { self.build_udp(&adjusted) }
+    }
+
+    /// Builds a packet and returns an owned copy of it.
+    ///
+    /// Unlike [`Self::build_packet`], the result does not borrow the builder,
+    /// at the cost of one allocation and copy per packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The application-layer data to include in the packet
+    ///
+    /// # Errors
+    ///
+    /// Returns `CrafterError::Ipv4LengthOverflow` if the payload does not fit
+    /// in the 16-bit IPv4 total-length field.
+    pub fn build_packet_owned(&mut self, payload: &[u8]) -> Result<Vec<u8>, CrafterError> {
+        self.build_packet(payload).map(<[u8]>::to_vec)
+    }
+
+    /// Builds a packet and returns it with a stable hash of its bytes.
+    ///
+    /// The hash is 64-bit FNV-1a, so it does not depend on the process or
+    /// the Rust version and can be compared across runs. Callers generating
+    /// many variants can use it to skip frames they have already written.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The application-layer data to include in the packet
+    ///
+    /// # Returns
+    ///
+    /// - `Ok((&[u8], u64))` - The packet, as returned by [`Self::build_packet`],
+    ///   and its hash
+    /// - `Err(CrafterError::Ipv4LengthOverflow)` if the payload does not fit
+    ///   in the 16-bit IPv4 total-length field
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::{Args, packet::PacketBuilder};
+    ///
+    /// let mut builder = PacketBuilder::from(&Args::default());
+    /// let (_, first) = builder.build_and_hash(b"probe").unwrap();
+    /// let (_, second) = builder.build_and_hash(b"probe").unwrap();
+    /// assert_eq!(first, second);
+    /// ```
+    pub fn build_and_hash(&mut self, payload: &[u8]) -> Result<(&[u8], u64), CrafterError> {
+        let packet = self.build_packet(payload)?;
+        Ok((packet, fnv1a_64(packet)))
+    }
+
+    /// Builds a packet and returns it as it grows layer by layer.
+    ///
+    /// The four snapshots end after the Ethernet header, the IPv4 header,
+    /// the Layer 4 header and the payload (see [`layer_ranges`]). Each one
+    /// is a prefix of the finished frame, so checksums already hold their
+    /// final values.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The application-layer data to include in the packet
+    ///
+    /// # Returns
+    ///
+    /// The snapshots, shortest first, or the error from [`Self::build_packet`]
+    /// or [`layer_ranges`] (e.g. for non-IPv4 frames such as LLDP).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::{Args, packet::PacketBuilder};
+    ///
+    /// let mut builder = PacketBuilder::from(&Args::default());
+    /// let snapshots = builder.build_with_snapshots(b"probe").unwrap();
+    /// let lengths: Vec<usize> = snapshots.iter().map(Vec::len).collect();
+    /// assert_eq!(lengths, [14, 34, 42, 47]);
+    /// ```
+    pub fn build_with_snapshots(&mut self, payload: &[u8]) -> Result<Vec<Vec<u8>>, CrafterError> {
+        let frame = self.build_packet(payload)?;
+        let ranges = layer_ranges(frame)?;
+        Ok([ranges.ethernet.end, ranges.ipv4.end, ranges.l4.end, ranges.payload.end]
+            .iter()
+            .map(|&end| frame[..end].to_vec())
+            .collect())
+    }
+
+    /// Switches to building bare Ethernet frames of the given EtherType.
+    ///
+    /// The returned [`L2Only`] uses this builder's source and destination
+    /// MAC and places the payload directly after the Ethernet header, with
+    /// no IP or Layer 4 headers and no padding.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::{Args, packet::PacketBuilder};
+    ///
+    /// let mut builder = PacketBuilder::from(&Args::default());
+    /// let frame = builder.l2_only(0x88b5).build(b"raw");
+    /// assert_eq!(frame.len(), 14 + 3);
+    /// assert_eq!(frame[12..14], [0x88, 0xb5]);
+    /// ```
+    pub fn l2_only(&mut self, ethertype: u16) -> L2Only<'_> {
+        L2Only { builder: self, ethertype: EtherType(ethertype) }
+    }
+
+    /// Sets the Layer 4 destination port used by subsequent builds.
+    ///
+    /// Unlike [`Self::with_dest_port`], this keeps the builder, including
+    /// its [`Self::set_on_built`] callback.
+    ///
+    /// # Arguments
+    ///
+    /// * `port` - The destination port
+    pub fn set_dest_port(&mut self, port: u16) {
+        self.dest_port = port;
+    }
+
+    /// Sets the Layer 4 source port used by subsequent builds.
+    ///
+    /// # Arguments
+    ///
+    /// * `port` - The source port
+    pub fn set_src_port(&mut self, port: u16) {
+        self.src_port = port;
+    }
+
+    /// Sets the destination IPv4 address used by subsequent builds.
+    ///
+    /// # Arguments
+    ///
+    /// * `ip` - The destination address
+    pub fn set_dst_ip(&mut self, ip: Ipv4Addr) {
+        self.dst_ip = ip;
+    }
+
+    /// Sets the IPv4 time to live used by subsequent builds.
+    ///
+    /// # Arguments
+    ///
+    /// * `ttl` - The TTL value (64 by default)
+    pub fn set_ttl(&mut self, ttl: u8) {
+        self.ttl = ttl;
+    }
+
+    /// Sets the Layer 4 protocol used by subsequent builds.
+    ///
+    /// # Arguments
+    ///
+    /// * `protocol` - TCP or UDP
+    pub fn set_l4_protocol(&mut self, protocol: L4Protocol) {
+        self.l4_protocol = protocol;
+    }
+
+    /// Sets the destination MAC address used by subsequent builds.
+    ///
+    /// This overrides a destination MAC derived by the builder, such as the
+    /// multicast MAC of an IGMP group.
+    ///
+    /// # Arguments
+    ///
+    /// * `mac` - The destination MAC address
+    pub fn set_dst_mac(&mut self, mac: [u8; 6]) {
+        self.dst_mac = mac;
+    }
+
+    /// Returns the builder's current addressing configuration.
+    pub fn config(&self) -> BuilderConfig {
+        BuilderConfig {
+            src_mac: MacAddr::from(self.src_mac).to_string(),
+            dst_mac: MacAddr::from(self.dst_mac).to_string(),
+            src_ip: self.src_ip,
+            dst_ip: self.dst_ip,
+            l4_protocol: self.l4_protocol.name().to_string(),
+            src_port: self.src_port,
+            dest_port: self.dest_port,
+            ip_bitfield: self.ip_bitfield,
+            ttl: self.ttl,
+        }
+    }
+
+    /// Serializes the builder's current configuration as pretty-printed JSON.
+    ///
+    /// This records the inputs a packet is built from (see [`BuilderConfig`]),
+    /// not the packet itself, so a run can be reproduced or compared later.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::{Args, packet::PacketBuilder};
+    ///
+    /// let builder = PacketBuilder::from(&Args::default());
+    /// assert!(builder.config_json().contains("\"dest_port\": 80"));
+    /// ```
+    pub fn config_json(&self) -> String {
+        serde_json::to_string_pretty(&self.config()).expect("BuilderConfig always serializes")
+    }
+
+    /// Returns the length of the frame built for an empty payload.
+    ///
+    /// This is the smallest frame the current configuration produces: all
+    /// headers, IPv4 and TCP options, and any trailer. Modes with a fixed
+    /// message (Neighbor Solicitation, LLDP, IGMP) ignore the payload, so
+    /// their size is the size of every frame. No padding to
+    /// [`MIN_FRAME_LENGTH`] is included.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::{Args, L4Protocol, packet::PacketBuilder};
+    ///
+    /// let builder = PacketBuilder::from(&Args::default());
+    /// assert_eq!(builder.min_frame_size(), 14 + 20 + 8);
+    ///
+    /// let tcp = PacketBuilder::from(&Args { l4_protocol: L4Protocol::Tcp, ..Args::default() });
+    /// assert_eq!(tcp.min_frame_size(), 14 + 20 + 20);
+    /// ```
+    pub fn min_frame_size(&self) -> usize {
+        if self.ns_target.is_some() {
+            return 14 + 40 + self.ipv6_ext.map_or(0, |_| IPV6_EXT_HEADER_LENGTH) + 24 + 8;
+        }
+        if let Some(lldpdu) = &self.lldpdu {
+            return 14 + lldpdu.len();
+        }
+        if self.arp_gratuitous {
+            return 14 + ARP_PACKET_LENGTH;
+        }
+        let l4_offset = self.ipv4_offset() + self.ipv4_header_length();
+        if self.igmp_group.is_some() || self.icmp_echo {
+            return l4_offset + 8;
+        }
+        match (&self.l4_protocol, self.ipv6_ext) {
+            (L4Protocol::Udp, Some(_)) => 14 + 40 + IPV6_EXT_HEADER_LENGTH + 8,
+            (L4Protocol::Tcp, Some(_)) => 14 + 40 + IPV6_EXT_HEADER_LENGTH + 20 + self.tcp_options().len(),
+            (L4Protocol::Udp, None) => l4_offset + 8 + self.trailer.len(),
+            (L4Protocol::Tcp, None) => l4_offset + 20 + self.tcp_options().len() + self.trailer.len(),
+            (L4Protocol::Icmp, _) => l4_offset + 8,
+        }
+    }
+
+    /// Returns the destination IPv4 address of built packets.
+    pub fn dst_ip(&self) -> Ipv4Addr {
+        self.dst_ip
+    }
+
+    /// Returns a builder for the reply direction of this builder's flow.
+    ///
+    /// The source and destination MAC addresses, IP addresses and ports are
+    /// swapped; everything else is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::{Args, packet::PacketBuilder};
+    ///
+    /// let builder = PacketBuilder::from(&Args::default());
+    /// let mut reply = builder.reversed();
+    /// let packet = reply.build_packet(b"pong").unwrap();
+    /// assert_eq!(&packet[6..12], &[0x11, 0x22, 0x33, 0x44, 0x55, 0x66]);
+    /// ```
+    pub fn reversed(&self) -> PacketBuilder {
+        let mut reply = self.clone();
+        std::mem::swap(&mut reply.src_ip, &mut reply.dst_ip);
+        std::mem::swap(&mut reply.src_mac, &mut reply.dst_mac);
+        std::mem::swap(&mut reply.src_port, &mut reply.dest_port);
+        reply
+    }
+
+    /// Returns a copy of this builder with a different destination port.
+    ///
+    /// The `with_*` methods leave `self` untouched, so one builder can
+    /// serve as the template for several variants.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::{Args, packet::PacketBuilder};
+    ///
+    /// let builder = PacketBuilder::from(&Args::default());
+    /// let mut variant = builder.with_dest_port(81);
+    /// let packet = variant.build_packet(b"probe").unwrap();
+    /// assert_eq!(&packet[36..38], &81u16.to_be_bytes());
+    /// ```
+    pub fn with_dest_port(&self, port: u16) -> PacketBuilder {
+        PacketBuilder { dest_port: port, ..self.clone() }
+    }
+
+    /// Returns a copy of this builder with a different source port.
+    pub fn with_src_port(&self, port: u16) -> PacketBuilder {
+        PacketBuilder { src_port: port, ..self.clone() }
+    }
+
+    /// Returns a copy of this builder with a different destination IPv4 address.
+    pub fn with_dst_ip(&self, ip: Ipv4Addr) -> PacketBuilder {
+        PacketBuilder { dst_ip: ip, ..self.clone() }
+    }
+
+    /// Returns a copy of this builder with a different IPv4 time to live.
+    pub fn with_ttl(&self, ttl: u8) -> PacketBuilder {
+        PacketBuilder { ttl, ..self.clone() }
+    }
+
+    /// Builds the packets of a multi-packet conversation preset.
+    ///
+    /// For [`Conversation::SynSynackAck`], this is a TCP three-way handshake
+    /// without payload:
+    /// 1. SYN from this builder's source, `seq = tcp_seq`
+    /// 2. SYN-ACK in the reverse direction, `seq = SERVER_ISN`, `ack = tcp_seq + 1`
+    /// 3. ACK from the source, `seq = tcp_seq + 1`, `ack = SERVER_ISN + 1`
+    ///
+    /// Like [`Self::build_packet`], each packet is cut to `--truncate_at`
+    /// and passed to the callback set with [`Self::set_on_built`].
+    ///
+    /// # Arguments
+    ///
+    /// * `conversation` - The preset to generate
+    ///
+    /// # Returns
+    ///
+    /// The complete packets in order, or an error if one cannot be built.
+    pub fn build_conversation(&mut self, conversation: Conversation) -> Result<Vec<Vec<u8>>, CrafterError> {
+        match conversation {
+            Conversation::SynSynackAck => {
+                let client_isn = self.tcp_seq;
+                let mut client = self.clone();
+                let mut server = self.reversed();
+                let mut packets = Vec::with_capacity(3);
+                
+                client.set_tcp_segment(TcpFlags::SYN, client_isn, 0);
+                packets.push(finish_packet(client.build_tcp(&[])?, self.truncate_at, &mut self.on_built).to_vec());
+                
+                server.set_tcp_segment(TcpFlags::SYN | TcpFlags::ACK, SERVER_ISN, client_isn.wrapping_add(1));
+                packets.push(finish_packet(server.build_tcp(&[])?, self.truncate_at, &mut self.on_built).to_vec());
+                
+                client.set_tcp_segment(TcpFlags::ACK, client_isn.wrapping_add(1), SERVER_ISN.wrapping_add(1));
+                packets.push(finish_packet(client.build_tcp(&[])?, self.truncate_at, &mut self.on_built).to_vec());
+                
+                Ok(packets)
+            }
         }
     }
 
+    /// Sets the flags, sequence and acknowledgement numbers of the next TCP segment.
+    fn set_tcp_segment(&mut self, flags: u8, seq: u32, ack: u32) {
+        self.tcp_flags = flags;
+        self.tcp_seq = seq;
+        self.tcp_ack = ack;
+        self.tcp_window = DEFAULT_TCP_WINDOW;
+    }
+
     /// Constructs a UDP packet with the given payload.
     ///
     /// Builds a complete packet with:
     /// - Ethernet header (14 bytes)
-    /// - IPv4 header (20 bytes)
+    /// - IPv4 header (20 bytes plus any options, see [`Self::ip_options`])
     /// - UDP header (8 bytes)
     /// - Payload
+    /// - Trailer, if configured (see [`Self::write_trailer`])
+    ///
+    /// The checksum field is filled in according to the configured
+    /// [`UdpChecksumMode`]. A computed checksum of 0 is sent as 0xFFFF in
+    /// `Force` mode, since both encode the same ones' complement value but 0
+    /// means "no checksum" on the wire (RFC 768). With `pseudo_src_ip` set,
+    /// the checksum is computed over that source address instead of the one
+    /// in the IPv4 header, so it deliberately fails validation.
     ///
     /// # Arguments
     ///
@@ -127,36 +913,63 @@ impl PacketBuilder {
     ///
     /// # Returns
     ///
-    /// A byte slice containing the complete UDP packet.
-    fn build_udp(&mut self, payload: &[u8]) -> &[u8] {
-        let total_length = 14 + 20 + 8 + payload.len();
+    /// A byte slice containing the complete UDP packet, or
+    /// `CrafterError::Ipv4LengthOverflow` if it is too large for IPv4.
+    fn build_udp(&mut self, payload: &[u8]) -> Result<&[u8], CrafterError> {
+        let l4_offset = self.ipv4_offset() + self.ipv4_header_length();
+        let l4_end = l4_offset + 8 + payload.len();
+        let total_length = l4_end + self.trailer.len();
         
         self.build_ethernet_header(total_length);
-        self.build_ipv4_header(total_length, IpNextHeaderProtocols::Udp, 8 + payload.len());
+        self.build_ipv4_header(total_length, IpNextHeaderProtocols::Udp, 8 + payload.len() + self.trailer.len())?;
 
-        let mut udp_packet = MutableUdpPacket::new(&mut self.buffer[34..total_length]).expect("Failed to create UDP packet");
-        udp_packet.set_source(12345);
+        let pseudo_src = self.pseudo_src_ip.unwrap_or(self.src_ip);
+        let mut udp_packet = MutableUdpPacket::new(&mut self.buffer[l4_offset..l4_end]).expect("Failed to create UDP packet");
+        udp_packet.set_source(self.src_port);
         udp_packet.set_destination(self.dest_port);
         udp_packet.set_length((8 + payload.len()) as u16);
         udp_packet.set_payload(payload);
         
-        let checksum = pnet::packet::udp::ipv4_checksum(
-            &udp_packet.to_immutable(),
-            &self.src_ip,
-            &self.dst_ip,
-        );
+        let checksum = match self.checksum_algo {
+            ChecksumAlgo::Pnet => pnet::packet::udp::ipv4_checksum(
+                &udp_packet.to_immutable(),
+                &pseudo_src,
+                &self.dst_ip,
+            ),
+            ChecksumAlgo::Manual => {
+                udp_packet.set_checksum(0);
+                ipv4_l4_checksum(pseudo_src, self.dst_ip, IpNextHeaderProtocols::Udp.0, udp_packet.packet())
+            }
+        };
+        let checksum = match self.udp_checksum {
+            UdpChecksumMode::Compute => checksum,
+            UdpChecksumMode::Zero => 0,
+            UdpChecksumMode::Force if checksum == 0 => 0xffff,
+            UdpChecksumMode::Force => checksum,
+        };
         udp_packet.set_checksum(checksum);
+        self.write_trailer(l4_end);
 
-        &self.buffer[..total_length]
+        Ok(&self.buffer[..total_length])
     }
 
     /// Constructs a TCP packet with the given payload.
     ///
     /// Builds a complete packet with:
     /// - Ethernet header (14 bytes)
-    /// - IPv4 header (20 bytes)
-    /// - TCP header (20 bytes, no options)
+    /// - IPv4 header (20 bytes plus any options, see [`Self::ip_options`])
+    /// - TCP header (20 bytes plus any options, see [`Self::tcp_options`])
     /// - Payload
+    /// - Trailer, if configured (see [`Self::write_trailer`])
+    ///
+    /// The segment is a SYN by default. When `rst` is set it is a RST instead:
+    /// only the RST flag is set, the window is zero and the payload is dropped.
+    /// `tcp_reserved` fills the low nibble of the data offset byte (reserved
+    /// bits and NS) and is covered by the checksum.
+    /// As for UDP, `pseudo_src_ip` replaces the source address in the
+    /// checksum's pseudo-header.
+    /// `tcp_checksum_override`, when set, is written as-is and no checksum
+    /// is computed.
     ///
     /// # Arguments
     ///
@@ -164,95 +977,1172 @@ impl PacketBuilder {
     ///
     /// # Returns
     ///
-    /// A byte slice containing the complete TCP packet.
-    fn build_tcp(&mut self, payload: &[u8]) -> &[u8] {
-        let total_length = 14 + 20 + 20 + payload.len();
+    /// A byte slice containing the complete TCP packet, or
+    /// `CrafterError::Ipv4LengthOverflow` if it is too large for IPv4.
+    fn build_tcp(&mut self, payload: &[u8]) -> Result<&[u8], CrafterError> {
+        let payload = if self.rst { &[][..] } else { payload };
+        let options = self.tcp_options();
+        let header_length = 20 + options.len();
+        let l4_offset = self.ipv4_offset() + self.ipv4_header_length();
+        let l4_end = l4_offset + header_length + payload.len();
+        let total_length = l4_end + self.trailer.len();
         
         self.build_ethernet_header(total_length);
-        self.build_ipv4_header(total_length, IpNextHeaderProtocols::Tcp, 20 + payload.len());
+        self.build_ipv4_header(total_length, IpNextHeaderProtocols::Tcp, header_length + payload.len() + self.trailer.len())?;
 
-        let mut tcp_packet = MutableTcpPacket::new(&mut self.buffer[34..total_length])
+        let pseudo_src = self.pseudo_src_ip.unwrap_or(self.src_ip);
+        let mut tcp_packet = MutableTcpPacket::new(&mut self.buffer[l4_offset..l4_end])
             .expect("Failed to create TCP packet");
-        tcp_packet.set_source(12345);
+        tcp_packet.set_source(self.src_port);
         tcp_packet.set_destination(self.dest_port);
-        tcp_packet.set_sequence(0);
-        tcp_packet.set_acknowledgement(0);
-        tcp_packet.set_data_offset(5);
-        tcp_packet.set_flags(0x02);
-        tcp_packet.set_window(64240);
+        tcp_packet.set_sequence(self.tcp_seq);
+        tcp_packet.set_acknowledgement(self.tcp_ack);
+        tcp_packet.set_data_offset((header_length / 4) as u8);
+        tcp_packet.set_reserved(self.tcp_reserved);
+        tcp_packet.get_options_raw_mut().copy_from_slice(&options);
+        tcp_packet.set_flags(self.tcp_flags);
+        tcp_packet.set_window(self.tcp_window);
         tcp_packet.set_urgent_ptr(0);
         tcp_packet.set_payload(payload);
         
-        let checksum = pnet::packet::tcp::ipv4_checksum(
-            &tcp_packet.to_immutable(),
-            &self.src_ip,
-            &self.dst_ip,
-        );
+        let checksum = match (self.tcp_checksum_override, self.checksum_algo) {
+            (Some(checksum), _) => checksum,
+            (None, ChecksumAlgo::Pnet) => pnet::packet::tcp::ipv4_checksum(
+                &tcp_packet.to_immutable(),
+                &pseudo_src,
+                &self.dst_ip,
+            ),
+            (None, ChecksumAlgo::Manual) => {
+                tcp_packet.set_checksum(0);
+                ipv4_l4_checksum(pseudo_src, self.dst_ip, IpNextHeaderProtocols::Tcp.0, tcp_packet.packet())
+            }
+        };
         tcp_packet.set_checksum(checksum);
+        self.write_trailer(l4_end);
 
-        &self.buffer[..total_length]
+        Ok(&self.buffer[..total_length])
     }
 
-    /// Constructs the Ethernet (Layer 2) header.
+    /// Constructs an ICMP echo request carrying `payload`.
     ///
-    /// Sets up the Ethernet frame with:
-    /// - Destination MAC address
-    /// - Source MAC address
-    /// - EtherType = 0x0800 (IPv4)
+    /// Builds a complete packet with:
+    /// - Ethernet header (14 bytes)
+    /// - IPv4 header (20 bytes plus any options, see [`Self::ip_options`]),
+    ///   protocol 1
+    /// - ICMP echo request header (8 bytes): identifier = `icmp_id`,
+    ///   sequence number = `icmp_seq`
+    /// - Payload
+    ///
+    /// Like `ping`, each call advances `icmp_seq` by one (wrapping), so the
+    /// packets of a burst can be told apart and matched with their replies.
     ///
     /// # Arguments
     ///
-    /// * `total_length` - Total packet length including all headers and payload
-    fn build_ethernet_header(&mut self, total_length: usize) {
-        let mut eth_packet = MutableEthernetPacket::new(&mut self.buffer[..total_length])
-            .expect("Failed to create Ethernet packet");
-        eth_packet.set_destination(self.dst_mac.into());
-        eth_packet.set_source(self.src_mac.into());
-        eth_packet.set_ethertype(EtherTypes::Ipv4);
+    /// * `payload` - The echo data
+    ///
+    /// # Returns
+    ///
+    /// A byte slice containing the complete ICMP packet, or
+    /// `CrafterError::Ipv4LengthOverflow` if it is too large for IPv4.
+    fn build_icmp_echo(&mut self, payload: &[u8]) -> Result<&[u8], CrafterError> {
+        let l4_offset = self.ipv4_offset() + self.ipv4_header_length();
+        let total_length = l4_offset + 8 + payload.len();
+        
+        self.build_ethernet_header(total_length);
+        self.build_ipv4_header(total_length, IpNextHeaderProtocols::Icmp, 8 + payload.len())?;
+
+        let mut echo = MutableEchoRequestPacket::new(&mut self.buffer[l4_offset..total_length])
+            .expect("Failed to create ICMP echo request packet");
+        echo.set_icmp_type(IcmpTypes::EchoRequest);
+        echo.set_icmp_code(IcmpCode(0));
+        echo.set_checksum(0);
+        echo.set_identifier(self.icmp_id);
+        echo.set_sequence_number(self.icmp_seq);
+        echo.set_payload(payload);
+
+        let checksum = pnet::packet::icmp::checksum(
+            &IcmpPacket::new(&self.buffer[l4_offset..total_length]).expect("Failed to read ICMP packet"),
+        );
+        self.buffer[l4_offset + 2..l4_offset + 4].copy_from_slice(&checksum.to_be_bytes());
+        self.icmp_seq = self.icmp_seq.wrapping_add(1);
+
+        Ok(&self.buffer[..total_length])
     }
 
-    /// Constructs the IPv4 (Layer 3) header.
+    /// Constructs an IGMPv2 Membership Report for `group`.
     ///
-    /// Sets up the IPv4 header with:
+    /// Builds a complete packet with:
+    /// - Ethernet header (14 bytes)
+    /// - IPv4 header (20 bytes plus any options, see [`Self::ip_options`]),
+    ///   protocol 2
+    /// - IGMP message (8 bytes, see [`igmp::encode_report`])
+    ///
+    /// Addressing, TTL and Router Alert are set up in `From<&Args>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The multicast group being reported
+    ///
+    /// # Returns
+    ///
+    /// A byte slice containing the complete IGMP packet.
+    fn build_igmp_report(&mut self, group: Ipv4Addr) -> Result<&[u8], CrafterError> {
+        let message = igmp::encode_report(group);
+        let l4_offset = self.ipv4_offset() + self.ipv4_header_length();
+        let total_length = l4_offset + message.len();
+        
+        self.build_ethernet_header(total_length);
+        self.build_ipv4_header(total_length, IpNextHeaderProtocols::Igmp, message.len())?;
+        self.buffer[l4_offset..total_length].copy_from_slice(&message);
+
+        Ok(&self.buffer[..total_length])
+    }
+
+    /// Copies the configured trailer into the buffer at `offset`.
+    ///
+    /// The trailer follows the L4 segment: it is counted in the IPv4 total
+    /// length but not in the UDP length field or either L4 checksum, so
+    /// receivers see a mismatch between the L3 and L4 lengths. TCP has no
+    /// length field of its own, so there the trailer reads as extra payload
+    /// with a checksum that does not cover it.
+    fn write_trailer(&mut self, offset: usize) {
+        let end = offset + self.trailer.len();
+        self.buffer[offset..end].copy_from_slice(&self.trailer);
+    }
+
+    /// Encodes the configured IPv4 options, padded to a 4-byte boundary.
+    ///
+    /// Supported options:
+    /// - Router Alert (type 148, RFC 2113), 4 bytes
+    /// - Timestamp (type 68, RFC 791) with prespecified addresses (flag 3),
+    ///   4 bytes plus 8 per address: the address and a zeroed timestamp slot
+    ///   for that router to fill in
+    ///
+    /// Padding uses End of Option List (0) bytes. Returns an empty vector
+    /// when no options are configured.
+    fn ip_options(&self) -> Vec<u8> {
+        let mut options = Vec::new();
+        if self.router_alert {
+            options.extend_from_slice(&[148, 4, 0, 0]);
+        }
+        if !self.ip_timestamp_addrs.is_empty() {
+            let length = 4 + 8 * self.ip_timestamp_addrs.len();
+            options.extend_from_slice(&[IP_OPTION_TIMESTAMP, length as u8, 5, TIMESTAMP_FLAG_PRESPECIFIED]);
+            for addr in &self.ip_timestamp_addrs {
+                options.extend_from_slice(&addr.octets());
+                options.extend_from_slice(&[0; 4]);
+            }
+        }
+        while options.len() % 4 != 0 {
+            options.push(0);
+        }
+        options
+    }
+
+    /// Returns the IPv4 flags for a packet of `ip_length` bytes.
+    ///
+    /// The flags come from the top 3 bits of `ip_bitfield`. Don't Fragment
+    /// is added for path MTU probes, and with `auto_df` once `ip_length`
+    /// reaches [`AUTO_DF_THRESHOLD_PERCENT`] of the MTU, as a stack doing
+    /// path MTU discovery would for large packets.
+    fn ip_flags(&self, ip_length: usize) -> u8 {
+        let flags = self.ip_bitfield >> 5;
+        let near_mtu = ip_length * 100 >= self.mtu * AUTO_DF_THRESHOLD_PERCENT;
+        if self.force_df || (self.auto_df && near_mtu) {
+            flags | Ipv4Flags::DontFragment
+        } else {
+            flags
+        }
+    }
+
+    /// Returns where the IPv4 header starts: after the Ethernet header, the
+    /// VLAN tag with `vlan_id` and, with `pppoe_session`, the PPPoE session
+    /// header and PPP protocol field.
+    fn ipv4_offset(&self) -> usize {
+        14 + self.vlan_id.map_or(0, |_| VLAN_TAG_LENGTH) + self.pppoe_session.map_or(0, |_| PPPOE_HEADER_LENGTH)
+    }
+
+    /// Returns the IPv4 header length in bytes, including options.
+    fn ipv4_header_length(&self) -> usize {
+        20 + self.ip_options().len()
+    }
+
+    /// Encodes the configured TCP options, padded to a 4-byte boundary.
+    ///
+    /// SYN segments with an option order preset get the preset's layout
+    /// (see [`Self::preset_tcp_options`]). Otherwise only the Maximum Segment
+    /// Size option (kind 2) is supported, and an empty vector is returned
+    /// when it is not configured.
+    fn tcp_options(&self) -> Vec<u8> {
+        let mut options = Vec::new();
+        if let (Some(order), true) = (self.tcp_option_order, self.tcp_flags & TcpFlags::SYN != 0) {
+            options = self.preset_tcp_options(order);
+        } else if let Some(mss) = self.tcp_mss {
+            options.extend_from_slice(&[2, 4]);
+            options.extend_from_slice(&mss.to_be_bytes());
+        }
+        while options.len() % 4 != 0 {
+            options.push(0);
+        }
+        options
+    }
+
+    /// Lays out MSS, SACK permitted, Timestamps and Window Scale in an OS's order.
+    ///
+    /// The MSS is the configured one or [`DEFAULT_PRESET_MSS`]. NOP (kind 1)
+    /// and EOL (kind 0) bytes are placed where the OS puts them; padding to
+    /// a 4-byte boundary is left to [`Self::tcp_options`].
+    fn preset_tcp_options(&self, order: TcpOptionOrder) -> Vec<u8> {
+        let mss = self.tcp_mss.unwrap_or(DEFAULT_PRESET_MSS).to_be_bytes();
+        let mss = [2, 4, mss[0], mss[1]];
+        let sack_permitted = [4, 2];
+        let tsval = PRESET_TSVAL.to_be_bytes();
+        let timestamps = [8, 10, tsval[0], tsval[1], tsval[2], tsval[3], 0, 0, 0, 0];
+        let shift = match order {
+            TcpOptionOrder::Linux => 7,
+            TcpOptionOrder::Windows => 8,
+            TcpOptionOrder::Macos => 6,
+        };
+        let window_scale = [3, 3, shift];
+        
+        let parts: &[&[u8]] = match order {
+            TcpOptionOrder::Linux => &[&mss, &sack_permitted, &timestamps, &[1], &window_scale],
+            TcpOptionOrder::Windows => &[&mss, &[1], &window_scale, &[1, 1], &sack_permitted],
+            TcpOptionOrder::Macos => {
+                &[&mss, &[1], &window_scale, &[1, 1], &timestamps, &sack_permitted, &[0]]
+            }
+        };
+        parts.concat()
+    }
+
+    /// Constructs an LLDP frame.
+    ///
+    /// Builds a complete frame with:
+    /// - Ethernet header (14 bytes, EtherType 0x88CC)
+    /// - The LLDPDU encoded from the arguments (see
+    ///   [`lldp::encode_lldpdu`])
+    ///
+    /// # Returns
+    ///
+    /// A byte slice containing the complete LLDP frame.
+    fn build_lldp(&mut self) -> &[u8] {
+        let lldpdu_length = self.lldpdu.as_ref().map_or(0, Vec::len);
+        let total_length = 14 + lldpdu_length;
+        self.build_ethernet_header_with_type(total_length, EtherTypes::Lldp);
+        if let Some(lldpdu) = &self.lldpdu {
+            self.buffer[14..total_length].copy_from_slice(lldpdu);
+        }
+
+        &self.buffer[..total_length]
+    }
+
+    /// Constructs a gratuitous ARP reply announcing `src_ip`.
+    ///
+    /// Builds a complete frame with:
+    /// - Ethernet header (14 bytes, EtherType 0x0806)
+    /// - ARP reply (28 bytes) for Ethernet/IPv4 whose sender and target
+    ///   protocol addresses are both `src_ip`, with `src_mac` as the sender
+    ///   hardware address and the broadcast MAC as the target
+    ///
+    /// Hosts that already use `src_ip` should report an address conflict.
+    ///
+    /// # Returns
+    ///
+    /// A byte slice containing the complete ARP frame.
+    fn build_arp_gratuitous(&mut self) -> &[u8] {
+        let total_length = 14 + ARP_PACKET_LENGTH;
+        self.build_ethernet_header_with_type(total_length, EtherTypes::Arp);
+
+        let mut arp_packet = MutableArpPacket::new(&mut self.buffer[14..total_length])
+            .expect("Failed to create ARP packet");
+        arp_packet.set_hardware_type(ArpHardwareTypes::Ethernet);
+        arp_packet.set_protocol_type(EtherTypes::Ipv4);
+        arp_packet.set_hw_addr_len(6);
+        arp_packet.set_proto_addr_len(4);
+        arp_packet.set_operation(ArpOperations::Reply);
+        arp_packet.set_sender_hw_addr(self.src_mac.into());
+        arp_packet.set_sender_proto_addr(self.src_ip);
+        arp_packet.set_target_hw_addr(MacAddr::broadcast());
+        arp_packet.set_target_proto_addr(self.src_ip);
+
+        &self.buffer[..total_length]
+    }
+
+    /// Constructs an ICMPv6 Neighbor Solicitation for `target`.
+    ///
+    /// Builds a complete packet with:
+    /// - Ethernet header (14 bytes, EtherType 0x86DD)
+    /// - IPv6 header (40 bytes) addressed to the target's solicited-node
+    ///   multicast group, hop limit 255, with the configured flow label
+    /// - The `ipv6_ext` extension header (8 bytes), if set, chained so the
+    ///   IPv6 Next Header names it and it names ICMPv6
+    /// - ICMPv6 NS (24 bytes) with a Source Link-Layer Address option (8 bytes)
+    ///
+    /// With `auto_l2` set, the Ethernet destination is the solicited-node
+    /// multicast MAC; otherwise the configured destination MAC is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The IPv6 address whose link-layer address is being resolved
+    ///
+    /// # Returns
+    ///
+    /// A byte slice containing the complete Neighbor Solicitation frame.
+    fn build_neighbor_solicit(&mut self, target: Ipv6Addr) -> &[u8] {
+        let icmp_length = 24 + 8;
+        let ext_length = self.ipv6_ext.map_or(0, |_| IPV6_EXT_HEADER_LENGTH);
+        let icmp_start = 14 + 40 + ext_length;
+        let total_length = icmp_start + icmp_length;
+        let dst_ipv6 = solicited_node_address(target);
+
+        if self.auto_l2 {
+            self.dst_mac = ipv6_solicited_node_mac(target);
+        }
+        self.build_ethernet_header_with_type(total_length, EtherTypes::Ipv6);
+
+        let mut ipv6_packet = MutableIpv6Packet::new(&mut self.buffer[14..total_length])
+            .expect("Failed to create IPv6 packet");
+        ipv6_packet.set_version(6);
+        ipv6_packet.set_traffic_class(0);
+        ipv6_packet.set_flow_label(self.flow_label);
+        ipv6_packet.set_payload_length((ext_length + icmp_length) as u16);
+        ipv6_packet.set_next_header(self.ipv6_ext.map_or(IpNextHeaderProtocols::Icmpv6, ipv6_ext_protocol));
+        ipv6_packet.set_hop_limit(255);
+        ipv6_packet.set_source(self.src_ipv6);
+        ipv6_packet.set_destination(dst_ipv6);
+
+        if let Some(ext) = self.ipv6_ext {
+            let header = ipv6_ext_header(ext, IpNextHeaderProtocols::Icmpv6);
+            self.buffer[54..icmp_start].copy_from_slice(&header);
+        }
+
+        let mut ns_packet = MutableNeighborSolicitPacket::new(&mut self.buffer[icmp_start..total_length])
+            .expect("Failed to create Neighbor Solicitation packet");
+        ns_packet.set_icmpv6_type(Icmpv6Types::NeighborSolicit);
+        ns_packet.set_icmpv6_code(pnet::packet::icmpv6::Icmpv6Code(0));
+        ns_packet.set_reserved(0);
+        ns_packet.set_target_addr(target);
+
+        // Source Link-Layer Address option: type 1, length 1 (8 bytes)
+        let option_start = icmp_start + 24;
+        self.buffer[option_start] = 1;
+        self.buffer[option_start + 1] = 1;
+        self.buffer[option_start + 2..option_start + 8].copy_from_slice(&self.src_mac);
+
+        // The pseudo-header names ICMPv6, the final upper-layer protocol,
+        // whatever extension headers precede it
+        let checksum = pnet::packet::icmpv6::checksum(
+            &Icmpv6Packet::new(&self.buffer[icmp_start..total_length]).expect("Failed to read ICMPv6 packet"),
+            &self.src_ipv6,
+            &dst_ipv6,
+        );
+        self.buffer[icmp_start + 2..icmp_start + 4].copy_from_slice(&checksum.to_be_bytes());
+
+        &self.buffer[..total_length]
+    }
+
+    /// Constructs a TCP or UDP packet over IPv6, per `l4_protocol`.
+    ///
+    /// Builds a complete packet with:
+    /// - Ethernet header (14 bytes, EtherType 0x86DD)
+    /// - IPv6 header (40 bytes) from `src_ipv6` to `dst_ipv6`, hop limit
+    ///   `ttl`, with the configured flow label
+    /// - The `ext` extension header (8 bytes), chained so the IPv6 Next
+    ///   Header names it and it names TCP or UDP
+    /// - TCP header (20 bytes plus any options, as for IPv4) or UDP header
+    ///   (8 bytes)
+    /// - Payload
+    ///
+    /// IPv4-only settings (IP options, trailer, checksum overrides) do not
+    /// apply. A UDP checksum of 0 is sent as 0xFFFF, since IPv6 forbids
+    /// omitting it (RFC 8200).
+    ///
+    /// # Arguments
+    ///
+    /// * `ext` - The extension header placed between IPv6 and the L4 header
+    /// * `payload` - The data to include in the segment
+    ///
+    /// # Returns
+    ///
+    /// A byte slice containing the complete IPv6 frame.
+    fn build_ipv6_l4(&mut self, ext: Ipv6ExtHeader, payload: &[u8]) -> &[u8] {
+        let tcp = self.l4_protocol == L4Protocol::Tcp;
+        let payload = if tcp && self.rst { &[][..] } else { payload };
+        let options = if tcp { self.tcp_options() } else { Vec::new() };
+        let (protocol, header_length) = if tcp {
+            (IpNextHeaderProtocols::Tcp, 20 + options.len())
+        } else {
+            (IpNextHeaderProtocols::Udp, 8)
+        };
+        let l4_offset = 14 + 40 + IPV6_EXT_HEADER_LENGTH;
+        let total_length = l4_offset + header_length + payload.len();
+
+        self.build_ethernet_header_with_type(total_length, EtherTypes::Ipv6);
+
+        let mut ipv6_packet = MutableIpv6Packet::new(&mut self.buffer[14..total_length])
+            .expect("Failed to create IPv6 packet");
+        ipv6_packet.set_version(6);
+        ipv6_packet.set_traffic_class(0);
+        ipv6_packet.set_flow_label(self.flow_label);
+        ipv6_packet.set_payload_length((total_length - 54) as u16);
+        ipv6_packet.set_next_header(ipv6_ext_protocol(ext));
+        ipv6_packet.set_hop_limit(self.ttl);
+        ipv6_packet.set_source(self.src_ipv6);
+        ipv6_packet.set_destination(self.dst_ipv6);
+        self.buffer[54..l4_offset].copy_from_slice(&ipv6_ext_header(ext, protocol));
+
+        // The pseudo-header names TCP or UDP, the final upper-layer protocol,
+        // not the extension header
+        let (src_ipv6, dst_ipv6) = (self.src_ipv6, self.dst_ipv6);
+        if tcp {
+            let mut tcp_packet = MutableTcpPacket::new(&mut self.buffer[l4_offset..total_length])
+                .expect("Failed to create TCP packet");
+            tcp_packet.set_source(self.src_port);
+            tcp_packet.set_destination(self.dest_port);
+            tcp_packet.set_sequence(self.tcp_seq);
+            tcp_packet.set_acknowledgement(self.tcp_ack);
+            tcp_packet.set_data_offset((header_length / 4) as u8);
+            tcp_packet.set_reserved(self.tcp_reserved);
+            tcp_packet.get_options_raw_mut().copy_from_slice(&options);
+            tcp_packet.set_flags(self.tcp_flags);
+            tcp_packet.set_window(self.tcp_window);
+            tcp_packet.set_urgent_ptr(0);
+            tcp_packet.set_payload(payload);
+            let checksum = pnet::packet::tcp::ipv6_checksum(&tcp_packet.to_immutable(), &src_ipv6, &dst_ipv6);
+            tcp_packet.set_checksum(checksum);
+        } else {
+            let mut udp_packet = MutableUdpPacket::new(&mut self.buffer[l4_offset..total_length])
+                .expect("Failed to create UDP packet");
+            udp_packet.set_source(self.src_port);
+            udp_packet.set_destination(self.dest_port);
+            udp_packet.set_length((8 + payload.len()) as u16);
+            udp_packet.set_payload(payload);
+            let checksum = pnet::packet::udp::ipv6_checksum(&udp_packet.to_immutable(), &src_ipv6, &dst_ipv6);
+            udp_packet.set_checksum(if checksum == 0 { 0xffff } else { checksum });
+        }
+
+        &self.buffer[..total_length]
+    }
+
+    /// Constructs the Ethernet (Layer 2) header.
+    ///
+    /// Sets up the Ethernet frame with:
+    /// - Destination MAC address
+    /// - Source MAC address
+    /// - EtherType = 0x0800 (IPv4), or 0x8864 (PPPoE session) with
+    ///   `pppoe_session`; `ethertype_override` replaces either, so the frame
+    ///   can claim another protocol while still carrying IPv4
+    /// - With `vlan_id`, an 802.1Q tag (EtherType 0x8100, PCP `vlan_pcp`)
+    ///   in front of that EtherType
+    /// - With `pppoe_session`, the PPPoE session header and the PPP protocol
+    ///   field (0x0021, IPv4) that precede the IPv4 header
+    ///
+    /// # Arguments
+    ///
+    /// * `total_length` - Total packet length including all headers and payload
+    fn build_ethernet_header(&mut self, total_length: usize) {
+        let default_ethertype = if self.pppoe_session.is_some() { EtherTypes::PppoeSession } else { EtherTypes::Ipv4 };
+        let ethertype = self.ethertype_override.map_or(default_ethertype, EtherType);
+        let mut pppoe_start = 14;
+        match self.vlan_id {
+            Some(vlan_id) => {
+                // The tag sits between the MACs and the inner EtherType
+                self.build_ethernet_header_with_type(total_length, EtherTypes::Vlan);
+                let tci = (u16::from(self.vlan_pcp) << 13) | vlan_id;
+                self.buffer[14..16].copy_from_slice(&tci.to_be_bytes());
+                self.buffer[16..18].copy_from_slice(&ethertype.0.to_be_bytes());
+                pppoe_start += VLAN_TAG_LENGTH;
+            }
+            None => self.build_ethernet_header_with_type(total_length, ethertype),
+        }
+
+        if let Some(session) = self.pppoe_session {
+            // PPPoE length covers the PPP protocol field and the IPv4 packet
+            let pppoe_length = (total_length - pppoe_start - 6) as u16;
+            let header = &mut self.buffer[pppoe_start..pppoe_start + PPPOE_HEADER_LENGTH];
+            header[0] = PPPOE_VERSION_TYPE;
+            header[1] = 0;
+            header[2..4].copy_from_slice(&session.to_be_bytes());
+            header[4..6].copy_from_slice(&pppoe_length.to_be_bytes());
+            header[6..8].copy_from_slice(&PPP_PROTOCOL_IPV4.to_be_bytes());
+        }
+    }
+
+    /// Constructs the Ethernet (Layer 2) header with an explicit EtherType.
+    ///
+    /// # Arguments
+    ///
+    /// * `total_length` - Total packet length including all headers and payload
+    /// * `ethertype` - EtherType identifying the Layer 3 protocol
+    fn build_ethernet_header_with_type(&mut self, total_length: usize, ethertype: EtherType) {
+        // Every build path starts here, so this is where the buffer is grown
+        if self.buffer.len() < total_length {
+            self.buffer.resize(total_length, 0);
+        }
+        
+        let mut eth_packet = MutableEthernetPacket::new(&mut self.buffer[..total_length])
+            .expect("Failed to create Ethernet packet");
+        eth_packet.set_destination(self.dst_mac.into());
+        eth_packet.set_source(self.src_mac.into());
+        eth_packet.set_ethertype(ethertype);
+    }
+
+    /// Constructs the IPv4 (Layer 3) header.
+    ///
+    /// Sets up the IPv4 header with:
     /// - Version = 4
-    /// - Header length = 5 (20 bytes, no options)
-    /// - DSCP/ECN = 0
+    /// - Header length = 5 plus one per 4 bytes of options
+    /// - Options from [`Self::ip_options`]
+    /// - DSCP from `dscp` (0 unless set with `--dscp`), ECN from `ecn` (0
+    ///   unless `--mark_ce` set Congestion Experienced)
     /// - Total length = IP header + payload
     /// - Identification = 0
-    /// - Flags and fragment offset from `ip_bitfield`
-    /// - TTL = 64
+    /// - Flags and fragment offset from `ip_bitfield`, plus Don't Fragment
+    ///   from `auto_df` (see [`Self::ip_flags`])
+    /// - TTL (64 unless changed with [`Self::set_ttl`])
     /// - Protocol (TCP or UDP)
     /// - Source and destination IP addresses
-    /// - Correct header checksum
+    /// - Version/IHL byte replaced by `ipv4_vihl`, if set, for fuzzing
+    /// - Correct header checksum, computed with the configured [`ChecksumAlgo`]
     ///
     /// # Arguments
     ///
     /// * `total_length` - Total packet length including Ethernet header
     /// * `protocol` - Next-level protocol (TCP or UDP)
     /// * `payload_length` - Length of Layer 4 header + data
+    ///
+    /// # Errors
+    ///
+    /// Returns `CrafterError::Ipv4LengthOverflow` if the IP header plus
+    /// `payload_length` exceeds 65535 bytes, the largest value the
+    /// total-length field can hold.
     fn build_ipv4_header(
         &mut self,
         total_length: usize,
         protocol: IpNextHeaderProtocol,
         payload_length: usize,
-    ) {
-        let mut ipv4_packet = MutableIpv4Packet::new(&mut self.buffer[14..total_length])
+    ) -> Result<(), CrafterError> {
+        let options = self.ip_options();
+        let header_length = 20 + options.len();
+        if header_length + payload_length > u16::MAX as usize {
+            return Err(CrafterError::Ipv4LengthOverflow {
+                length: header_length + payload_length,
+            });
+        }
+        let flags = self.ip_flags(header_length + payload_length);
+        let ipv4_offset = self.ipv4_offset();
+        let mut ipv4_packet = MutableIpv4Packet::new(&mut self.buffer[ipv4_offset..total_length])
             .expect("Failed to create IPv4 packet");
         
         ipv4_packet.set_version(4);
-        ipv4_packet.set_header_length(5);
-        ipv4_packet.set_dscp(0);
-        ipv4_packet.set_ecn(0);
-        ipv4_packet.set_total_length((20 + payload_length) as u16);
+        ipv4_packet.set_header_length((header_length / 4) as u8);
+        ipv4_packet.get_options_raw_mut().copy_from_slice(&options);
+        ipv4_packet.set_dscp(self.dscp);
+        ipv4_packet.set_ecn(self.ecn);
+        ipv4_packet.set_total_length((header_length + payload_length) as u16);
         ipv4_packet.set_identification(0);
-        ipv4_packet.set_flags(self.ip_bitfield >> 5);
+        ipv4_packet.set_flags(flags);
         ipv4_packet.set_fragment_offset((self.ip_bitfield as u16 & 0x1F) << 8);
-        ipv4_packet.set_ttl(64);
+        ipv4_packet.set_ttl(self.ttl);
         ipv4_packet.set_next_level_protocol(protocol);
         ipv4_packet.set_source(self.src_ip);
         ipv4_packet.set_destination(self.dst_ip);
+        if let Some(vihl) = self.ipv4_vihl {
+            ipv4_packet.set_version(vihl >> 4);
+            ipv4_packet.set_header_length(vihl & 0x0f);
+        }
         
-        let checksum = ipv4_checksum(&ipv4_packet.to_immutable());
+        // With an `ipv4_vihl` lie the IHL no longer says which bytes to sum,
+        // so the checksum is taken over the header actually built
+        let checksum = match (self.checksum_algo, self.ipv4_vihl) {
+            (ChecksumAlgo::Pnet, None) => ipv4_checksum(&ipv4_packet.to_immutable()),
+            (ChecksumAlgo::Manual, _) | (_, Some(_)) => {
+                ipv4_packet.set_checksum(0);
+                internet_checksum(&ipv4_packet.packet()[..header_length])
+            }
+        };
         ipv4_packet.set_checksum(checksum);
+        
+        Ok(())
+    }
+}
+
+/// Length of the extension headers built for `--ipv6_ext`, in bytes.
+pub const IPV6_EXT_HEADER_LENGTH: usize = 8;
+
+/// Returns the Next Header value that identifies `ext`.
+fn ipv6_ext_protocol(ext: Ipv6ExtHeader) -> IpNextHeaderProtocol {
+    match ext {
+        Ipv6ExtHeader::HopByHop => IpNextHeaderProtocols::Hopopt,
+        Ipv6ExtHeader::Fragment => IpNextHeaderProtocols::Ipv6Frag,
+    }
+}
+
+/// Encodes an 8-byte IPv6 extension header followed by `next_header`.
+///
+/// Hop-by-Hop carries one PadN option filling the remaining six bytes.
+/// Fragment describes an atomic fragment: offset 0, More Fragments clear,
+/// identification 0.
+fn ipv6_ext_header(ext: Ipv6ExtHeader, next_header: IpNextHeaderProtocol) -> [u8; IPV6_EXT_HEADER_LENGTH] {
+    match ext {
+        // Hdr Ext Len 0 (8 bytes), then PadN: type 1, 4 bytes of zeros
+        Ipv6ExtHeader::HopByHop => [next_header.0, 0, 1, 4, 0, 0, 0, 0],
+        Ipv6ExtHeader::Fragment => [next_header.0, 0, 0, 0, 0, 0, 0, 0],
+    }
+}
+
+/// Hashes a list of frames for change detection across runs.
+///
+/// A single frame hashes to the same value [`PacketBuilder::build_and_hash`]
+/// returns for it. Several frames hash to the FNV-1a hash of their
+/// individual hashes, so order matters.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::{frames_hash, PacketBuilder}};
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// let (frame, hash) = builder.build_and_hash(b"probe").unwrap();
+/// assert_eq!(frames_hash(&[frame.to_vec()]), hash);
+/// ```
+pub fn frames_hash(frames: &[Vec<u8>]) -> u64 {
+    match frames {
+        [frame] => fnv1a_64(frame),
+        _ => {
+            let hashes: Vec<u8> = frames.iter().flat_map(|frame| fnv1a_64(frame).to_be_bytes()).collect();
+            fnv1a_64(&hashes)
+        }
+    }
+}
+
+/// Computes the 64-bit FNV-1a hash of `bytes`.
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes
+        .iter()
+        .fold(OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+/// Returns the offset of the IPv4 header in an Ethernet frame.
+///
+/// This is the one place that decides whether a frame carries IPv4 and
+/// where its header starts; [`layer_ranges`] and everything built on it
+/// go through here. An 802.1Q tag is skipped, as built with `--vlan_id`,
+/// and so is a PPPoE session header whose PPP protocol is IPv4, as built
+/// with `--pppoe_session`.
+///
+/// # Returns
+///
+/// `Some(offset)` if the frame carries IPv4, `None` otherwise.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::{ipv4_header_offset, PacketBuilder}};
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// assert_eq!(ipv4_header_offset(builder.build_packet(b"probe").unwrap()), Some(14));
+/// assert_eq!(ipv4_header_offset(&[0u8; 14]), None);
+/// ```
+pub fn ipv4_header_offset(frame: &[u8]) -> Option<usize> {
+    let read_u16 = |at: usize| Some(u16::from_be_bytes([*frame.get(at)?, *frame.get(at + 1)?]));
+    let mut offset = 14;
+    if read_u16(offset - 2)? == EtherTypes::Vlan.0 {
+        offset += VLAN_TAG_LENGTH;
+    }
+    match read_u16(offset - 2)? {
+        ethertype if ethertype == EtherTypes::Ipv4.0 => Some(offset),
+        // The PPP protocol field closes the PPPoE session header
+        ethertype if ethertype == EtherTypes::PppoeSession.0 => {
+            (read_u16(offset + PPPOE_HEADER_LENGTH - 2)? == PPP_PROTOCOL_IPV4).then_some(offset + PPPOE_HEADER_LENGTH)
+        }
+        _ => None,
+    }
+}
+
+/// Splits an Ethernet/IPv4 frame into the byte ranges of its layers.
+///
+/// The IPv4 header is located with [`ipv4_header_offset`].
+/// The IPv4 header length comes from the IHL field and the Layer 4 header
+/// length from the protocol: the TCP data offset, or 8 bytes for UDP and
+/// ICMP. The payload ends at the IPv4 total length, so Ethernet padding
+/// after the IP packet is excluded.
+///
+/// # Arguments
+///
+/// * `frame` - The complete frame
+///
+/// # Errors
+///
+/// Returns `CrafterError::MalformedFrame` if the frame is not IPv4, is
+/// truncated, has inconsistent length fields or carries another protocol.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::{layer_ranges, PacketBuilder}};
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// let frame = builder.build_packet(b"probe").unwrap();
+/// let ranges = layer_ranges(frame).unwrap();
+/// assert_eq!(&frame[ranges.payload], b"probe");
+/// ```
+pub fn layer_ranges(frame: &[u8]) -> Result<LayerRanges, CrafterError> {
+    let malformed = |reason: &str| CrafterError::MalformedFrame { reason: reason.to_string() };
+    
+    if frame.len() < 14 {
+        return Err(malformed("shorter than an Ethernet header"));
+    }
+    let ip_start = ipv4_header_offset(frame).ok_or_else(|| malformed("EtherType is not IPv4"))?;
+    let ihl = *frame.get(ip_start).ok_or_else(|| malformed("missing IPv4 header"))? as usize & 0x0f;
+    let l4_start = ip_start + ihl * 4;
+    if ihl < 5 || frame.len() < l4_start {
+        return Err(malformed("truncated or invalid IPv4 header"));
+    }
+    let total_length = u16::from_be_bytes([frame[ip_start + 2], frame[ip_start + 3]]) as usize;
+    let ip_end = ip_start + total_length;
+    if total_length < ihl * 4 || frame.len() < ip_end {
+        return Err(malformed("IPv4 total length does not match the frame"));
+    }
+    
+    let protocol = IpNextHeaderProtocol(frame[ip_start + 9]);
+    let l4_length = match protocol {
+        IpNextHeaderProtocols::Tcp => {
+            let offset = *frame.get(l4_start + 12).ok_or_else(|| malformed("truncated TCP header"))?;
+            (offset >> 4) as usize * 4
+        }
+        IpNextHeaderProtocols::Udp | IpNextHeaderProtocols::Icmp => 8,
+        _ => return Err(malformed("unsupported Layer 4 protocol")),
+    };
+    let l4_end = l4_start + l4_length;
+    if l4_length < 8 || l4_end > ip_end {
+        return Err(malformed("truncated or invalid Layer 4 header"));
+    }
+    
+    Ok(LayerRanges {
+        ethernet: 0..ip_start,
+        ipv4: ip_start..l4_start,
+        l4: l4_start..l4_end,
+        payload: l4_end..ip_end,
+    })
+}
+
+/// Compares a frame against an expected hex fixture.
+///
+/// Whitespace in `expected_hex` is ignored, so fixtures can be split across
+/// lines. On mismatch, the message gives the first differing offset and the
+/// layer it falls in, using [`layer_ranges`] of the expected frame (or of
+/// `actual` if the fixture does not parse). Bytes past the IPv4 packet are
+/// reported as trailer.
+///
+/// # Errors
+///
+/// Returns a message if the fixture is not valid hex or the bytes differ.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::assert_matches_hex;
+///
+/// assert!(assert_matches_hex(&[0xde, 0xad], "de ad").is_ok());
+/// assert!(assert_matches_hex(&[0xde, 0xad], "dead beef").is_err());
+/// ```
+pub fn assert_matches_hex(actual: &[u8], expected_hex: &str) -> Result<(), String> {
+    let digits: String = expected_hex.chars().filter(|c| !c.is_whitespace()).collect();
+    let expected = hex::decode(&digits).map_err(|e| format!("Invalid hex fixture: {}", e))?;
+    
+    let offset = match actual.iter().zip(&expected).position(|(a, e)| a != e) {
+        Some(offset) => offset,
+        None if actual.len() == expected.len() => return Ok(()),
+        None => actual.len().min(expected.len()),
+    };
+    
+    let byte = |bytes: &[u8]| bytes.get(offset).map_or("end of frame".to_string(), |b| format!("0x{:02x}", b));
+    let layer = match layer_ranges(&expected).or_else(|_| layer_ranges(actual)) {
+        Ok(ranges) => [
+            ("Ethernet header", ranges.ethernet),
+            ("IPv4 header", ranges.ipv4),
+            ("Layer 4 header", ranges.l4),
+            ("payload", ranges.payload),
+        ]
+        .into_iter()
+        .find(|(_, range)| range.contains(&offset))
+        .map_or("trailer".to_string(), |(name, range)| format!("{}, byte {}", name, offset - range.start)),
+        Err(_) => "unknown layer".to_string(),
+    };
+    
+    Err(format!(
+        "Frames differ at offset {} ({}): expected {}, got {} (expected {} bytes, got {})",
+        offset,
+        layer,
+        byte(&expected),
+        byte(actual),
+        expected.len(),
+        actual.len()
+    ))
+}
+
+/// Returns the solicited-node multicast address for an IPv6 address.
+///
+/// The address is `ff02::1:ffXX:XXXX`, where the low 24 bits are taken from
+/// `target` (RFC 4291, section 2.7.1).
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::solicited_node_address;
+///
+/// let target = "2001:db8::1:2345:6789".parse().unwrap();
+/// assert_eq!(solicited_node_address(target), "ff02::1:ff45:6789".parse::<std::net::Ipv6Addr>().unwrap());
+/// ```
+pub fn solicited_node_address(target: Ipv6Addr) -> Ipv6Addr {
+    let t = target.octets();
+    let mut octets = [0u8; 16];
+    octets[0] = 0xff;
+    octets[1] = 0x02;
+    octets[11] = 0x01;
+    octets[12] = 0xff;
+    octets[13..].copy_from_slice(&t[13..]);
+    Ipv6Addr::from(octets)
+}
+
+/// Returns the Ethernet multicast MAC for a target's solicited-node group.
+///
+/// IPv6 multicast maps to `33:33` followed by the low 32 bits of the group
+/// address, so a Neighbor Solicitation for `target` is sent to
+/// `33:33:ff` plus the last 3 bytes of `target`.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::ipv6_solicited_node_mac;
+///
+/// let target = "fe80::aabb:ccdd".parse().unwrap();
+/// assert_eq!(ipv6_solicited_node_mac(target), [0x33, 0x33, 0xff, 0xbb, 0xcc, 0xdd]);
+/// ```
+pub fn ipv6_solicited_node_mac(target: Ipv6Addr) -> [u8; 6] {
+    let t = target.octets();
+    [0x33, 0x33, 0xff, t[13], t[14], t[15]]
+}
+
+/// Returns the Ethernet multicast MAC for an IPv4 multicast group.
+///
+/// IPv4 multicast maps to `01:00:5e` followed by the low 23 bits of the
+/// group address (RFC 1112, section 6.4).
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::ipv4_multicast_mac;
+///
+/// let group = "239.129.2.3".parse().unwrap();
+/// assert_eq!(ipv4_multicast_mac(group), [0x01, 0x00, 0x5e, 0x01, 0x02, 0x03]);
+/// ```
+pub fn ipv4_multicast_mac(group: Ipv4Addr) -> [u8; 6] {
+    let g = group.octets();
+    [0x01, 0x00, 0x5e, g[1] & 0x7f, g[2], g[3]]
+}
+
+/// Returns whether a built packet's checksums deserve a zero-value warning.
+///
+/// A computed checksum can legitimately come out as `0x0000`, but some tools
+/// read a zero checksum as "not computed". `main` uses this under `--verbose`
+/// to point that out.
+///
+/// # Arguments
+///
+/// * `ip_checksum` - The IPv4 header checksum
+/// * `l4_checksum` - The TCP/UDP checksum
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::would_warn_zero_checksum;
+///
+/// assert!(would_warn_zero_checksum(0x0000, 0x1234));
+/// assert!(!would_warn_zero_checksum(0xabcd, 0x1234));
+/// ```
+pub fn would_warn_zero_checksum(ip_checksum: u16, l4_checksum: u16) -> bool {
+    ip_checksum == 0 || l4_checksum == 0
+}
+
+/// Checks a frame against basic sanity rules and describes every problem.
+///
+/// Catches packets a host or middlebox would likely drop: a multicast
+/// source MAC, an IPv4 header that is cut short, has the wrong version or
+/// a bad checksum, a total length larger than the frame, a zero TTL, and
+/// TCP/UDP headers with a bad length or checksum. A UDP checksum of 0
+/// means "no checksum" and is accepted. The IPv4 header is located with
+/// [`ipv4_header_offset`]; frames without one only get the Ethernet checks.
+///
+/// # Returns
+///
+/// One human-readable warning per problem; empty for a clean frame.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::{basic_sanity, PacketBuilder}};
+///
+/// let mut frame = PacketBuilder::from(&Args::default()).build_packet(b"probe").unwrap().to_vec();
+/// assert!(basic_sanity(&frame).is_empty());
+///
+/// frame[14 + 8] = 0; // TTL, without fixing the checksum
+/// assert_eq!(basic_sanity(&frame), ["IPv4 checksum invalid", "IPv4 TTL is zero"]);
+/// ```
+pub fn basic_sanity(frame: &[u8]) -> Vec<String> {
+    let mut warnings: Vec<String> = Vec::new();
+    if frame.len() < 14 {
+        warnings.push("frame shorter than an Ethernet header".into());
+        return warnings;
+    }
+    if frame[6] & 0x01 != 0 {
+        warnings.push("source MAC is multicast".into());
+    }
+    let Some(start) = ipv4_header_offset(frame) else {
+        return warnings;
+    };
+
+    let Some(ip) = Ipv4Packet::new(&frame[start..]) else {
+        warnings.push("frame ends inside the IPv4 header".into());
+        return warnings;
+    };
+    let header_length = ip.get_header_length() as usize * 4;
+    let total_length = ip.get_total_length() as usize;
+    if ip.get_version() != 4 {
+        warnings.push("IP version is not 4".into());
+    }
+    if header_length < 20 || header_length > frame.len() - start {
+        warnings.push("IPv4 header length invalid".into());
+        return warnings;
+    }
+    if total_length > frame.len() - start {
+        warnings.push("IP total length exceeds frame length".into());
+    }
+    if total_length < header_length {
+        warnings.push("IP total length shorter than its header".into());
+    }
+    if ipv4_checksum(&ip) != ip.get_checksum() {
+        warnings.push("IPv4 checksum invalid".into());
+    }
+    if ip.get_ttl() == 0 {
+        warnings.push("IPv4 TTL is zero".into());
+    }
+
+    let ip_end = (start + total_length).clamp(start + header_length, frame.len());
+    let segment = &frame[start + header_length..ip_end];
+    let (src, dst) = (ip.get_source(), ip.get_destination());
+    match ip.get_next_level_protocol() {
+        IpNextHeaderProtocols::Udp => match UdpPacket::new(segment) {
+            Some(udp) => {
+                if udp.get_length() as usize != segment.len() {
+                    warnings.push("UDP length mismatch".into());
+                }
+                if udp.get_checksum() != 0 && pnet::packet::udp::ipv4_checksum(&udp, &src, &dst) != udp.get_checksum() {
+                    warnings.push("UDP checksum invalid".into());
+                }
+            }
+            None => warnings.push("UDP header truncated".into()),
+        },
+        IpNextHeaderProtocols::Tcp => match TcpPacket::new(segment) {
+            Some(tcp) => {
+                let data_offset = tcp.get_data_offset() as usize * 4;
+                if data_offset < 20 || data_offset > segment.len() {
+                    warnings.push("TCP data offset invalid".into());
+                }
+                if pnet::packet::tcp::ipv4_checksum(&tcp, &src, &dst) != tcp.get_checksum() {
+                    warnings.push("TCP checksum invalid".into());
+                }
+            }
+            None => warnings.push("TCP header truncated".into()),
+        },
+        _ => {}
+    }
+    warnings
+}
+
+/// Maps a DSCP codepoint to the 802.1p priority code point of its class.
+///
+/// Uses the common default mapping of IEEE 802.1Q/RFC 8325: the PCP is the
+/// class selector, i.e. the top three bits of the DSCP. EF (46) maps to 5,
+/// CS6 (48) to 6 and best effort (0) to 0.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::dscp_to_pcp;
+///
+/// assert_eq!(dscp_to_pcp(46), 5);
+/// assert_eq!(dscp_to_pcp(26), 3); // AF31
+/// assert_eq!(dscp_to_pcp(0), 0);
+/// ```
+pub fn dscp_to_pcp(dscp: u8) -> u8 {
+    (dscp >> 3) & 0b111
+}
+
+/// Computes the TCP Maximum Segment Size for a path MTU.
+///
+/// The MSS is the MTU minus the IP and TCP headers without options:
+/// 40 bytes for IPv4 (20 + 20) and 60 bytes for IPv6 (40 + 20).
+///
+/// # Arguments
+///
+/// * `mtu` - Path MTU in bytes
+/// * `ipv6` - Whether the segment is carried over IPv6
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::mss_from_mtu;
+///
+/// assert_eq!(mss_from_mtu(1500, false), 1460);
+/// assert_eq!(mss_from_mtu(1500, true), 1440);
+/// ```
+pub fn mss_from_mtu(mtu: u16, ipv6: bool) -> u16 {
+    let overhead = if ipv6 { 60 } else { 40 };
+    mtu.saturating_sub(overhead)
+}
+
+/// Returns the IPv4 pseudo-header covered by the TCP/UDP checksum.
+///
+/// The 12-byte layout (RFC 768, RFC 793) is:
+/// ```text
+/// +--------+--------+--------+--------+
+/// |          Source Address           |
+/// +--------+--------+--------+--------+
+/// |        Destination Address        |
+/// +--------+--------+--------+--------+
+/// |  zero  |protocol|   L4 length     |
+/// +--------+--------+--------+--------+
+/// ```
+///
+/// The L4 checksum is the ones' complement of the ones' complement sum of
+/// this pseudo-header followed by the L4 header (checksum field zeroed) and
+/// payload.
+///
+/// # Arguments
+///
+/// * `src` - Source IPv4 address
+/// * `dst` - Destination IPv4 address
+/// * `protocol` - IP protocol number (6 = TCP, 17 = UDP)
+/// * `l4_len` - Length of the L4 header plus payload in bytes
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::ipv4_pseudo_header;
+///
+/// let header = ipv4_pseudo_header("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap(), 17, 12);
+/// assert_eq!(header, [10, 0, 0, 1, 10, 0, 0, 2, 0, 17, 0, 12]);
+/// ```
+pub fn ipv4_pseudo_header(src: Ipv4Addr, dst: Ipv4Addr, protocol: u8, l4_len: u16) -> [u8; 12] {
+    let mut header = [0u8; 12];
+    header[..4].copy_from_slice(&src.octets());
+    header[4..8].copy_from_slice(&dst.octets());
+    header[9] = protocol;
+    header[10..].copy_from_slice(&l4_len.to_be_bytes());
+    header
+}
+
+/// Computes the Internet checksum (RFC 1071) of `data`.
+///
+/// This is the ones' complement of the ones' complement sum of `data` read
+/// as big-endian 16-bit words, with an odd trailing byte padded with zero.
+/// The checksum field inside `data`, if any, must be zeroed by the caller.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::internet_checksum;
+///
+/// // Example from RFC 1071, section 3
+/// assert_eq!(internet_checksum(&[0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7]), !0xddf2);
+/// ```
+pub fn internet_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|word| u32::from(u16::from_be_bytes([word[0], word.get(1).copied().unwrap_or(0)])))
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Returns the 16-bit word that turns an internet checksum of `current` into `target`.
+///
+/// Adding the word at an even offset of the checksummed data (where a zero
+/// word was when `current` was computed) makes the checksum come out to
+/// `target`. This is the checksum-neutralization trick behind
+/// `--checksum_target`.
+///
+/// In one's complement arithmetic a non-zero sum never folds to 0, so a
+/// target of 0xFFFF cannot be reached (the checksum comes out as 0).
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::{checksum_adjustment, internet_checksum};
+///
+/// let mut data = vec![0x45, 0x00, 0x12, 0x34, 0x00, 0x00];
+/// let word = checksum_adjustment(internet_checksum(&data), 0xbeef);
+/// data[4..].copy_from_slice(&word.to_be_bytes());
+/// assert_eq!(internet_checksum(&data), 0xbeef);
+/// ```
+pub fn checksum_adjustment(current: u16, target: u16) -> u16 {
+    let sum = u32::from(!target) + u32::from(current);
+    ((sum & 0xffff) + (sum >> 16)) as u16
+}
+
+/// Computes a TCP/UDP checksum over the IPv4 pseudo-header and `segment`.
+///
+/// `segment` is the L4 header and payload with the checksum field zeroed.
+fn ipv4_l4_checksum(src: Ipv4Addr, dst: Ipv4Addr, protocol: u8, segment: &[u8]) -> u16 {
+    let mut data = ipv4_pseudo_header(src, dst, protocol, segment.len() as u16).to_vec();
+    data.extend_from_slice(segment);
+    internet_checksum(&data)
+}
+
+/// Pads a short frame and rejects an oversized one.
+///
+/// Frames shorter than [`MIN_FRAME_LENGTH`] are zero-padded up to it. The
+/// largest accepted frame is the 14-byte Ethernet header plus `mtu`, or
+/// plus [`JUMBO_MTU`] when `allow_jumbo` is set and larger.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to check, padded in place
+/// * `mtu` - Maximum IP packet size in bytes
+/// * `allow_jumbo` - Whether to accept frames up to the jumbo MTU
+///
+/// # Returns
+///
+/// - `Ok(())` if the frame is within bounds (after padding)
+/// - `Err(CrafterError::FrameTooLarge)` if it exceeds the maximum
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::packet::enforce_bounds;
+///
+/// let mut frame = vec![0u8; 42];
+/// enforce_bounds(&mut frame, 1500, false).unwrap();
+/// assert_eq!(frame.len(), 60);
+///
+/// let mut big = vec![0u8; 1600];
+/// assert!(enforce_bounds(&mut big, 1500, false).is_err());
+/// assert!(enforce_bounds(&mut big, 1500, true).is_ok());
+/// ```
+pub fn enforce_bounds(frame: &mut Vec<u8>, mtu: usize, allow_jumbo: bool) -> Result<(), CrafterError> {
+    let mtu = if allow_jumbo { mtu.max(JUMBO_MTU) } else { mtu };
+    let max = 14 + mtu;
+    if frame.len() > max {
+        return Err(CrafterError::FrameTooLarge { length: frame.len(), max });
+    }
+    if frame.len() < MIN_FRAME_LENGTH {
+        frame.resize(MIN_FRAME_LENGTH, 0);
     }
+    Ok(())
 }
@@ -2,6 +2,92 @@
 //!
 //! This module provides custom parser functions.
 
+use std::net::Ipv4Addr;
+
+use clap::ValueEnum;
+
+use crate::{Args, DebugFormat, HexBytes, L4Protocol};
+use crate::output::Sink;
+
+/// A flow 5-tuple parsed from a `--flow` specification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowSpec {
+    /// Layer 4 protocol
+    pub protocol: L4Protocol,
+    /// Source IPv4 address
+    pub src_ip: Ipv4Addr,
+    /// Source port
+    pub src_port: u16,
+    /// Destination IPv4 address
+    pub dst_ip: Ipv4Addr,
+    /// Destination port
+    pub dst_port: u16,
+}
+
+/// A builder setting changed by an `--interactive` `set` command.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplSetting {
+    /// `set dest_port <port>`
+    DestPort(u16),
+    /// `set src_port <port>`
+    SrcPort(u16),
+    /// `set dst_ip <ipv4>`
+    DstIp(Ipv4Addr),
+    /// `set dst_mac <mac>`
+    DstMac([u8; 6]),
+    /// `set ttl <ttl>`
+    Ttl(u8),
+    /// `set l4_protocol <tcp|udp|icmp>`
+    L4Protocol(L4Protocol),
+    /// `set payload <text>`: the rest of the line, spaces included
+    Payload(String),
+}
+
+/// One line of input to the `--interactive` mode.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplCommand {
+    /// `set <field> <value>`: change a builder setting
+    Set(ReplSetting),
+    /// `build`: build a packet with the current settings
+    Build,
+    /// `write <path>`: write the last built packet, in the format of the extension
+    Write(String),
+    /// `show`: print the current addressing configuration
+    Show,
+    /// `help`: list the commands
+    Help,
+    /// `quit` or `exit`: leave the mode
+    Quit,
+}
+
+/// A range of payload sizes parsed from a `--size_sweep` specification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SizeSweep {
+    /// First payload size in bytes
+    pub start: usize,
+    /// Last payload size in bytes (inclusive, if reached by `step`)
+    pub end: usize,
+    /// Increment between sizes
+    pub step: usize,
+}
+
+impl SizeSweep {
+    /// Returns the payload sizes of the sweep, from `start` up to and
+    /// including `end`, `step` bytes apart.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use packet_crafter::parsing::SizeSweep;
+    ///
+    /// let sweep = SizeSweep { start: 100, end: 250, step: 100 };
+    /// assert_eq!(sweep.sizes().collect::<Vec<_>>(), [100, 200]);
+    /// ```
+    pub fn sizes(&self) -> impl Iterator<Item = usize> {
+        (self.start..=self.end).step_by(self.step)
+    }
+}
+
 /// Parses a MAC address string into a 6-byte array.
 ///
 /// Accepts MAC addresses in the standard colon-separated format:
@@ -38,7 +124,7 @@
 /// assert!(parse_mac("xx:yy:zz:aa:bb:cc").is_err());
 /// ```
 pub fn parse_mac(mac: &str) -> Result<[u8; 6], String> {
-    let octets: Vec<&str> = mac.split(|c| c == ':').collect();
+    let octets: Vec<&str> = mac.split(':').collect();
     if octets.len() != 6 {
         return Err(format!("Expected 6 octets, got {}", octets.len()));
     }
@@ -52,6 +138,80 @@ pub fn parse_mac(mac: &str) -> Result<[u8; 6], String> {
     Ok(bytes)
 }
 
+/// Parses a MAC address specification, which may ask for a random address.
+///
+/// Accepts:
+/// - `aa:bb:cc:dd:ee:ff` - a fixed address, as for [`parse_mac`]
+/// - `random` - a random locally administered unicast address
+/// - `random:aa:bb:cc` - the given 3-byte OUI followed by 3 random bytes
+///
+/// A new address is drawn on every call.
+///
+/// # Arguments
+///
+/// * `spec` - A string slice containing the MAC address specification
+///
+/// # Returns
+///
+/// * `Ok([u8; 6])` - A 6-byte array representing the MAC address
+/// * `Err(String)` - An error message if parsing fails
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::parse_mac_spec;
+///
+/// let mac = parse_mac_spec("random:00:11:22").unwrap();
+/// assert_eq!(&mac[..3], &[0x00, 0x11, 0x22]);
+///
+/// // Random addresses are locally administered unicast
+/// let mac = parse_mac_spec("random").unwrap();
+/// assert_eq!(mac[0] & 0x03, 0x02);
+///
+/// // The OUI must have exactly 3 octets
+/// assert!(parse_mac_spec("random:00:11").is_err());
+/// ```
+pub fn parse_mac_spec(spec: &str) -> Result<[u8; 6], String> {
+    let random = random_u64().to_be_bytes();
+    if spec == "random" {
+        let mut mac = [0u8; 6];
+        mac.copy_from_slice(&random[..6]);
+        mac[0] = (mac[0] & 0xfc) | 0x02;
+        return Ok(mac);
+    }
+    let Some(oui) = spec.strip_prefix("random:") else {
+        return parse_mac(spec);
+    };
+    let octets: Vec<&str> = oui.split(':').collect();
+    if octets.len() != 3 {
+        return Err(format!("Expected a 3-octet OUI, got {} octets", octets.len()));
+    }
+    let mut mac = [0u8; 6];
+    for (i, octet) in octets.iter().enumerate() {
+        mac[i] = u8::from_str_radix(octet, 16).map_err(|_| format!("Invalid octet: {}", octet))?;
+    }
+    mac[3..].copy_from_slice(&random[..3]);
+    Ok(mac)
+}
+
+/// Returns a random 64-bit value from the standard library's hasher keys.
+///
+/// Each `RandomState` is seeded differently, which is enough randomness for
+/// test addresses without pulling in a dedicated RNG crate.
+fn random_u64() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    hasher.finish()
+}
+
 /// Parses a bitfield value from a string, supporting both decimal and hexadecimal formats.
 ///
 /// This function is used to parse the `--ip_bitfield` argument which manipulates
@@ -107,4 +267,528 @@ pub fn parse_bitfield(s: &str) -> Result<u8, String> {
     }
 }
 
+/// Parses a 16-bit EtherType given in hex, with or without a `0x` prefix.
+///
+/// # Arguments
+///
+/// * `s` - A string slice such as `"0x86dd"` or `"88cc"`
+///
+/// # Returns
+///
+/// * `Ok(u16)` - The EtherType value
+/// * `Err(String)` - An error message if `s` is not a 16-bit hex number
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::parse_ethertype;
+///
+/// assert_eq!(parse_ethertype("0x86dd").unwrap(), 0x86dd);
+/// assert_eq!(parse_ethertype("88CC").unwrap(), 0x88cc);
+/// assert!(parse_ethertype("0x12345").is_err());
+/// ```
+pub fn parse_ethertype(s: &str) -> Result<u16, String> {
+    let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+    u16::from_str_radix(digits, 16).map_err(|_| format!("Invalid EtherType: {}", s))
+}
+
+/// Parses a 16-bit checksum given in hexadecimal, with or without `0x`.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::parse_checksum;
+///
+/// assert_eq!(parse_checksum("0xbeef").unwrap(), 0xbeef);
+/// assert_eq!(parse_checksum("1A2B").unwrap(), 0x1a2b);
+/// assert!(parse_checksum("0x10000").is_err());
+/// ```
+pub fn parse_checksum(s: &str) -> Result<u16, String> {
+    let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+    u16::from_str_radix(digits, 16).map_err(|_| format!("Invalid checksum: {}", s))
+}
+
+
+
+
+/// Parses a hex dump back into raw bytes.
+///
+/// Accepts the classic `xxd` layout as well as `tcpdump -xx` output: each
+/// line starts with a hexadecimal offset followed by a colon, then hex byte
+/// columns. An ASCII gutter (separated from the hex columns by two or more
+/// spaces, as `xxd` prints it) is ignored, as are blank lines.
+///
+/// # Arguments
+///
+/// * `text` - The hex dump text
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` - The bytes described by the dump
+/// * `Err(String)` - An error message if parsing fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - A line has no `offset:` prefix or the offset is not hexadecimal
+/// - An offset does not match the number of bytes read so far
+/// - A hex column contains invalid or odd-length hex
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::parse_hexdump;
+///
+/// let dump = "00000000: 4500 001c  E...\n00000004: 0000 4000  ..@.\n";
+/// assert_eq!(parse_hexdump(dump).unwrap(), vec![0x45, 0x00, 0x00, 0x1c, 0x00, 0x00, 0x40, 0x00]);
+///
+/// // Offset does not match the bytes read so far
+/// assert!(parse_hexdump("00000010: 4500\n").is_err());
+/// ```
+pub fn parse_hexdump(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (offset, rest) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Line {}: missing offset", number + 1))?;
+        let offset_str = offset.trim();
+        let offset_digits = offset_str.strip_prefix("0x").unwrap_or(offset_str);
+        let offset = usize::from_str_radix(offset_digits, 16)
+            .map_err(|_| format!("Line {}: invalid offset: {}", number + 1, offset_str))?;
+        if offset != bytes.len() {
+            return Err(format!(
+                "Line {}: offset 0x{:x} does not match {} bytes read so far",
+                number + 1, offset, bytes.len()
+            ));
+        }
+        let columns = rest.trim_start().split("  ").next().unwrap_or("");
+        let hex_digits: String = columns.split_whitespace().collect();
+        let decoded = hex::decode(&hex_digits)
+            .map_err(|_| format!("Line {}: invalid hex data: {}", number + 1, columns))?;
+        bytes.extend_from_slice(&decoded);
+    }
+    Ok(bytes)
+}
+
+/// Parses a 20-bit IPv6 flow label, in decimal or `0x`-prefixed hexadecimal.
+///
+/// # Arguments
+///
+/// * `s` - A string slice containing the flow label
+///
+/// # Returns
+///
+/// * `Ok(u32)` - The flow label, at most `0xFFFFF`
+/// * `Err(String)` - An error message if parsing fails or the value needs
+///   more than 20 bits
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::parse_flow_label;
+///
+/// assert_eq!(parse_flow_label("0xFFFFF").unwrap(), 0xFFFFF);
+/// assert_eq!(parse_flow_label("12345").unwrap(), 12345);
+/// assert!(parse_flow_label("0x100000").is_err());
+/// ```
+pub fn parse_flow_label(s: &str) -> Result<u32, String> {
+    let (input, radix) = if let Some(hex_str) = s.strip_prefix("0x") {
+        (hex_str, 16)
+    } else {
+        (s, 10)
+    };
+    let value = u32::from_str_radix(input, radix).map_err(|_| format!("Invalid flow label: {}", s))?;
+    if value > 0xFFFFF {
+        return Err(format!("Flow label {} does not fit in 20 bits (max 0xFFFFF)", s));
+    }
+    Ok(value)
+}
+
+/// Parses a string of hex digits into bytes.
+///
+/// An optional `0x` prefix is accepted and whitespace between digits is
+/// ignored, so `"dead beef"` and `"0xdeadbeef"` are equivalent.
+///
+/// # Arguments
+///
+/// * `s` - A string slice containing an even number of hex digits
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` - The decoded bytes
+/// * `Err(String)` - An error message if parsing fails
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::parse_hex_bytes;
+///
+/// assert_eq!(parse_hex_bytes("0xdead beef").unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+/// assert!(parse_hex_bytes("abc").is_err());
+/// ```
+pub fn parse_hex_bytes(s: &str) -> Result<Vec<u8>, String> {
+    let trimmed = s.trim();
+    let digits: String = trimmed
+        .strip_prefix("0x")
+        .unwrap_or(trimmed)
+        .split_whitespace()
+        .collect();
+    hex::decode(&digits).map_err(|_| format!("Invalid hex bytes: {}", s))
+}
+
+/// Parses a hex argument such as `--trailer_hex` with [`parse_hex_bytes`].
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{HexBytes, parsing::parse_hex_arg};
+///
+/// assert_eq!(parse_hex_arg("dead").unwrap(), HexBytes(vec![0xde, 0xad]));
+/// ```
+pub fn parse_hex_arg(s: &str) -> Result<HexBytes, String> {
+    parse_hex_bytes(s).map(HexBytes)
+}
+
+/// Expands a hex packet template, substituting `{{name}}` placeholders from `args`.
+///
+/// The template is a string of hex digits (whitespace and newlines are
+/// ignored). Each placeholder is replaced by the big-endian bytes of the
+/// named argument:
+///
+/// | Placeholder                | Bytes |
+/// |----------------------------|-------|
+/// | `src_ip`, `dst_ip`         | 4     |
+/// | `src_port`, `dst_port`     | 2     |
+/// | `src_mac`, `dst_mac`       | 6     |
+/// | `tcp_seq`                  | 4     |
+///
+/// `dest_port` is accepted as an alias of `dst_port`.
+///
+/// # Arguments
+///
+/// * `text` - The template text
+/// * `args` - Arguments supplying the placeholder values
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` - The expanded packet bytes
+/// * `Err(String)` - An error message for an unknown or unterminated
+///   placeholder, or invalid hex
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, parsing::expand_template};
+///
+/// let args = Args { dest_port: 443, ..Args::default() };
+/// assert_eq!(expand_template("ff {{dst_port}} ff", &args).unwrap(), vec![0xff, 0x01, 0xbb, 0xff]);
+/// assert!(expand_template("{{colour}}", &args).is_err());
+/// ```
+pub fn expand_template(text: &str, args: &Args) -> Result<Vec<u8>, String> {
+    let mut hex_text = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        hex_text.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| format!("Unterminated placeholder: {}", &rest[start..]))?;
+        hex_text.push_str(&hex::encode(template_value(after[..end].trim(), args)?));
+        rest = &after[end + 2..];
+    }
+    hex_text.push_str(rest);
+    parse_hex_bytes(&hex_text)
+}
+
+/// Returns the bytes substituted for a template placeholder.
+fn template_value(name: &str, args: &Args) -> Result<Vec<u8>, String> {
+    match name {
+        "src_ip" => Ok(args.src_ip.octets().to_vec()),
+        "dst_ip" => Ok(args.dst_ip.octets().to_vec()),
+        "src_port" => Ok(args.src_port.to_be_bytes().to_vec()),
+        "dst_port" | "dest_port" => Ok(args.dest_port.to_be_bytes().to_vec()),
+        "src_mac" => Ok(args.src_mac.to_vec()),
+        "dst_mac" => Ok(args.dst_mac.to_vec()),
+        "tcp_seq" => Ok(args.tcp_seq.to_be_bytes().to_vec()),
+        other => Err(format!("Unknown template placeholder: {{{{{}}}}}", other)),
+    }
+}
+
+/// Parses a link speed such as `1Gbps`, `100Mbps` or `10000` into bits per second.
+///
+/// A plain number is taken as bits per second. The suffixes `bps`, `Kbps`,
+/// `Mbps` and `Gbps` (case-insensitive, the `bps` part optional) scale by
+/// powers of 1000.
+///
+/// # Arguments
+///
+/// * `s` - A string slice containing the link speed
+///
+/// # Returns
+///
+/// * `Ok(u64)` - The speed in bits per second
+/// * `Err(String)` - An error message if parsing fails
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::parse_link_speed;
+///
+/// assert_eq!(parse_link_speed("1Gbps").unwrap(), 1_000_000_000);
+/// assert_eq!(parse_link_speed("100m").unwrap(), 100_000_000);
+/// assert_eq!(parse_link_speed("9600").unwrap(), 9600);
+/// assert!(parse_link_speed("fast").is_err());
+/// ```
+pub fn parse_link_speed(s: &str) -> Result<u64, String> {
+    let lower = s.trim().to_lowercase();
+    let number = lower.strip_suffix("bps").unwrap_or(&lower);
+    let (digits, multiplier) = match number.chars().last() {
+        Some('k') => (&number[..number.len() - 1], 1_000),
+        Some('m') => (&number[..number.len() - 1], 1_000_000),
+        Some('g') => (&number[..number.len() - 1], 1_000_000_000),
+        _ => (number, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|value| value.checked_mul(multiplier))
+        .ok_or_else(|| format!("Invalid link speed: {}", s))
+}
+
+/// Parses a flow 5-tuple of the form `"<proto> <src_ip>:<port> -> <dst_ip>:<port>"`.
+///
+/// The protocol is `tcp` or `udp` (case-insensitive). Whitespace around the
+/// arrow is optional.
+///
+/// # Arguments
+///
+/// * `s` - A string slice containing the flow specification
+///
+/// # Returns
+///
+/// * `Ok(FlowSpec)` - The parsed protocol, addresses and ports
+/// * `Err(String)` - An error message if parsing fails
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::parse_flow;
+///
+/// let flow = parse_flow("tcp 10.0.0.1:1234 -> 8.8.8.8:443").unwrap();
+/// assert_eq!(flow.src_port, 1234);
+/// assert_eq!(flow.dst_port, 443);
+///
+/// // Missing arrow
+/// assert!(parse_flow("tcp 10.0.0.1:1234 8.8.8.8:443").is_err());
+/// ```
+pub fn parse_flow(s: &str) -> Result<FlowSpec, String> {
+    let (protocol, endpoints) = s
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| format!("Expected '<proto> <src>:<port> -> <dst>:<port>', got: {}", s))?;
+    let protocol = match protocol.to_lowercase().as_str() {
+        "tcp" => L4Protocol::Tcp,
+        "udp" => L4Protocol::Udp,
+        other => return Err(format!("Unknown protocol: {}", other)),
+    };
+    let (src, dst) = endpoints
+        .split_once("->")
+        .ok_or_else(|| format!("Missing '->' in flow: {}", s))?;
+    let (src_ip, src_port) = parse_endpoint(src.trim())?;
+    let (dst_ip, dst_port) = parse_endpoint(dst.trim())?;
+    Ok(FlowSpec { protocol, src_ip, src_port, dst_ip, dst_port })
+}
+
+/// Returns the canonical initial IPv4 TTL used by an operating system.
+///
+/// Passive fingerprinting tools guess the sender's OS from the TTL of
+/// received packets, rounded up to the nearest of these values. The name is
+/// matched case-insensitively; unknown names get the common default of 64.
+///
+/// | OS                          | Initial TTL |
+/// |-----------------------------|-------------|
+/// | `linux`, `macos`, `freebsd` | 64          |
+/// | `windows`                   | 128         |
+/// | `cisco`, `solaris`          | 255         |
+///
+/// # Arguments
+///
+/// * `os` - The operating system name
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::initial_ttl_for_os;
+///
+/// assert_eq!(initial_ttl_for_os("Windows"), 128);
+/// assert_eq!(initial_ttl_for_os("plan9"), 64);
+/// ```
+pub fn initial_ttl_for_os(os: &str) -> u8 {
+    match os.trim().to_lowercase().as_str() {
+        "windows" => 128,
+        "cisco" | "solaris" => 255,
+        _ => 64,
+    }
+}
+
+/// Parses an output sink of the form `<kind>:<target>`.
+///
+/// Supported sinks:
+/// - `pcap:<path>`, `json:<path>`, `yaml:<path>` - write a debug file
+/// - `stdout:hex` - print each packet as a line of hex
+/// - `send:<interface>` - send the packets on an interface
+///
+/// # Arguments
+///
+/// * `s` - A string slice containing the sink specification
+///
+/// # Returns
+///
+/// * `Ok(Sink)` - The parsed sink
+/// * `Err(String)` - An error message if parsing fails
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{DebugFormat, output::Sink, parsing::parse_sink};
+///
+/// assert_eq!(parse_sink("stdout:hex").unwrap(), Sink::StdoutHex);
+/// assert_eq!(
+///     parse_sink("pcap:out.pcap").unwrap(),
+///     Sink::File { format: DebugFormat::Pcap, path: "out.pcap".to_string() }
+/// );
+/// assert!(parse_sink("ftp:host").is_err());
+/// ```
+pub fn parse_sink(s: &str) -> Result<Sink, String> {
+    let (kind, target) = s
+        .split_once(':')
+        .ok_or_else(|| format!("Expected '<kind>:<target>', got: {}", s))?;
+    if target.is_empty() {
+        return Err(format!("Missing target in sink: {}", s));
+    }
+    let file = |format| Sink::File { format, path: target.to_string() };
+    match kind {
+        "pcap" => Ok(file(DebugFormat::Pcap)),
+        "json" => Ok(file(DebugFormat::Json)),
+        "yaml" => Ok(file(DebugFormat::Yaml)),
+        "stdout" if target == "hex" => Ok(Sink::StdoutHex),
+        "stdout" => Err(format!("Unsupported stdout format: {} (expected 'hex')", target)),
+        "send" => Ok(Sink::Send { interface: target.to_string() }),
+        other => Err(format!("Unknown sink kind: {}", other)),
+    }
+}
+
+/// Parses a `start:end:step` payload size sweep.
+///
+/// # Arguments
+///
+/// * `s` - A string slice such as `100:1500:100`
+///
+/// # Returns
+///
+/// * `Ok(SizeSweep)` - The parsed sweep
+/// * `Err(String)` - If a part is missing or not a number, `step` is 0 or
+///   `start` is greater than `end`
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::{parse_size_sweep, SizeSweep};
+///
+/// assert_eq!(parse_size_sweep("100:300:100").unwrap(), SizeSweep { start: 100, end: 300, step: 100 });
+/// assert!(parse_size_sweep("300:100:100").is_err());
+/// assert!(parse_size_sweep("100:300:0").is_err());
+/// ```
+pub fn parse_size_sweep(s: &str) -> Result<SizeSweep, String> {
+    let parts: Vec<&str> = s.split(':').collect();
+    let [start, end, step] = parts[..] else {
+        return Err(format!("Expected '<start>:<end>:<step>', got: {}", s));
+    };
+    let number = |part: &str| part.trim().parse::<usize>().map_err(|_| format!("Invalid size: {}", part));
+    let sweep = SizeSweep { start: number(start)?, end: number(end)?, step: number(step)? };
+    if sweep.step == 0 {
+        return Err("Sweep step must be greater than 0".to_string());
+    }
+    if sweep.start > sweep.end {
+        return Err(format!("Sweep start {} is greater than end {}", sweep.start, sweep.end));
+    }
+    Ok(sweep)
+}
+
+/// Parses one line of input to the `--interactive` mode.
+///
+/// Surrounding whitespace is ignored. Field names of `set` match the
+/// command-line flags they stand for.
+///
+/// # Arguments
+///
+/// * `line` - A command such as `set dest_port 443`, `build` or `write out.pcap`
+///
+/// # Returns
+///
+/// * `Ok(ReplCommand)` - The parsed command
+/// * `Err(String)` - If the command or field is unknown, an argument is
+///   missing or extra, or a value does not parse
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::parsing::{parse_repl_command, ReplCommand, ReplSetting};
+///
+/// assert_eq!(parse_repl_command("set dest_port 443").unwrap(), ReplCommand::Set(ReplSetting::DestPort(443)));
+/// assert_eq!(parse_repl_command("write out.pcap").unwrap(), ReplCommand::Write("out.pcap".to_string()));
+/// assert!(parse_repl_command("launch").is_err());
+/// ```
+pub fn parse_repl_command(line: &str) -> Result<ReplCommand, String> {
+    let line = line.trim();
+    let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    let no_argument = |command: ReplCommand| {
+        if rest.is_empty() { Ok(command) } else { Err(format!("'{}' takes no argument", line)) }
+    };
+    
+    match command {
+        "set" => {
+            let (field, value) = rest
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| "Expected 'set <field> <value>'".to_string())?;
+            let value = value.trim();
+            let invalid = |_| format!("Invalid value for {}: {}", field, value);
+            let setting = match field {
+                "dest_port" => ReplSetting::DestPort(value.parse().map_err(invalid)?),
+                "src_port" => ReplSetting::SrcPort(value.parse().map_err(invalid)?),
+                "dst_ip" => ReplSetting::DstIp(value.parse().map_err(|_| format!("Invalid IPv4 address: {}", value))?),
+                "dst_mac" => ReplSetting::DstMac(parse_mac(value)?),
+                "ttl" => ReplSetting::Ttl(value.parse().map_err(invalid)?),
+                "l4_protocol" => ReplSetting::L4Protocol(L4Protocol::from_str(value, true)?),
+                "payload" => ReplSetting::Payload(value.to_string()),
+                other => return Err(format!(
+                    "Unknown field: {} (expected dest_port, src_port, dst_ip, dst_mac, ttl, l4_protocol or payload)",
+                    other
+                )),
+            };
+            Ok(ReplCommand::Set(setting))
+        }
+        "build" => no_argument(ReplCommand::Build),
+        "write" if rest.is_empty() => Err("Expected 'write <path>'".to_string()),
+        "write" => Ok(ReplCommand::Write(rest.to_string())),
+        "show" => no_argument(ReplCommand::Show),
+        "help" => no_argument(ReplCommand::Help),
+        "quit" | "exit" => no_argument(ReplCommand::Quit),
+        other => Err(format!("Unknown command: {} (type 'help' for the list)", other)),
+    }
+}
 
+/// Parses an `<ipv4>:<port>` endpoint.
+fn parse_endpoint(s: &str) -> Result<(Ipv4Addr, u16), String> {
+    let (ip, port) = s
+        .rsplit_once(':')
+        .ok_or_else(|| format!("Expected <ip>:<port>, got: {}", s))?;
+    let ip = ip.parse().map_err(|_| format!("Invalid IPv4 address: {}", ip))?;
+    let port = port.parse().map_err(|_| format!("Invalid port: {}", port))?;
+    Ok((ip, port))
+}
\ No newline at end of file
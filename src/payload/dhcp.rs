@@ -0,0 +1,50 @@
+//! DHCP (BOOTP) message encoding.
+
+/// BOOTP client port (source port of client messages).
+pub const CLIENT_PORT: u16 = 68;
+
+/// BOOTP server port (destination port of client messages).
+pub const SERVER_PORT: u16 = 67;
+
+/// DHCP magic cookie marking the start of the options field (RFC 2131).
+pub const MAGIC_COOKIE: [u8; 4] = [0x63, 0x82, 0x53, 0x63];
+
+/// Transaction ID placed in crafted messages.
+const TRANSACTION_ID: u32 = 0x3903_f326;
+
+/// Encodes a minimal DHCP DISCOVER message.
+///
+/// The message has the fixed 236-byte BOOTP header followed by the magic
+/// cookie and options:
+/// - `op` = 1 (BOOTREQUEST), `htype` = 1 (Ethernet), `hlen` = 6
+/// - `flags` = 0x8000 (broadcast reply requested)
+/// - `chaddr` = `client_mac`
+/// - Option 53 (DHCP Message Type) = 1 (DISCOVER), then option 255 (End)
+///
+/// # Arguments
+///
+/// * `client_mac` - The client hardware address to place in `chaddr`
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::payload::dhcp::{encode_discover, MAGIC_COOKIE};
+///
+/// let message = encode_discover([0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff]);
+/// assert_eq!(message[0], 1);
+/// assert_eq!(&message[236..240], &MAGIC_COOKIE);
+/// ```
+pub fn encode_discover(client_mac: [u8; 6]) -> Vec<u8> {
+    let mut message = vec![0u8; 236];
+    message[0] = 1; // op: BOOTREQUEST
+    message[1] = 1; // htype: Ethernet
+    message[2] = 6; // hlen
+    message[4..8].copy_from_slice(&TRANSACTION_ID.to_be_bytes());
+    message[10..12].copy_from_slice(&0x8000u16.to_be_bytes()); // flags: broadcast
+    message[28..34].copy_from_slice(&client_mac); // chaddr
+    
+    message.extend_from_slice(&MAGIC_COOKIE);
+    message.extend_from_slice(&[53, 1, 1]); // DHCP Message Type: DISCOVER
+    message.push(255); // End
+    message
+}
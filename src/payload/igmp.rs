@@ -0,0 +1,38 @@
+//! IGMP message encoding.
+
+use std::net::Ipv4Addr;
+
+use crate::packet::internet_checksum;
+
+/// IGMPv2 Membership Report message type (RFC 2236).
+pub const MEMBERSHIP_REPORT_V2: u8 = 0x16;
+
+/// Encodes an IGMPv2 Membership Report for `group`.
+///
+/// The 8-byte message is:
+/// - `type` = 0x16 (Version 2 Membership Report)
+/// - `max resp time` = 0 (unused in reports)
+/// - `checksum` over the message
+/// - `group address` = `group`
+///
+/// # Arguments
+///
+/// * `group` - The multicast group being joined
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::payload::igmp::{encode_report, MEMBERSHIP_REPORT_V2};
+///
+/// let message = encode_report("239.1.2.3".parse().unwrap());
+/// assert_eq!(message[0], MEMBERSHIP_REPORT_V2);
+/// assert_eq!(&message[4..8], &[239, 1, 2, 3]);
+/// ```
+pub fn encode_report(group: Ipv4Addr) -> Vec<u8> {
+    let mut message = vec![0u8; 8];
+    message[0] = MEMBERSHIP_REPORT_V2;
+    message[4..8].copy_from_slice(&group.octets());
+    let checksum = internet_checksum(&message);
+    message[2..4].copy_from_slice(&checksum.to_be_bytes());
+    message
+}
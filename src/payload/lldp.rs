@@ -0,0 +1,67 @@
+//! LLDP (IEEE 802.1AB) LLDPDU encoding.
+
+/// Nearest-bridge multicast address that LLDP frames are sent to.
+pub const NEAREST_BRIDGE_MAC: [u8; 6] = [0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e];
+
+/// End of LLDPDU TLV type.
+pub const TLV_END: u8 = 0;
+
+/// Chassis ID TLV type.
+pub const TLV_CHASSIS_ID: u8 = 1;
+
+/// Port ID TLV type.
+pub const TLV_PORT_ID: u8 = 2;
+
+/// Time To Live TLV type.
+pub const TLV_TTL: u8 = 3;
+
+/// Chassis ID subtype for a MAC address.
+pub const CHASSIS_SUBTYPE_MAC: u8 = 4;
+
+/// Port ID subtype for an interface name.
+pub const PORT_SUBTYPE_INTERFACE_NAME: u8 = 5;
+
+/// Longest Port ID accepted by [`encode_lldpdu`], in bytes.
+pub const MAX_PORT_ID_LENGTH: usize = 255;
+
+/// Encodes an LLDPDU with the three mandatory TLVs.
+///
+/// The TLVs are, in order:
+/// - Chassis ID, subtype 4 (MAC address) = `chassis_mac`
+/// - Port ID, subtype 5 (interface name) = `port_id`
+/// - Time To Live = `ttl` seconds
+/// - End of LLDPDU
+///
+/// Each TLV starts with a 16-bit header holding the 7-bit type and the
+/// 9-bit value length.
+///
+/// # Arguments
+///
+/// * `chassis_mac` - MAC address identifying the sending device
+/// * `port_id` - Name of the sending port, at most [`MAX_PORT_ID_LENGTH`] bytes
+/// * `ttl` - How long receivers should keep the information
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::payload::lldp::{encode_lldpdu, TLV_CHASSIS_ID};
+///
+/// let lldpdu = encode_lldpdu([0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff], "eth0", 120);
+/// assert_eq!(lldpdu[0] >> 1, TLV_CHASSIS_ID);
+/// assert_eq!(&lldpdu[lldpdu.len() - 2..], &[0, 0]);
+/// ```
+pub fn encode_lldpdu(chassis_mac: [u8; 6], port_id: &str, ttl: u16) -> Vec<u8> {
+    let mut lldpdu = Vec::new();
+    push_tlv(&mut lldpdu, TLV_CHASSIS_ID, &[&[CHASSIS_SUBTYPE_MAC][..], &chassis_mac].concat());
+    push_tlv(&mut lldpdu, TLV_PORT_ID, &[&[PORT_SUBTYPE_INTERFACE_NAME][..], port_id.as_bytes()].concat());
+    push_tlv(&mut lldpdu, TLV_TTL, &ttl.to_be_bytes());
+    push_tlv(&mut lldpdu, TLV_END, &[]);
+    lldpdu
+}
+
+/// Appends one TLV: a type/length header followed by `value`.
+fn push_tlv(lldpdu: &mut Vec<u8>, tlv_type: u8, value: &[u8]) {
+    let header = ((tlv_type as u16) << 9) | value.len() as u16;
+    lldpdu.extend_from_slice(&header.to_be_bytes());
+    lldpdu.extend_from_slice(value);
+}
@@ -0,0 +1,8 @@
+//! Application-layer payload encoders.
+//!
+//! Each submodule encodes the payload for one protocol preset; the result is
+//! passed to [`PacketBuilder::build_packet`](crate::packet::PacketBuilder::build_packet).
+
+pub mod dhcp;
+pub mod igmp;
+pub mod lldp;
@@ -0,0 +1,91 @@
+//! Summary reports printed after multi-packet runs.
+//!
+//! The formatting lives in `main.rs`; this module holds the calculations so
+//! they can be tested directly.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Per-frame Ethernet overhead on the wire not present in the built bytes:
+/// FCS (4), preamble and SFD (8) and the minimum inter-frame gap (12).
+pub const ETHERNET_WIRE_OVERHEAD: usize = 4 + 8 + 12;
+
+/// Minimum Ethernet frame length without FCS; shorter frames are padded.
+pub const ETHERNET_MIN_FRAME: usize = 60;
+
+/// Width in bytes of each frame-size histogram bucket.
+pub const HISTOGRAM_BUCKET_WIDTH: usize = 64;
+
+/// Returns the histogram bucket index for a frame length.
+///
+/// Buckets are [`HISTOGRAM_BUCKET_WIDTH`] bytes wide: bucket 0 holds
+/// 0–63 bytes, bucket 1 holds 64–127 bytes, and so on.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::report::bucket_length;
+///
+/// assert_eq!(bucket_length(42), 0);
+/// assert_eq!(bucket_length(64), 1);
+/// assert_eq!(bucket_length(1514), 23);
+/// ```
+pub fn bucket_length(len: usize) -> usize {
+    len / HISTOGRAM_BUCKET_WIDTH
+}
+
+/// Counts packets per frame-size bucket.
+///
+/// The map is keyed by bucket index (see [`bucket_length`]) and only
+/// contains buckets with at least one packet, in ascending order.
+pub fn length_histogram(packets: &[Vec<u8>]) -> BTreeMap<usize, usize> {
+    let mut histogram = BTreeMap::new();
+    for packet in packets {
+        *histogram.entry(bucket_length(packet.len())).or_insert(0) += 1;
+    }
+    histogram
+}
+
+/// Returns the number of bytes a frame occupies on the wire.
+///
+/// Short frames are padded to [`ETHERNET_MIN_FRAME`], then the FCS, preamble
+/// and inter-frame gap are added (see [`ETHERNET_WIRE_OVERHEAD`]).
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::report::wire_bytes;
+///
+/// assert_eq!(wire_bytes(42), 84);
+/// assert_eq!(wire_bytes(1514), 1538);
+/// ```
+pub fn wire_bytes(frame_len: usize) -> usize {
+    frame_len.max(ETHERNET_MIN_FRAME) + ETHERNET_WIRE_OVERHEAD
+}
+
+/// Estimates how long `total_bits` take to transmit at `link_bps`.
+///
+/// # Arguments
+///
+/// * `total_bits` - Number of bits to transmit, including framing overhead
+/// * `link_bps` - Link speed in bits per second
+///
+/// # Returns
+///
+/// The transmission time, or `Duration::ZERO` if `link_bps` is 0.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::report::estimate_wire_time;
+/// use std::time::Duration;
+///
+/// assert_eq!(estimate_wire_time(1_000_000_000, 1_000_000_000), Duration::from_secs(1));
+/// ```
+pub fn estimate_wire_time(total_bits: u64, link_bps: u64) -> Duration {
+    if link_bps == 0 {
+        return Duration::ZERO;
+    }
+    let nanos = total_bits as u128 * 1_000_000_000 / link_bps as u128;
+    Duration::from_nanos(nanos as u64)
+}
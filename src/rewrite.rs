@@ -0,0 +1,104 @@
+//! Address rewriting of captured packets.
+//!
+//! `--rewrite` reads an Ethernet PCAP, replaces the MAC and IPv4 addresses
+//! of every frame with the configured ones and writes the result, e.g. to
+//! anonymize a capture or replay it on another network.
+
+use std::fs::File;
+use std::path::Path;
+
+use pcap_file::pcap::{PcapPacket, PcapReader, PcapWriter};
+use pnet::packet::ipv4::MutableIpv4Packet;
+
+use crate::{Args, CrafterError};
+use crate::overrides::recompute_checksums;
+use crate::packet::ipv4_header_offset;
+
+/// Rewrites the addresses of one Ethernet frame in place.
+///
+/// The source and destination MAC are always replaced with `--src_mac`
+/// and `--dst_mac`. For IPv4 frames, found with [`ipv4_header_offset`] so
+/// 802.1Q tags and PPPoE are skipped, the source and destination IP are
+/// replaced with `--src_ip` and `--dst_ip`, and the IPv4 and TCP/UDP
+/// checksums are recomputed (a UDP checksum of 0 stays 0). Other
+/// EtherTypes keep their payload untouched.
+///
+/// # Arguments
+///
+/// * `buf` - The frame to modify in place
+/// * `args` - Supplies the replacement addresses
+///
+/// # Errors
+///
+/// `CrafterError::MalformedFrame` if the frame is shorter than an Ethernet
+/// header, or claims IPv4 but is too short for an IPv4 header.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::PacketBuilder, rewrite::rewrite_packet};
+///
+/// let mut frame = PacketBuilder::from(&Args::default()).build_packet(b"probe").unwrap().to_vec();
+/// let args = Args { dst_ip: "10.9.9.9".parse().unwrap(), ..Args::default() };
+///
+/// rewrite_packet(&mut frame, &args).unwrap();
+/// assert_eq!(frame[30..34], [10, 9, 9, 9]);
+/// ```
+pub fn rewrite_packet(buf: &mut [u8], args: &Args) -> Result<(), CrafterError> {
+    let malformed = |reason: &str| CrafterError::MalformedFrame { reason: reason.to_string() };
+
+    if buf.len() < 14 {
+        return Err(malformed("shorter than an Ethernet header"));
+    }
+    buf[0..6].copy_from_slice(&args.dst_mac);
+    buf[6..12].copy_from_slice(&args.src_mac);
+    let Some(start) = ipv4_header_offset(buf) else {
+        return Ok(());
+    };
+
+    let mut ip = MutableIpv4Packet::new(&mut buf[start..]).ok_or_else(|| malformed("truncated IPv4 header"))?;
+    ip.set_source(args.src_ip);
+    ip.set_destination(args.dst_ip);
+    recompute_checksums(buf);
+    Ok(())
+}
+
+/// Applies [`rewrite_packet`] to every packet of an Ethernet PCAP file.
+///
+/// The output keeps the input's PCAP header and each record's timestamp
+/// and original length.
+///
+/// # Arguments
+///
+/// * `input` - PCAP file to read
+/// * `output` - PCAP file to create
+/// * `args` - Supplies the replacement addresses
+///
+/// # Returns
+///
+/// - `Ok(usize)` with the number of packets rewritten
+/// - `Err(Box<dyn std::error::Error>)` if reading or writing fails, the
+///   capture is not Ethernet, or a frame is malformed
+pub fn rewrite_pcap(input: &Path, output: &Path, args: &Args) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut reader = PcapReader::new(File::open(input)?)?;
+    let header = reader.header();
+    if header.datalink != pcap_file::DataLink::ETHERNET {
+        return Err(format!("{} is not an Ethernet capture", input.display()).into());
+    }
+    let mut writer = PcapWriter::with_header(File::create(output)?, header)?;
+
+    let mut count = 0;
+    while let Some(packet) = reader.next_packet() {
+        let packet = packet?;
+        let mut data = packet.data.into_owned();
+        rewrite_packet(&mut data, args).map_err(|e| format!("packet {}: {}", count, e))?;
+        writer.write_packet(&PcapPacket {
+            timestamp: packet.timestamp,
+            orig_len: packet.orig_len,
+            data: data.into(),
+        })?;
+        count += 1;
+    }
+
+    Ok(count)
+}
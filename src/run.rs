@@ -0,0 +1,404 @@
+//! Run modes that drive the packet builder from an input source.
+//!
+//! These helpers hold the loops behind the CLI's multi-packet modes so they
+//! can be exercised with in-memory readers in tests, along with the
+//! `--validate_only` check and the `--interactive` command loop. Timed generation goes through a [`Clock`] so it
+//! can be driven by a mock clock instead of real time.
+
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::{Args, CrafterError, DebugFormat, L4Protocol};
+use crate::output::{write_json, write_pcap, write_tcpdump, write_yaml};
+use crate::packet::PacketBuilder;
+use crate::parsing::{parse_repl_command, ReplCommand, ReplSetting, SizeSweep};
+
+/// Builds one packet per line read from `reader`, until EOF.
+///
+/// Each line (without its trailing `\n` or `\r\n`) is used as the payload of
+/// one packet. This backs the `--stdin_loop` mode.
+///
+/// # Arguments
+///
+/// * `builder` - The configured builder used for every packet
+/// * `reader` - Source of newline-delimited payloads
+///
+/// # Returns
+///
+/// - `Ok(Vec<Vec<u8>>)` with one complete packet per input line
+/// - `Err(Box<dyn std::error::Error>)` if reading from `reader` fails or a
+///   packet cannot be built
+///
+/// # Examples
+///
+/// ```no_run
+/// use packet_crafter::{Args, packet::PacketBuilder, run::build_from_lines};
+/// use std::io::Cursor;
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// let packets = build_from_lines(&mut builder, Cursor::new("one\ntwo\n")).unwrap();
+/// assert_eq!(packets.len(), 2);
+/// ```
+pub fn build_from_lines<R: BufRead>(
+    builder: &mut PacketBuilder,
+    mut reader: R,
+) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error>> {
+    let mut packets = Vec::new();
+    let mut line = Vec::new();
+    
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        }
+        packets.push(builder.build_packet(&line)?.to_vec());
+    }
+    
+    Ok(packets)
+}
+
+/// Builds the `--count` packets of a burst.
+///
+/// Packet `i` carries [`Args::burst_payload`]`(i)`, so with
+/// `--payload_counter` the payloads count up from 0.
+///
+/// # Arguments
+///
+/// * `builder` - The configured builder used for every packet
+/// * `args` - The parsed command-line arguments
+///
+/// # Returns
+///
+/// - `Ok(Vec<Vec<u8>>)` with `args.count` complete packets
+/// - `Err(CrafterError)` if a packet cannot be built
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::PacketBuilder, run::build_burst};
+///
+/// let args = Args { count: 3, payload_counter: Some(8), ..Args::default() };
+/// let mut builder = PacketBuilder::from(&args);
+/// let packets = build_burst(&mut builder, &args).unwrap();
+/// assert_eq!(packets.len(), 3);
+/// ```
+pub fn build_burst(builder: &mut PacketBuilder, args: &Args) -> Result<Vec<Vec<u8>>, CrafterError> {
+    (0..args.count)
+        .map(|index| builder.build_packet(&args.burst_payload(index)).map(|packet| packet.to_vec()))
+        .collect()
+}
+
+/// Builds the same packet once as TCP and once as UDP.
+///
+/// Apart from the Layer 4 protocol, both packets use the builder's
+/// configuration, so addresses and ports match. This backs the
+/// `--both_protocols` mode. The builder is left set to UDP.
+///
+/// # Arguments
+///
+/// * `builder` - The configured builder; its protocol is changed
+/// * `payload` - Payload of both packets
+///
+/// # Returns
+///
+/// - `Ok(Vec<Vec<u8>>)` with the TCP packet followed by the UDP packet
+/// - `Err(CrafterError)` if a packet cannot be built
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::PacketBuilder, run::build_both_protocols};
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// let packets = build_both_protocols(&mut builder, b"probe").unwrap();
+/// assert_eq!(packets.len(), 2);
+/// ```
+pub fn build_both_protocols(builder: &mut PacketBuilder, payload: &[u8]) -> Result<Vec<Vec<u8>>, CrafterError> {
+    [L4Protocol::Tcp, L4Protocol::Udp]
+        .into_iter()
+        .map(|protocol| {
+            builder.set_l4_protocol(protocol);
+            builder.build_packet(payload).map(|packet| packet.to_vec())
+        })
+        .collect()
+}
+
+/// Builds one packet per payload size of a `--size_sweep`.
+///
+/// Each payload is a run of counting bytes (0, 1, 2, ... wrapping at 256)
+/// of the swept size, as for `--pmtu_probe`, so truncation is easy to spot.
+///
+/// # Arguments
+///
+/// * `builder` - The configured builder used for every packet
+/// * `sweep` - The payload sizes to build
+///
+/// # Returns
+///
+/// - `Ok(Vec<Vec<u8>>)` with one packet per size, smallest first
+/// - `Err(CrafterError)` if a packet cannot be built, e.g. because the
+///   payload no longer fits in IPv4
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::PacketBuilder, parsing::SizeSweep, run::build_size_sweep};
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// let sweep = SizeSweep { start: 100, end: 300, step: 100 };
+/// assert_eq!(build_size_sweep(&mut builder, &sweep).unwrap().len(), 3);
+/// ```
+pub fn build_size_sweep(builder: &mut PacketBuilder, sweep: &SizeSweep) -> Result<Vec<Vec<u8>>, CrafterError> {
+    sweep
+        .sizes()
+        .map(|size| {
+            let payload: Vec<u8> = (0..size).map(|i| i as u8).collect();
+            builder.build_packet(&payload).map(|packet| packet.to_vec())
+        })
+        .collect()
+}
+
+/// Checks the arguments without building or writing anything.
+///
+/// Runs [`Args::validate`] and then the checks `main` would otherwise only
+/// hit at run time, such as whether the `--from_hexdump` file exists. Prints
+/// `OK` to stdout or `FAIL: <reason>` to stderr. This backs the
+/// `--validate_only` mode.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command-line arguments
+///
+/// # Returns
+///
+/// The process exit code: `0` if the arguments are valid, `1` otherwise.
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, run::run_validate_only};
+///
+/// assert_eq!(run_validate_only(&Args::default()), 0);
+/// ```
+pub fn run_validate_only(args: &Args) -> i32 {
+    let result = args.validate().and_then(|()| match &args.from_hexdump {
+        Some(path) if !std::path::Path::new(path).is_file() => {
+            Err(format!("Hex dump file '{}' does not exist", path))
+        }
+        _ => Ok(()),
+    });
+    
+    match result {
+        Ok(()) => {
+            println!("OK");
+            0
+        }
+        Err(e) => {
+            eprintln!("FAIL: {}", e);
+            1
+        }
+    }
+}
+
+/// Time source for [`run_for_duration`].
+pub trait Clock {
+    /// Time elapsed since the clock was started.
+    fn elapsed(&self) -> Duration;
+
+    /// Blocks for `duration`.
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// [`Clock`] backed by the system's monotonic clock.
+pub struct SystemClock {
+    /// When the clock was started
+    start: Instant,
+}
+
+impl SystemClock {
+    /// Starts a clock at the current instant.
+    pub fn start() -> Self {
+        Self { start: Instant::now() }
+    }
+}
+
+impl Clock for SystemClock {
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Calls `emit` every `interval` until `duration` has elapsed on `clock`.
+///
+/// Packet `n` is due `n * interval` after the start, so time spent in
+/// `emit` does not stretch the schedule. This backs the `--duration_ms`
+/// mode; `emit` receives the 0-based packet index.
+///
+/// # Arguments
+///
+/// * `clock` - Time source, [`SystemClock`] outside of tests
+/// * `duration` - How long to keep producing packets
+/// * `interval` - Spacing between packets; zero produces them back to back
+/// * `emit` - Builds and delivers one packet
+///
+/// # Returns
+///
+/// - `Ok(u64)` with the number of packets produced
+/// - The first error returned by `emit`, which stops the loop
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::run::{run_for_duration, Clock};
+/// use std::time::Duration;
+///
+/// struct MockClock(Duration);
+///
+/// impl Clock for MockClock {
+///     fn elapsed(&self) -> Duration { self.0 }
+///     fn sleep(&mut self, duration: Duration) { self.0 += duration; }
+/// }
+///
+/// let mut clock = MockClock(Duration::ZERO);
+/// let produced = run_for_duration(&mut clock, Duration::from_millis(50), Duration::from_millis(10), |_| Ok::<_, ()>(()));
+/// assert_eq!(produced, Ok(5));
+/// ```
+pub fn run_for_duration<C, F, E>(clock: &mut C, duration: Duration, interval: Duration, mut emit: F) -> Result<u64, E>
+where
+    C: Clock,
+    F: FnMut(u64) -> Result<(), E>,
+{
+    let mut produced = 0;
+    while clock.elapsed() < duration {
+        emit(produced)?;
+        produced += 1;
+
+        let next = interval * produced as u32;
+        if next >= duration {
+            break;
+        }
+        clock.sleep(next.saturating_sub(clock.elapsed()));
+    }
+    Ok(produced)
+}
+
+/// Commands listed by `help` in the `--interactive` mode.
+const REPL_HELP: &str = "\
+set <field> <value>  change dest_port, src_port, dst_ip, dst_mac, ttl, l4_protocol or payload
+build                build a packet and print it as hex
+write <path>         write the last packet (.pcap, .json, .yaml/.yml or .txt)
+show                 print the addressing configuration
+quit                 leave";
+
+/// Runs the `--interactive` mode: applies commands read from `reader` to
+/// `builder` until EOF or `quit`.
+///
+/// Each command gets one line of feedback on `out` (`show` and `help`
+/// print several). Invalid commands and failed builds or writes print an
+/// `error: ...` line and the loop goes on; blank lines are skipped.
+///
+/// # Arguments
+///
+/// * `builder` - The live builder that `set` commands modify
+/// * `payload` - Initial payload of built packets, replaced by `set payload`
+/// * `reader` - Source of commands, one per line
+/// * `out` - Destination of the feedback
+///
+/// # Returns
+///
+/// - `Ok(())` once the input ends or `quit` is read
+/// - `Err(std::io::Error)` if reading commands or writing feedback fails
+///
+/// # Examples
+///
+/// ```rust
+/// use packet_crafter::{Args, packet::PacketBuilder, run::run_repl};
+/// use std::io::Cursor;
+///
+/// let mut builder = PacketBuilder::from(&Args::default());
+/// let mut out = Vec::new();
+/// run_repl(&mut builder, b"hi".to_vec(), Cursor::new("set dest_port 443\nbuild\n"), &mut out).unwrap();
+/// assert!(String::from_utf8(out).unwrap().contains("built 44 bytes"));
+/// ```
+pub fn run_repl<R: BufRead, W: Write>(
+    builder: &mut PacketBuilder,
+    mut payload: Vec<u8>,
+    reader: R,
+    out: &mut W,
+) -> std::io::Result<()> {
+    let mut last_packet: Option<Vec<u8>> = None;
+    
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let command = match parse_repl_command(&line) {
+            Ok(command) => command,
+            Err(e) => {
+                writeln!(out, "error: {}", e)?;
+                continue;
+            }
+        };
+        
+        match command {
+            ReplCommand::Set(setting) => {
+                match setting {
+                    ReplSetting::DestPort(port) => builder.set_dest_port(port),
+                    ReplSetting::SrcPort(port) => builder.set_src_port(port),
+                    ReplSetting::DstIp(ip) => builder.set_dst_ip(ip),
+                    ReplSetting::DstMac(mac) => builder.set_dst_mac(mac),
+                    ReplSetting::Ttl(ttl) => builder.set_ttl(ttl),
+                    ReplSetting::L4Protocol(protocol) => builder.set_l4_protocol(protocol),
+                    ReplSetting::Payload(text) => payload = text.into_bytes(),
+                }
+                writeln!(out, "ok")?;
+            }
+            ReplCommand::Build => match builder.build_packet(&payload) {
+                Ok(packet) => {
+                    writeln!(out, "built {} bytes: {}", packet.len(), hex::encode(packet))?;
+                    last_packet = Some(packet.to_vec());
+                }
+                Err(e) => writeln!(out, "error: {}", e)?,
+            },
+            ReplCommand::Write(path) => match &last_packet {
+                Some(packet) => match write_packet_file(Path::new(&path), packet) {
+                    Ok(()) => writeln!(out, "wrote {}", path)?,
+                    Err(e) => writeln!(out, "error: {}", e)?,
+                },
+                None => writeln!(out, "error: nothing built yet, use 'build' first")?,
+            },
+            ReplCommand::Show => writeln!(out, "{}", builder.config_json())?,
+            ReplCommand::Help => writeln!(out, "{}", REPL_HELP)?,
+            ReplCommand::Quit => break,
+        }
+    }
+    
+    Ok(())
+}
+
+/// Writes one packet to `path` in the debug format its extension stands for.
+fn write_packet_file(path: &Path, packet: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let format = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(DebugFormat::from_extension)
+        .ok_or_else(|| format!("cannot infer a format from '{}' (expected .pcap, .json, .yaml or .txt)", path.display()))?;
+    match format {
+        DebugFormat::Pcap => write_pcap(path, packet),
+        DebugFormat::Json => write_json(path, packet),
+        DebugFormat::Yaml => write_yaml(path, packet),
+        DebugFormat::Tcpdump => write_tcpdump(path, &[packet.to_vec()]),
+    }
+}
@@ -0,0 +1,197 @@
+//! Raw frame transmission for send mode.
+//!
+//! This module opens a Layer 2 channel on a named interface and writes
+//! complete Ethernet frames to it. Sending requires raw socket privileges
+//! (root or `CAP_NET_RAW` on Linux). [`send_with_retries`] adds retries and
+//! reply detection on top of a [`ProbeTransport`] and reports what happened
+//! as a [`SendOutcome`].
+
+use pnet::datalink::{self, Channel};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::traceroute::ProbeTransport;
+
+/// Sends a complete Ethernet frame on the named interface.
+///
+/// # Arguments
+///
+/// * `interface_name` - Name of the interface to transmit on (e.g. `eth0`)
+/// * `packet` - The complete packet bytes (Ethernet frame)
+///
+/// # Returns
+///
+/// - `Ok(())` once the frame has been handed to the interface
+/// - `Err(Box<dyn std::error::Error>)` if the interface is unknown, the
+///   channel cannot be opened, or the write fails
+///
+/// # Examples
+///
+/// ```no_run
+/// use packet_crafter::send::send_packet;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let frame = vec![0u8; 60];
+/// send_packet("eth0", &frame)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn send_packet(interface_name: &str, packet: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let interface = datalink::interfaces()
+        .into_iter()
+        .find(|iface| iface.name == interface_name)
+        .ok_or_else(|| format!("Interface '{}' not found", interface_name))?;
+    
+    let mut tx = match datalink::channel(&interface, Default::default())? {
+        Channel::Ethernet(tx, _rx) => tx,
+        _ => return Err("Unsupported datalink channel type".into()),
+    };
+    
+    match tx.send_to(packet, None) {
+        Some(result) => Ok(result?),
+        None => Err("Failed to send packet".into()),
+    }
+}
+
+/// Reads the MTU of a network interface from sysfs.
+///
+/// `reader` is given `/sys/class/net/<name>/mtu` and returns its contents;
+/// pass `std::fs::read_to_string` to read the live system, or a stub in
+/// tests.
+///
+/// # Returns
+///
+/// The MTU, or `None` if `name` is not a plain interface name, the file
+/// cannot be read or does not hold a positive integer.
+///
+/// # Examples
+///
+/// ```
+/// use packet_crafter::send::read_interface_mtu;
+///
+/// let mtu = read_interface_mtu("eth0", |_| Ok("9000\n".to_string()));
+/// assert_eq!(mtu, Some(9000));
+/// ```
+pub fn read_interface_mtu<F>(name: &str, reader: F) -> Option<u32>
+where
+    F: FnOnce(&Path) -> std::io::Result<String>,
+{
+    if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+        return None;
+    }
+    let path: PathBuf = ["/sys/class/net", name, "mtu"].iter().collect();
+    let mtu = reader(&path).ok()?.trim().parse().ok()?;
+    (mtu > 0).then_some(mtu)
+}
+
+/// Linux capability number of `CAP_NET_RAW`.
+const CAP_NET_RAW: u32 = 13;
+
+/// Reports whether this process may open the raw sockets `--send` needs.
+///
+/// Reads `/proc/self/status`: the process qualifies if its effective user
+/// is root or its effective capability set contains `CAP_NET_RAW`. Where
+/// that file does not exist (other platforms) the check cannot tell and
+/// returns `true`, leaving the socket call to report any failure.
+pub fn has_raw_socket_privilege() -> bool {
+    std::fs::read_to_string("/proc/self/status").map_or(true, |status| raw_socket_privilege_in_status(&status))
+}
+
+/// Applies the [`has_raw_socket_privilege`] check to the text of a
+/// `/proc/<pid>/status` file.
+///
+/// # Examples
+///
+/// ```
+/// use packet_crafter::send::raw_socket_privilege_in_status;
+///
+/// assert!(raw_socket_privilege_in_status("Uid:\t0\t0\t0\t0\nCapEff:\t0000000000000000\n"));
+/// assert!(raw_socket_privilege_in_status("Uid:\t1000\t1000\t1000\t1000\nCapEff:\t0000000000002000\n"));
+/// assert!(!raw_socket_privilege_in_status("Uid:\t1000\t1000\t1000\t1000\nCapEff:\t0000000000000000\n"));
+/// ```
+pub fn raw_socket_privilege_in_status(status: &str) -> bool {
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(|rest| rest.split_whitespace().collect::<Vec<_>>())
+    };
+    // Uid: real, effective, saved, filesystem
+    let root = field("Uid:").is_some_and(|uids| uids.get(1) == Some(&"0"));
+    let net_raw = field("CapEff:")
+        .and_then(|caps| u64::from_str_radix(caps.first()?, 16).ok())
+        .is_some_and(|caps| caps & (1 << CAP_NET_RAW) != 0);
+    root || net_raw
+}
+
+/// Result of sending one frame with [`send_with_retries`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SendOutcome {
+    /// Number of times the frame was handed to the transport
+    pub attempts: u32,
+    /// Total bytes written across all successful attempts
+    pub bytes_sent: usize,
+    /// Whether a reply to the frame was observed
+    pub reply_seen: bool,
+    /// Time spent from the first attempt to the last
+    pub elapsed: Duration,
+}
+
+/// Sends a frame, retrying on transport errors and, if `expect_reply` is
+/// set, on timeouts.
+///
+/// Without `expect_reply` the first successful write ends the loop and
+/// replies are not waited for. With it, each attempt waits up to `timeout`
+/// and the loop ends at the first reply.
+///
+/// # Arguments
+///
+/// * `transport` - Sends the frame and reports replies
+/// * `packet` - The complete packet bytes (Ethernet frame)
+/// * `retries` - Extra attempts allowed after the first one
+/// * `expect_reply` - Whether a missing reply counts as a failed attempt
+/// * `timeout` - How long to wait for a reply per attempt
+///
+/// # Returns
+///
+/// - `Ok(SendOutcome)` once an attempt succeeded or the retries ran out with
+///   at least one frame written
+/// - `Err(Box<dyn std::error::Error>)` with the last transport error if no
+///   attempt managed to write the frame
+pub fn send_with_retries<T: ProbeTransport>(
+    transport: &mut T,
+    packet: &[u8],
+    retries: u32,
+    expect_reply: bool,
+    timeout: Duration,
+) -> Result<SendOutcome, Box<dyn std::error::Error>> {
+    let start = Instant::now();
+    let wait = if expect_reply { timeout } else { Duration::ZERO };
+    let mut outcome = SendOutcome {
+        attempts: 0,
+        bytes_sent: 0,
+        reply_seen: false,
+        elapsed: Duration::ZERO,
+    };
+    let mut last_error = None;
+    
+    while outcome.attempts <= retries {
+        outcome.attempts += 1;
+        match transport.probe(packet, wait) {
+            Ok(reply) => {
+                outcome.bytes_sent += packet.len();
+                outcome.reply_seen = reply.is_some();
+                if outcome.reply_seen || !expect_reply {
+                    break;
+                }
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    
+    outcome.elapsed = start.elapsed();
+    match last_error {
+        Some(e) if outcome.bytes_sent == 0 => Err(e),
+        _ => Ok(outcome),
+    }
+}
@@ -0,0 +1,167 @@
+//! TTL-based traceroute.
+//!
+//! Probes are built with increasing TTL, from 1 up to a hop limit, and the
+//! routers that answer with ICMP Time Exceeded are recorded as the path. The
+//! loop talks to the network through the [`ProbeTransport`] trait so it can
+//! be driven by a mock in tests; [`DatalinkTransport`] is the real
+//! implementation used by send mode.
+
+use pnet::datalink::{self, Channel, Config, DataLinkReceiver, DataLinkSender};
+use pnet::packet::Packet;
+use pnet::packet::ethernet::{EthernetPacket, EtherTypes};
+use pnet::packet::icmp::{IcmpPacket, IcmpTypes};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use std::net::Ipv4Addr;
+use std::time::{Duration, Instant};
+
+use crate::packet::PacketBuilder;
+
+/// A reply observed for one probe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbeReply {
+    /// Address of the host that answered
+    pub from: Ipv4Addr,
+    /// Whether the answer came from the destination itself
+    pub reached: bool,
+}
+
+/// One entry of a traceroute path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hop {
+    /// TTL the probe was sent with
+    pub ttl: u8,
+    /// Address that answered, or `None` if the probe timed out
+    pub responder: Option<Ipv4Addr>,
+}
+
+/// Sends a probe and waits for the reply it provokes.
+pub trait ProbeTransport {
+    /// Sends `packet` and waits up to `timeout` for a reply.
+    ///
+    /// Returns `Ok(None)` if nothing relevant arrived before the timeout.
+    fn probe(&mut self, packet: &[u8], timeout: Duration) -> Result<Option<ProbeReply>, Box<dyn std::error::Error>>;
+}
+
+/// Runs a traceroute towards the builder's destination.
+///
+/// For each TTL from 1 to `max_hops`, one probe is built with
+/// [`PacketBuilder::build_packet`] and sent through `transport`. The loop
+/// stops early once the destination answers.
+///
+/// # Arguments
+///
+/// * `builder` - Builder configured for the destination; its TTL is changed
+/// * `transport` - Sends probes and reports replies
+/// * `payload` - Payload of every probe
+/// * `max_hops` - Highest TTL to try
+/// * `timeout` - How long to wait for each reply
+///
+/// # Returns
+///
+/// The hops in TTL order, or the first build/transport error.
+pub fn traceroute<T: ProbeTransport>(
+    builder: &mut PacketBuilder,
+    transport: &mut T,
+    payload: &[u8],
+    max_hops: u8,
+    timeout: Duration,
+) -> Result<Vec<Hop>, Box<dyn std::error::Error>> {
+    let mut path = Vec::new();
+    for ttl in 1..=max_hops {
+        builder.set_ttl(ttl);
+        let packet = builder.build_packet(payload)?;
+        let reply = transport.probe(packet, timeout)?;
+        path.push(Hop { ttl, responder: reply.map(|r| r.from) });
+        if reply.is_some_and(|r| r.reached) {
+            break;
+        }
+    }
+    Ok(path)
+}
+
+/// Interprets a received frame as a reply to a probe sent to `target`.
+///
+/// Recognized replies:
+/// - ICMP Time Exceeded whose quoted header was addressed to `target`
+///   (an intermediate hop)
+/// - ICMP Destination Unreachable from `target` (the destination)
+/// - Any TCP segment from `target` (the destination, e.g. SYN-ACK or RST)
+///
+/// Returns `None` for anything else.
+pub fn classify_reply(frame: &[u8], target: Ipv4Addr) -> Option<ProbeReply> {
+    let eth = EthernetPacket::new(frame)?;
+    if eth.get_ethertype() != EtherTypes::Ipv4 {
+        return None;
+    }
+    let ip = Ipv4Packet::new(eth.payload())?;
+    let from = ip.get_source();
+    match ip.get_next_level_protocol() {
+        IpNextHeaderProtocols::Icmp => {
+            let icmp = IcmpPacket::new(ip.payload())?;
+            match icmp.get_icmp_type() {
+                IcmpTypes::TimeExceeded => {
+                    // 4 unused bytes, then the original IP header
+                    let quoted = Ipv4Packet::new(icmp.payload().get(4..)?)?;
+                    (quoted.get_destination() == target).then_some(ProbeReply { from, reached: false })
+                }
+                IcmpTypes::DestinationUnreachable | IcmpTypes::EchoReply if from == target => {
+                    Some(ProbeReply { from, reached: true })
+                }
+                _ => None,
+            }
+        }
+        IpNextHeaderProtocols::Tcp if from == target => Some(ProbeReply { from, reached: true }),
+        _ => None,
+    }
+}
+
+/// Probe transport over a raw Layer 2 channel on a named interface.
+pub struct DatalinkTransport {
+    tx: Box<dyn DataLinkSender>,
+    rx: Box<dyn DataLinkReceiver>,
+    target: Ipv4Addr,
+}
+
+impl DatalinkTransport {
+    /// Opens a channel on `interface_name` for probing `target`.
+    ///
+    /// Requires raw socket privileges.
+    pub fn open(interface_name: &str, target: Ipv4Addr) -> Result<Self, Box<dyn std::error::Error>> {
+        let interface = datalink::interfaces()
+            .into_iter()
+            .find(|iface| iface.name == interface_name)
+            .ok_or_else(|| format!("Interface '{}' not found", interface_name))?;
+        let config = Config {
+            read_timeout: Some(Duration::from_millis(50)),
+            ..Default::default()
+        };
+        match datalink::channel(&interface, config)? {
+            Channel::Ethernet(tx, rx) => Ok(Self { tx, rx, target }),
+            _ => Err("Unsupported datalink channel type".into()),
+        }
+    }
+}
+
+impl ProbeTransport for DatalinkTransport {
+    fn probe(&mut self, packet: &[u8], timeout: Duration) -> Result<Option<ProbeReply>, Box<dyn std::error::Error>> {
+        match self.tx.send_to(packet, None) {
+            Some(result) => result?,
+            None => return Err("Failed to send packet".into()),
+        }
+        
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            match self.rx.next() {
+                Ok(frame) => {
+                    if let Some(reply) = classify_reply(frame, self.target) {
+                        return Ok(Some(reply));
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,38 @@
+//! Tests for the runtime capability queries
+
+use packet_crafter::{supported_debug_formats, supported_l4_protocols, DebugFormat, L4Protocol, ValueEnum};
+
+#[test]
+fn test_supported_l4_protocols() {
+    let protocols = supported_l4_protocols();
+    assert!(protocols.contains(&"tcp"));
+    assert!(protocols.contains(&"udp"));
+}
+
+#[test]
+fn test_supported_debug_formats() {
+    let formats = supported_debug_formats();
+    assert!(formats.contains(&"json"));
+    assert!(formats.contains(&"pcap"));
+}
+
+#[test]
+fn test_supported_names_match_cli_values() {
+    for name in supported_l4_protocols() {
+        assert!(L4Protocol::from_str(name, false).is_ok(), "'{}' should parse", name);
+    }
+    for name in supported_debug_formats() {
+        assert!(DebugFormat::from_str(name, false).is_ok(), "'{}' should parse", name);
+    }
+}
+
+#[test]
+fn test_debug_format_extension_round_trip() {
+    for format in DebugFormat::value_variants() {
+        assert_eq!(DebugFormat::from_extension(format.extension()).as_ref(), Some(format));
+    }
+    assert_eq!(DebugFormat::from_extension("yml"), Some(DebugFormat::Yaml));
+    assert_eq!(DebugFormat::from_extension("Json"), Some(DebugFormat::Json));
+    assert_eq!(DebugFormat::from_extension("txt"), Some(DebugFormat::Tcpdump));
+    assert_eq!(DebugFormat::from_extension("bin"), None);
+}
@@ -1,6 +1,6 @@
 //! Tests for command-line argument validation
 
-use packet_crafter::Args;
+use packet_crafter::{Args, DebugFormat, HexBytes, Parser, output::Sink};
 
 #[test]
 fn test_validation_both_format_and_file() {
@@ -15,6 +15,7 @@ fn test_validation_both_format_and_file() {
         debug_file: Some("test.json".to_string()),
         debug_format: Some(packet_crafter::DebugFormat::Json),
         ip_bitfield: 0,
+        ..Args::default()
     };
     
     assert!(args.validate().is_ok(), "Valid args should pass validation");
@@ -33,6 +34,7 @@ fn test_validation_neither_format_nor_file() {
         debug_file: None,
         debug_format: None,
         ip_bitfield: 0,
+        ..Args::default()
     };
     
     assert!(args.validate().is_ok(), "No debug output should be valid");
@@ -51,6 +53,7 @@ fn test_validation_format_without_file() {
         debug_file: None,
         debug_format: Some(packet_crafter::DebugFormat::Json),
         ip_bitfield: 0,
+        ..Args::default()
     };
     
     assert!(args.validate().is_err(), "Format without file should fail");
@@ -69,6 +72,7 @@ fn test_validation_file_without_format() {
         debug_file: Some("test.json".to_string()),
         debug_format: None,
         ip_bitfield: 0,
+        ..Args::default()
     };
     
     assert!(args.validate().is_err(), "File without format should fail");
@@ -87,6 +91,7 @@ fn test_validation_extension_mismatch() {
         debug_file: Some("test.pcap".to_string()),
         debug_format: Some(packet_crafter::DebugFormat::Json),
         ip_bitfield: 0,
+        ..Args::default()
     };
     
     assert!(args.validate().is_err(), "Format/extension mismatch should fail");
@@ -105,8 +110,188 @@ fn test_validation_pcap_format() {
         debug_file: Some("test.pcap".to_string()),
         debug_format: Some(packet_crafter::DebugFormat::Pcap),
         ip_bitfield: 0,
+        ..Args::default()
     };
     
     assert!(args.validate().is_ok(), "PCAP format with .pcap extension should be valid");
 }
 
+
+#[test]
+fn test_validation_rst_requires_tcp() {
+    let mut args = Args { rst: true, ..Args::default() };
+    assert!(args.validate().is_err(), "RST over UDP should fail");
+    
+    args.l4_protocol = packet_crafter::L4Protocol::Tcp;
+    assert!(args.validate().is_ok(), "RST over TCP should be valid");
+}
+
+#[test]
+fn test_validation_yaml_extensions() {
+    let mut args = Args {
+        debug_file: Some("test.yaml".to_string()),
+        debug_format: Some(packet_crafter::DebugFormat::Yaml),
+        ..Args::default()
+    };
+    assert!(args.validate().is_ok(), "YAML format with .yaml extension should be valid");
+    
+    args.debug_file = Some("test.yml".to_string());
+    assert!(args.validate().is_ok(), "YAML format with .yml extension should be valid");
+    
+    args.debug_file = Some("test.json".to_string());
+    assert!(args.validate().is_err(), "YAML format with .json extension should fail");
+}
+
+#[test]
+fn test_apply_flow_sets_fields() {
+    let mut args = Args::parse_from(["packet_crafter", "--flow", "tcp 10.0.0.1:1234 -> 8.8.8.8:443"]);
+    args.apply_flow();
+    
+    assert_eq!(args.l4_protocol, packet_crafter::L4Protocol::Tcp);
+    assert_eq!(args.src_ip.to_string(), "10.0.0.1");
+    assert_eq!(args.src_port, 1234);
+    assert_eq!(args.dst_ip.to_string(), "8.8.8.8");
+    assert_eq!(args.dest_port, 443);
+}
+
+#[test]
+fn test_reflect_port_sets_both_ports() {
+    use pnet::packet::{Packet, ethernet::EthernetPacket, ipv4::Ipv4Packet, udp::UdpPacket};
+    
+    let mut args = Args::parse_from(["packet_crafter", "--reflect_port=53"]);
+    args.apply_flow();
+    assert_eq!((args.src_port, args.dest_port), (53, 53));
+    assert!(args.strict_warnings().is_empty(), "equal ports are intended with --reflect_port");
+    
+    let mut builder = packet_crafter::packet::PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"reflect").unwrap();
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!((udp.get_source(), udp.get_destination()), (53, 53));
+    assert_eq!(udp.get_checksum(), pnet::packet::udp::ipv4_checksum(&udp, &args.src_ip, &args.dst_ip));
+    
+    let unintended = Args::parse_from(["packet_crafter", "--src_port=53", "--dest_port=53"]);
+    assert_eq!(unintended.strict_warnings().len(), 1);
+    assert!(Args::try_parse_from(["packet_crafter", "--reflect_port=53", "--src_port=1"]).is_err());
+}
+
+#[test]
+fn test_payload_pad_fills_payload_size() {
+    let args = Args::parse_from(["packet_crafter", "--payload_pad=0xAA", "--payload_size=100"]);
+    assert!(args.validate().is_ok());
+    assert_eq!(args.payload(), vec![0xAA; 100]);
+    
+    let args = Args::parse_from(["packet_crafter", "--payload_pad=0x41"]);
+    assert!(args.validate().is_err(), "--payload_pad needs a size");
+}
+
+#[test]
+fn test_multiple_sinks_parse_in_order() {
+    let args = Args::try_parse_from([
+        "packet_crafter",
+        "--sink", "pcap:out.pcap",
+        "--sink", "stdout:hex",
+        "--sink", "send:eth0",
+    ])
+    .unwrap();
+    
+    assert_eq!(
+        args.sinks,
+        vec![
+            Sink::File { format: DebugFormat::Pcap, path: "out.pcap".to_string() },
+            Sink::StdoutHex,
+            Sink::Send { interface: "eth0".to_string() },
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_sink_rejected() {
+    assert!(Args::try_parse_from(["packet_crafter", "--sink", "ftp:host"]).is_err());
+    assert!(Args::try_parse_from(["packet_crafter", "--sink", "stdout:binary"]).is_err());
+    assert!(Args::try_parse_from(["packet_crafter", "--sink", "pcap"]).is_err());
+}
+
+#[test]
+fn test_tcp_reserved_range() {
+    let args = Args::try_parse_from(["packet_crafter", "--tcp_reserved", "15"]).unwrap();
+    assert_eq!(args.tcp_reserved, 15);
+    assert!(Args::try_parse_from(["packet_crafter", "--tcp_reserved", "16"]).is_err());
+}
+
+#[test]
+fn test_ip_timestamp_addrs_limit() {
+    let args = Args::try_parse_from(["packet_crafter", "--ip_timestamp_addrs", "10.0.0.1,10.0.0.2"]).unwrap();
+    assert_eq!(args.ip_timestamp_addrs.len(), 2);
+    assert!(args.validate().is_ok());
+    
+    let args = Args::try_parse_from([
+        "packet_crafter",
+        "--ip_timestamp_addrs",
+        "10.0.0.1,10.0.0.2,10.0.0.3,10.0.0.4,10.0.0.5",
+    ])
+    .unwrap();
+    assert!(args.validate().is_err(), "Five addresses do not fit in the IPv4 options");
+}
+
+#[test]
+fn test_payload_counter_too_small() {
+    let args = Args { payload_counter: Some(3), ..Args::default() };
+    assert!(args.validate().is_err());
+    let args = Args { payload_counter: Some(4), ..Args::default() };
+    assert!(args.validate().is_ok());
+}
+
+#[test]
+fn test_auto_format_from_extension() {
+    let args = Args::try_parse_from(["packet_crafter", "--auto_format", "--debug_file", "capture.pcap"]).unwrap();
+    assert_eq!(args.resolved_debug_format(), Some(DebugFormat::Pcap));
+    assert!(args.validate().is_ok());
+    
+    let args = Args::try_parse_from(["packet_crafter", "--auto_format", "--debug_file", "capture.bin"]).unwrap();
+    assert_eq!(args.resolved_debug_format(), None);
+    assert!(args.validate().is_err(), "Unknown extension cannot be inferred");
+    
+    let args = Args::try_parse_from(["packet_crafter", "--debug_file", "capture.pcap"]).unwrap();
+    assert_eq!(args.resolved_debug_format(), None, "Inference is opt-in");
+}
+
+#[test]
+fn test_ipv6_ext_without_ns_target_needs_tcp_or_udp() {
+    let args = Args::try_parse_from(["packet_crafter", "--ipv6_ext", "fragment", "--l4_protocol", "tcp"]).unwrap();
+    assert!(args.validate().is_ok());
+    
+    let args = Args::try_parse_from(["packet_crafter", "--ipv6_ext", "fragment", "--l4_protocol", "icmp"]).unwrap();
+    assert!(args.validate().is_err(), "ICMP is only built over IPv4");
+    
+    let args = Args::try_parse_from(["packet_crafter", "--ipv6_ext", "hopbyhop", "--vlan_id", "10"]).unwrap();
+    assert!(args.validate().is_err(), "VLAN tags only wrap IPv4");
+}
+
+#[test]
+fn test_ethertype_override_rejects_ipv4_field_tools() {
+    for extra in ["--refix_ip_checksum", "--endian_swap=tcp.window", "--overrides=o.json", "--sink=json:out.json"] {
+        let args = Args::try_parse_from(["packet_crafter", "--ethertype_override=0x86dd", extra]).unwrap();
+        assert!(args.validate().is_err(), "{} should be rejected", extra);
+    }
+    
+    let args = Args::try_parse_from(["packet_crafter", "--ethertype_override=0x86dd", "--debug_file=out.pcap", "--debug_format=pcap"]).unwrap();
+    assert!(args.validate().is_ok(), "PCAP output does not decode the frame");
+    let args = Args::try_parse_from(["packet_crafter", "--ethertype_override=0x0800", "--refix_ip_checksum"]).unwrap();
+    assert!(args.validate().is_ok(), "An IPv4 EtherType hides nothing");
+}
+
+#[test]
+fn test_raw_overrides_requires_overrides() {
+    let args = Args::try_parse_from(["packet_crafter", "--raw_overrides"]).unwrap();
+    assert!(args.validate().is_err());
+    let args = Args::try_parse_from(["packet_crafter", "--raw_overrides", "--overrides=o.json", "--refix_ip_checksum"]).unwrap();
+    assert!(args.validate().is_ok());
+}
+
+#[test]
+fn test_trailer_hex_parses_as_one_value() {
+    let args = Args::try_parse_from(["packet_crafter", "--trailer_hex=dead beef"]).unwrap();
+    assert_eq!(args.trailer_hex, Some(HexBytes(vec![0xde, 0xad, 0xbe, 0xef])));
+}
@@ -0,0 +1,36 @@
+//! Tests for the benchmark harness
+
+use packet_crafter::{Args, L4Protocol, bench::{compare_alloc, time_builds}, packet::PacketBuilder};
+
+// ==================== Alloc Benchmark Tests ====================
+
+#[test]
+fn test_compare_alloc_completes() {
+    let mut builder = PacketBuilder::from(&Args::default());
+    
+    let report = compare_alloc(&mut builder, b"bench", 50).unwrap();
+    
+    assert_eq!(report.iterations, 50);
+    assert!(report.overhead_per_packet_ns().is_finite());
+}
+
+#[test]
+fn test_time_builds_both_paths() {
+    let mut builder = PacketBuilder::from(&Args::default());
+    
+    assert!(time_builds(&mut builder, b"bench", 10, false).is_ok());
+    assert!(time_builds(&mut builder, b"bench", 10, true).is_ok());
+}
+
+#[test]
+fn test_owned_matches_borrowed() {
+    for protocol in [L4Protocol::Udp, L4Protocol::Tcp] {
+        let args = Args { l4_protocol: protocol, ..Args::default() };
+        let mut builder = PacketBuilder::from(&args);
+        
+        let owned = builder.build_packet_owned(b"bench").unwrap();
+        let borrowed = builder.build_packet(b"bench").unwrap();
+        
+        assert_eq!(owned.as_slice(), borrowed);
+    }
+}
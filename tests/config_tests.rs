@@ -0,0 +1,126 @@
+//! Tests for TOML config files and profiles
+
+use packet_crafter::config::{config_args, parse_args, spec_args};
+use packet_crafter::{packet::PacketBuilder, L4Protocol};
+use std::fs;
+use tempfile::TempDir;
+
+const PROFILES: &str = r#"
+verbose = true
+
+[profiles.web]
+dst_ip = "10.0.0.80"
+dest_port = 443
+l4_protocol = "tcp"
+
+[profiles.dns]
+dst_ip = "10.0.0.53"
+dest_port = 53
+"#;
+
+fn write_config(dir: &TempDir) -> String {
+    let path = dir.path().join("targets.toml");
+    fs::write(&path, PROFILES).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_profile_selects_values() {
+    let dir = TempDir::new().unwrap();
+    let config = write_config(&dir);
+    
+    let args = parse_args(["packet_crafter", "--config", &config, "--profile=dns"]).unwrap();
+    
+    assert_eq!(args.dest_port, 53);
+    assert_eq!(args.dst_ip, "10.0.0.53".parse::<std::net::Ipv4Addr>().unwrap());
+    assert_eq!(args.l4_protocol, L4Protocol::Udp, "Values of other profiles should not leak");
+    assert!(args.verbose, "Top-level values should apply");
+}
+
+#[test]
+fn test_command_line_overrides_profile() {
+    let dir = TempDir::new().unwrap();
+    let config = write_config(&dir);
+    
+    let args = parse_args(["packet_crafter", "--dest_port=8443", "--config", &config, "--profile=web"]).unwrap();
+    
+    assert_eq!(args.dest_port, 8443);
+    assert_eq!(args.l4_protocol, L4Protocol::Tcp);
+}
+
+#[test]
+fn test_unknown_profile_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    let config = write_config(&dir);
+    
+    let err = parse_args(["packet_crafter", "--config", &config, "--profile=ntp"]).unwrap_err();
+    assert!(err.to_string().contains("Unknown profile 'ntp'"), "{}", err);
+}
+
+#[test]
+fn test_config_args_conversion() {
+    let text = "sink = [\"stdout:hex\", \"pcap:out.pcap\"]\nrst = false\n[profiles.p]\nttl_hint = 1.5\n";
+    
+    assert_eq!(config_args(text, None).unwrap(), ["--sink=stdout:hex", "--sink=pcap:out.pcap"]);
+    assert_eq!(config_args(text, Some("p")).unwrap().last().unwrap(), "--ttl_hint=1.5");
+    assert!(config_args("profile = \"x\"", None).is_err());
+    assert!(config_args("[nested]\nkey = 1", None).is_err());
+}
+
+const TCP_SPEC: &str = r#"
+ethernet:
+  src: "02:00:00:00:00:01"
+  dst: "02:00:00:00:00:02"
+ip:
+  src: 10.0.0.1
+  dst: 10.0.0.80
+l4:
+  protocol: tcp
+  src_port: 40000
+  dst_port: 443
+  seq: 1000
+payload:
+  size: 16
+  pad: 65
+"#;
+
+#[test]
+fn test_spec_matches_flag_build() {
+    let dir = TempDir::new().unwrap();
+    let spec = dir.path().join("packet.yaml");
+    fs::write(&spec, TCP_SPEC).unwrap();
+    
+    let from_spec = parse_args(["packet_crafter", "--spec", spec.to_str().unwrap()]).unwrap();
+    let from_flags = parse_args([
+        "packet_crafter",
+        "--src_mac=02:00:00:00:00:01",
+        "--dst_mac=02:00:00:00:00:02",
+        "--src_ip=10.0.0.1",
+        "--dst_ip=10.0.0.80",
+        "--l4_protocol=tcp",
+        "--src_port=40000",
+        "--dest_port=443",
+        "--tcp_seq=1000",
+        "--payload_size=16",
+        "--payload_pad=65",
+    ])
+    .unwrap();
+    assert_eq!(from_spec.l4_protocol, L4Protocol::Tcp);
+    
+    let spec_packet = PacketBuilder::from(&from_spec).build_packet(&from_spec.payload()).unwrap().to_vec();
+    let flag_packet = PacketBuilder::from(&from_flags).build_packet(&from_flags.payload()).unwrap().to_vec();
+    assert_eq!(spec_packet, flag_packet);
+}
+
+#[test]
+fn test_spec_rejects_unknown_keys_and_yields_to_command_line() {
+    let err = spec_args("l4:\n  protocol: tcp\n  window: 10\n").unwrap_err();
+    assert!(err.contains("window"), "{}", err);
+    
+    let dir = TempDir::new().unwrap();
+    let spec = dir.path().join("packet.yaml");
+    fs::write(&spec, TCP_SPEC).unwrap();
+    let args = parse_args(["packet_crafter", "--dest_port=8443", "--spec", spec.to_str().unwrap()]).unwrap();
+    assert_eq!(args.dest_port, 8443);
+    assert_eq!(args.src_port, 40000);
+}
@@ -0,0 +1,76 @@
+//! Tests for streaming decode of PCAP captures
+
+use packet_crafter::{Args, L4Protocol, Parser, packet::PacketBuilder, output::write_pcap_session};
+use packet_crafter::decode::{decode_packet, stream, DecodeError, L4Info};
+use std::io::Cursor;
+use tempfile::TempDir;
+
+/// Returns the bytes of a PCAP holding `packets`.
+fn pcap_bytes(packets: &[Vec<u8>]) -> Vec<u8> {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("capture.pcap");
+    write_pcap_session(&path, packets).unwrap();
+    std::fs::read(path).unwrap()
+}
+
+#[test]
+fn test_stream_summarizes_each_packet() {
+    let udp = PacketBuilder::from(&Args::default()).build_packet(b"first").unwrap().to_vec();
+    let tcp_args = Args { l4_protocol: L4Protocol::Tcp, dest_port: 443, ..Args::default() };
+    let tcp = PacketBuilder::from(&tcp_args).build_packet(b"second").unwrap().to_vec();
+
+    let summaries: Vec<_> = stream(Cursor::new(pcap_bytes(&[udp.clone(), tcp.clone(), udp.clone()])))
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(summaries.len(), 3);
+    assert_eq!(summaries.iter().map(|s| s.index).collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(summaries[0].length, udp.len());
+    assert_eq!(summaries[1].length, tcp.len());
+    assert!(matches!(summaries[0].decoded.l4, Some(L4Info::Udp { .. })));
+    assert!(matches!(summaries[1].decoded.l4, Some(L4Info::Tcp { destination_port: 443, .. })));
+}
+
+#[test]
+fn test_stream_reports_bad_frames_and_continues() {
+    let udp = PacketBuilder::from(&Args::default()).build_packet(b"ok").unwrap().to_vec();
+    let runt = vec![0u8; 6];
+
+    let results: Vec<_> = stream(Cursor::new(pcap_bytes(&[runt, udp]))).collect();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0], Err(DecodeError::TruncatedEthernet));
+    assert_eq!(results[1].as_ref().unwrap().index, 1);
+}
+
+#[test]
+fn test_truncate_at_cuts_inside_ipv4_header() {
+    let args = Args::try_parse_from(["packet_crafter", "--truncate_at=20"]).unwrap();
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"payload").unwrap();
+    
+    assert_eq!(packet.len(), 20);
+    assert_eq!(decode_packet(packet), Err(DecodeError::TruncatedIpv4));
+}
+
+#[test]
+fn test_decode_pppoe_session_frame() {
+    let args = Args::try_parse_from(["packet_crafter", "--pppoe_session=4660", "--l4_protocol=tcp"]).unwrap();
+    let mut builder = PacketBuilder::from(&args);
+    let decoded = decode_packet(builder.build_packet(b"payload").unwrap()).unwrap();
+    
+    assert_eq!(decoded.ethernet.ethertype, 0x8864);
+    assert_eq!(decoded.ipv4.unwrap().destination, args.dst_ip.to_string());
+    assert!(matches!(decoded.l4, Some(L4Info::Tcp { destination_port: 80, .. })));
+}
+
+#[test]
+fn test_decode_vlan_tagged_frame() {
+    let args = Args::try_parse_from(["packet_crafter", "--vlan_id=100", "--pppoe_session=4660"]).unwrap();
+    let mut builder = PacketBuilder::from(&args);
+    let decoded = decode_packet(builder.build_packet(b"payload").unwrap()).unwrap();
+    
+    assert_eq!(decoded.ethernet.ethertype, 0x8100);
+    assert_eq!(decoded.ipv4.unwrap().destination, args.dst_ip.to_string());
+    assert!(matches!(decoded.l4, Some(L4Info::Udp { destination_port: 80, .. })));
+}
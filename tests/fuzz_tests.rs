@@ -0,0 +1,62 @@
+//! Tests for seeded field fuzzing
+
+use packet_crafter::{Args, CrafterError, L4Protocol, packet::PacketBuilder};
+use packet_crafter::fuzz::fuzz_frames;
+use pnet::packet::Packet;
+use pnet::packet::ethernet::EthernetPacket;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::tcp::TcpPacket;
+
+fn tcp_frame() -> Vec<u8> {
+    let args = Args { l4_protocol: L4Protocol::Tcp, ..Args::default() };
+    PacketBuilder::from(&args).build_packet(b"fuzz").unwrap().to_vec()
+}
+
+fn fields(paths: &[&str]) -> Vec<String> {
+    paths.iter().map(|path| path.to_string()).collect()
+}
+
+#[test]
+fn test_fuzz_is_deterministic_for_a_seed() {
+    let frame = tcp_frame();
+    let fields = fields(&["ipv4.ttl", "tcp.window"]);
+
+    let first = fuzz_frames(&frame, &fields, 16, 42).unwrap();
+    let second = fuzz_frames(&frame, &fields, 16, 42).unwrap();
+    assert_eq!(first, second, "same seed should give the same packets");
+    assert_ne!(first, fuzz_frames(&frame, &fields, 16, 43).unwrap());
+
+    let windows: Vec<u16> = first
+        .iter()
+        .map(|packet| {
+            let eth = EthernetPacket::new(packet).unwrap();
+            let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+            TcpPacket::new(ipv4.payload()).unwrap().get_window()
+        })
+        .collect();
+    assert!(windows.iter().any(|&window| window != windows[0]), "values should vary across packets");
+}
+
+#[test]
+fn test_fuzz_only_changes_targeted_fields() {
+    let frame = tcp_frame();
+
+    for packet in fuzz_frames(&frame, &fields(&["ipv4.ttl"]), 8, 1).unwrap() {
+        assert_eq!(packet.len(), frame.len());
+        let eth = EthernetPacket::new(&packet).unwrap();
+        let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+        assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4), "IPv4 checksum should be recomputed");
+
+        // Only the TTL (byte 8) and header checksum (bytes 10-11) of the IPv4 header may differ
+        let differing: Vec<usize> = (0..frame.len()).filter(|&i| packet[i] != frame[i]).collect();
+        assert!(differing.iter().all(|&i| [14 + 8, 14 + 10, 14 + 11].contains(&i)), "unexpected change at {:?}", differing);
+    }
+}
+
+#[test]
+fn test_fuzz_rejects_structural_fields() {
+    assert_eq!(
+        fuzz_frames(&tcp_frame(), &fields(&["ipv4.total_length"]), 1, 0),
+        Err(CrafterError::UnknownOverrideField { path: "ipv4.total_length".to_string() })
+    );
+}
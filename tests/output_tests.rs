@@ -1,6 +1,6 @@
 //! Tests for output functionality (PCAP and JSON writing)
 
-use packet_crafter::{Args, L4Protocol, packet::PacketBuilder, output::{write_pcap, write_json}, parsing};
+use packet_crafter::{Args, L4Protocol, packet::PacketBuilder, output::{write_pcap, write_json, write_yaml, write_atomic, write_json_session, write_pcap_session, write_bundle, read_bundle, render_json, render_json_schema, render_tcpdump, write_tcpdump, write_digest_sidecar, write_if_changed, estimate_packet_count, write_index, pcap_packet_offsets, PacketInfo}, parsing};
 use std::fs;
 use tempfile::TempDir;
 use pcap_file::pcap::PcapReader;
@@ -17,6 +17,7 @@ fn create_test_args() -> Args {
         debug_file: None,
         debug_format: None,
         ip_bitfield: 0,
+        ..Args::default()
     }
 }
 
@@ -29,7 +30,7 @@ fn test_write_pcap_creates_valid_file() {
     
     let args = create_test_args();
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"test packet");
+    let packet = builder.build_packet(b"test packet").unwrap();
     
     let result = write_pcap(&file_path, packet);
     assert!(result.is_ok(), "PCAP write should succeed");
@@ -48,7 +49,7 @@ fn test_write_pcap_contains_packet_data() {
     
     let args = create_test_args();
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"test");
+    let packet = builder.build_packet(b"test").unwrap();
     let packet_len = packet.len();
     
     write_pcap(&file_path, packet).unwrap();
@@ -70,7 +71,7 @@ fn test_write_json_creates_valid_file() {
     
     let args = create_test_args();
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"test packet");
+    let packet = builder.build_packet(b"test packet").unwrap();
     
     let result = write_json(&file_path, packet);
     assert!(result.is_ok(), "JSON write should succeed");
@@ -92,7 +93,7 @@ fn test_write_json_correct_data() {
     
     let args = create_test_args();
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"test");
+    let packet = builder.build_packet(b"test").unwrap();
     let expected_hex = hex::encode(packet);
     let expected_len = packet.len();
     
@@ -105,6 +106,94 @@ fn test_write_json_correct_data() {
     assert_eq!(json["data"].as_str().unwrap(), expected_hex);
 }
 
+// ==================== YAML Output Tests ====================
+
+#[test]
+fn test_write_yaml_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.yaml");
+    
+    let mut args = create_test_args();
+    args.l4_protocol = L4Protocol::Tcp;
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"yaml").unwrap();
+    let expected_hex = hex::encode(packet);
+    let expected_len = packet.len();
+    
+    write_yaml(&file_path, packet).unwrap();
+    
+    let content = fs::read_to_string(&file_path).unwrap();
+    let info: PacketInfo = serde_yaml::from_str(&content).unwrap();
+    
+    assert_eq!(info.length, expected_len);
+    assert_eq!(info.data, expected_hex);
+    assert_eq!(info.ethernet.unwrap().source, "aa:bb:cc:dd:ee:ff");
+    let ipv4 = info.ipv4.unwrap();
+    assert_eq!(ipv4.source, "192.168.1.1");
+    assert_eq!(ipv4.destination, "192.168.1.2");
+    assert_eq!(ipv4.protocol, 6);
+    match info.l4.unwrap() {
+        packet_crafter::decode::L4Info::Tcp { destination_port, flags, .. } => {
+            assert_eq!(destination_port, 80);
+            assert_eq!(flags, 0x02);
+        }
+        other => panic!("Expected TCP header, got {:?}", other),
+    }
+}
+
+// ==================== tcpdump Output Tests ====================
+
+#[test]
+fn test_tcpdump_first_line_summarizes_packet() {
+    let args = create_test_args();
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap().to_vec();
+    
+    let text = render_tcpdump(&packet, 1_700_000_000.25);
+    let first = text.lines().next().unwrap();
+    assert!(first.starts_with("2023-11-14 22:13:20.250000 "), "unexpected timestamp: {}", first);
+    assert!(first.contains("IP 192.168.1.1.12345 > 192.168.1.2.80: UDP"), "unexpected summary: {}", first);
+    assert!(first.contains(&format!("length {}", packet.len())));
+    
+    // 46 bytes: two full hex lines and one of 14 bytes
+    let hex_lines: Vec<&str> = text.lines().skip(1).collect();
+    assert_eq!(hex_lines.len(), 3);
+    assert_eq!(hex_lines[0], "\t0x0000:  1122 3344 5566 aabb ccdd eeff 0800 4500");
+    assert!(hex_lines[2].starts_with("\t0x0020:  "));
+    
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.txt");
+    write_tcpdump(&file_path, &[packet.clone(), packet]).unwrap();
+    let written = fs::read_to_string(&file_path).unwrap();
+    assert_eq!(written.lines().filter(|line| line.contains(" IP ")).count(), 2);
+}
+
+// ==================== Session Output Tests ====================
+
+#[test]
+fn test_write_json_session_indexes_records() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("burst.json");
+    
+    let args = create_test_args();
+    let mut builder = PacketBuilder::from(&args);
+    let packets: Vec<Vec<u8>> = (0..3).map(|_| builder.build_packet(b"burst").unwrap().to_vec()).collect();
+    
+    write_json_session(&file_path, &packets).unwrap();
+    
+    let content = fs::read_to_string(&file_path).unwrap();
+    let records: Vec<PacketInfo> = serde_json::from_str(&content).unwrap();
+    let indices: Vec<Option<usize>> = records.iter().map(|r| r.index).collect();
+    assert_eq!(indices, vec![Some(0), Some(1), Some(2)]);
+}
+
+#[test]
+fn test_write_json_single_packet_has_no_index() {
+    let json = render_json(&[0xaa, 0xbb], 1.5).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert!(value.get("index").is_none(), "Single-packet output should not carry an index");
+}
+
 // ==================== Protocol Tests ====================
 
 #[test]
@@ -116,7 +205,7 @@ fn test_write_tcp_packet_to_pcap() {
     args.l4_protocol = L4Protocol::Tcp;
     
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"TCP test");
+    let packet = builder.build_packet(b"TCP test").unwrap();
     
     let result = write_pcap(&file_path, packet);
     assert!(result.is_ok(), "Should write TCP packet to PCAP");
@@ -131,8 +220,191 @@ fn test_write_tcp_packet_to_json() {
     args.l4_protocol = L4Protocol::Tcp;
     
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"TCP test");
+    let packet = builder.build_packet(b"TCP test").unwrap();
     
     let result = write_json(&file_path, packet);
     assert!(result.is_ok(), "Should write TCP packet to JSON");
 }
+
+// ==================== Determinism Tests ====================
+
+#[test]
+fn test_render_json_is_deterministic() {
+    let args = create_test_args();
+    
+    let mut first = PacketBuilder::from(&args);
+    let json_a = render_json(first.build_packet(b"snapshot").unwrap(), 1700000000.25).unwrap();
+    
+    let mut second = PacketBuilder::from(&args);
+    let json_b = render_json(second.build_packet(b"snapshot").unwrap(), 1700000000.25).unwrap();
+    
+    assert_eq!(json_a, json_b, "Same packet and timestamp should render identically");
+}
+
+// ==================== Atomic Write Tests ====================
+
+#[test]
+fn test_write_atomic_renames_into_place() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.json");
+    
+    let args = create_test_args();
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"atomic").unwrap();
+    
+    write_atomic(&file_path, |tmp| write_json(tmp, packet)).unwrap();
+    
+    let content = fs::read_to_string(&file_path).unwrap();
+    let info: PacketInfo = serde_json::from_str(&content).unwrap();
+    assert_eq!(info.length, packet.len());
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1, "Temporary file should be gone");
+}
+
+#[test]
+fn test_write_atomic_failed_write_leaves_no_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.pcap");
+    
+    let result = write_atomic(&file_path, |tmp| {
+        fs::write(tmp, b"partial")?;
+        Err("simulated write failure".into())
+    });
+    
+    assert!(result.is_err(), "Writer error should be returned");
+    assert!(!file_path.exists(), "Destination should not be created");
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0, "Temporary file should be removed");
+}
+
+// ==================== Bundle Output Tests ====================
+
+#[test]
+fn test_bundle_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("packets.bundle");
+    
+    let args = create_test_args();
+    let mut builder = PacketBuilder::from(&args);
+    let packets: Vec<Vec<u8>> = [&b"first"[..], b"second packet", b""]
+        .iter()
+        .map(|payload| builder.build_packet(payload).unwrap().to_vec())
+        .collect();
+    
+    write_bundle(&file_path, &packets).unwrap();
+    
+    let size = fs::metadata(&file_path).unwrap().len() as usize;
+    assert_eq!(size, 8 + packets.iter().map(|p| 4 + p.len()).sum::<usize>());
+    assert_eq!(read_bundle(&file_path).unwrap(), packets);
+}
+
+#[test]
+fn test_read_bundle_bad_magic() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("not.bundle");
+    fs::write(&file_path, b"PCAP\x00\x00\x00\x00").unwrap();
+    
+    let err = read_bundle(&file_path).unwrap_err();
+    assert!(err.to_string().contains("bad magic"), "Unexpected error: {}", err);
+}
+
+// ==================== JSON Schema Tests ====================
+
+#[test]
+fn test_json_schema_declares_layers() {
+    let schema: serde_json::Value = serde_json::from_str(&render_json_schema().unwrap()).unwrap();
+    
+    let properties = schema["properties"].as_object().expect("Schema should have properties");
+    for field in ["timestamp", "length", "data", "ethernet", "ipv4", "l4"] {
+        assert!(properties.contains_key(field), "Missing property {}", field);
+    }
+    assert!(schema["definitions"]["Ipv4Info"]["properties"]["ttl"].is_object());
+    assert!(schema["definitions"]["L4Info"].is_object());
+}
+
+// ==================== Digest Sidecar Tests ====================
+
+#[test]
+fn test_digest_sidecar_matches_packet_hash() {
+    use sha2::{Digest, Sha256};
+    
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("evidence.pcap");
+    
+    let args = create_test_args();
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"evidence").unwrap().to_vec();
+    
+    write_pcap(&file_path, &packet).unwrap();
+    write_digest_sidecar(&file_path, std::slice::from_ref(&packet)).unwrap();
+    
+    let sidecar = fs::read_to_string(temp_dir.path().join("evidence.pcap.sha256")).unwrap();
+    let expected: String = Sha256::digest(&packet).iter().map(|b| format!("{:02x}", b)).collect();
+    assert_eq!(sidecar, format!("{}\n", expected));
+}
+
+#[test]
+fn test_write_if_changed_skips_identical_run() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("fixture.pcap");
+    
+    let run = |args: &Args| {
+        let mut builder = PacketBuilder::from(args);
+        let (packet, hash) = builder.build_and_hash(b"fixture").unwrap();
+        let packet = packet.to_vec();
+        write_if_changed(&file_path, hash, |path| write_pcap(path, &packet)).unwrap()
+    };
+    
+    let args = create_test_args();
+    assert!(run(&args));
+    fs::write(&file_path, b"sentinel").unwrap();
+    assert!(!run(&args), "identical packet must not rewrite the file");
+    assert_eq!(fs::read(&file_path).unwrap(), b"sentinel");
+    
+    let changed = Args { dest_port: 8080, ..create_test_args() };
+    assert!(run(&changed));
+    assert_ne!(fs::read(&file_path).unwrap(), b"sentinel");
+}
+
+#[test]
+fn test_estimate_packet_count_matches_written_pcap() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("budget.pcap");
+    
+    let mut builder = PacketBuilder::from(&create_test_args());
+    let packet = builder.build_packet(b"budget").unwrap().to_vec();
+    
+    // 24-byte global header + 16-byte record header per packet
+    let budget = 24 + 3 * (16 + packet.len()) + 5;
+    let count = estimate_packet_count(packet.len(), budget);
+    assert_eq!(count, 3);
+    
+    write_pcap_session(&file_path, &vec![packet.clone(); count]).unwrap();
+    assert!(fs::metadata(&file_path).unwrap().len() as usize <= budget);
+    assert_eq!(estimate_packet_count(packet.len(), 10), 0);
+}
+
+#[test]
+fn test_index_offsets_locate_each_packet() {
+    use std::io::{Read, Seek, SeekFrom};
+    
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("indexed.pcap");
+    
+    let mut builder = PacketBuilder::from(&create_test_args());
+    let packets: Vec<Vec<u8>> = ["a", "bb", "a much longer payload"]
+        .iter()
+        .map(|payload| builder.build_packet(payload.as_bytes()).unwrap().to_vec())
+        .collect();
+    write_pcap_session(&file_path, &packets).unwrap();
+    write_index(&file_path, &pcap_packet_offsets(&packets)).unwrap();
+    
+    let index = fs::read_to_string(temp_dir.path().join("indexed.pcap.idx")).unwrap();
+    let mut file = fs::File::open(&file_path).unwrap();
+    assert_eq!(index.lines().count(), packets.len());
+    for (line, packet) in index.lines().zip(&packets) {
+        let (offset, length) = line.split_once(' ').unwrap();
+        let mut data = vec![0u8; length.parse().unwrap()];
+        file.seek(SeekFrom::Start(offset.parse().unwrap())).unwrap();
+        file.read_exact(&mut data).unwrap();
+        assert_eq!(&data, packet);
+    }
+}
@@ -0,0 +1,203 @@
+//! Tests for per-field overrides
+
+use packet_crafter::{Args, CrafterError, L4Protocol, Parser, packet::{PacketBuilder, PPPOE_HEADER_LENGTH, VLAN_TAG_LENGTH}};
+use packet_crafter::overrides::{apply_overrides, apply_overrides_raw, load_overrides, refix_ipv4_checksum, swap_field_endianness, Overrides};
+use pnet::packet::Packet;
+use pnet::packet::ethernet::EthernetPacket;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::tcp::TcpPacket;
+use tempfile::TempDir;
+
+fn build(protocol: L4Protocol) -> Vec<u8> {
+    let args = Args { l4_protocol: protocol, ..Args::default() };
+    let mut builder = PacketBuilder::from(&args);
+    builder.build_packet(b"override").unwrap().to_vec()
+}
+
+fn build_from(argv: &[&str]) -> Vec<u8> {
+    let args = Args::try_parse_from(argv).unwrap();
+    PacketBuilder::from(&args).build_packet(b"override").unwrap().to_vec()
+}
+
+fn table(entries: &[(&str, u64)]) -> Overrides {
+    entries.iter().map(|(path, value)| (path.to_string(), *value)).collect()
+}
+
+// ==================== Apply Tests ====================
+
+#[test]
+fn test_override_ipv4_ttl_recomputes_checksum() {
+    let mut frame = build(L4Protocol::Udp);
+    
+    apply_overrides(&mut frame, &table(&[("ipv4.ttl", 10)])).unwrap();
+    
+    let eth = EthernetPacket::new(&frame).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_ttl(), 10);
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4), "IPv4 checksum should be recomputed");
+}
+
+#[test]
+fn test_override_tcp_window_recomputes_checksum() {
+    let mut frame = build(L4Protocol::Tcp);
+    
+    apply_overrides(&mut frame, &table(&[("tcp.window", 1024)])).unwrap();
+    
+    let eth = EthernetPacket::new(&frame).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(tcp.get_window(), 1024);
+    let expected = pnet::packet::tcp::ipv4_checksum(&tcp, &ipv4.get_source(), &ipv4.get_destination());
+    assert_eq!(tcp.get_checksum(), expected, "TCP checksum should be recomputed");
+}
+
+#[test]
+fn test_override_checksum_is_kept() {
+    let mut frame = build(L4Protocol::Udp);
+    
+    apply_overrides(&mut frame, &table(&[("ipv4.ttl", 10), ("ipv4.checksum", 0xbeef)])).unwrap();
+    
+    let eth = EthernetPacket::new(&frame).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_checksum(), 0xbeef);
+}
+
+// ==================== Error Tests ====================
+
+#[test]
+fn test_override_errors() {
+    let mut frame = build(L4Protocol::Udp);
+    
+    assert!(matches!(
+        apply_overrides(&mut frame, &table(&[("ipv4.colour", 1)])),
+        Err(CrafterError::UnknownOverrideField { .. })
+    ));
+    assert!(matches!(
+        apply_overrides(&mut frame, &table(&[("ipv4.ttl", 256)])),
+        Err(CrafterError::OverrideOutOfRange { bits: 8, .. })
+    ));
+    assert!(matches!(
+        apply_overrides(&mut frame, &table(&[("tcp.window", 0)])),
+        Err(CrafterError::OverrideTargetMissing { .. })
+    ));
+}
+
+// ==================== Loading Tests ====================
+
+#[test]
+fn test_load_overrides_from_json() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("overrides.json");
+    std::fs::write(&path, r#"{"ipv4.ttl": 10, "udp.dst_port": 53}"#).unwrap();
+    
+    let overrides = load_overrides(&path).unwrap();
+    
+    assert_eq!(overrides, table(&[("ipv4.ttl", 10), ("udp.dst_port", 53)]));
+}
+
+// ==================== Endian Swap Tests ====================
+
+#[test]
+fn test_endian_swap_tcp_window() {
+    let original = build(L4Protocol::Tcp);
+    let mut frame = original.clone();
+    
+    swap_field_endianness(&mut frame, "tcp.window").unwrap();
+    
+    let tcp_start = 14 + 20;
+    let window = tcp_start + 14..tcp_start + 16;
+    assert_eq!(frame[window.clone()], [original[window.end - 1], original[window.start]], "Window bytes should be reversed");
+    assert_eq!(frame[tcp_start..window.start], original[tcp_start..window.start], "Header before the window should be intact");
+    assert_eq!(frame[window.end + 2..], original[window.end + 2..], "Urgent pointer and payload should be intact");
+    
+    let eth = EthernetPacket::new(&frame).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(tcp.get_window(), u16::from_be_bytes([original[window.start], original[window.end - 1]]).swap_bytes());
+    assert_eq!(
+        tcp.get_checksum(),
+        pnet::packet::tcp::ipv4_checksum(&tcp, &ipv4.get_source(), &ipv4.get_destination()),
+        "TCP checksum should be recomputed"
+    );
+}
+
+#[test]
+fn test_endian_swap_rejects_unknown_or_missing_field() {
+    let mut frame = build(L4Protocol::Udp);
+    
+    assert!(matches!(
+        swap_field_endianness(&mut frame, "ipv4.ttl"),
+        Err(CrafterError::UnknownOverrideField { .. })
+    ));
+    assert!(matches!(
+        swap_field_endianness(&mut frame, "tcp.window"),
+        Err(CrafterError::OverrideTargetMissing { .. })
+    ));
+}
+
+#[test]
+fn test_refix_ip_checksum_leaves_l4_stale() {
+    let mut frame = build(L4Protocol::Tcp);
+    apply_overrides_raw(&mut frame, &table(&[("ipv4.ttl", 5), ("tcp.window", 1024)])).unwrap();
+    
+    let ipv4 = Ipv4Packet::new(&frame[14..]).unwrap();
+    assert_ne!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4), "raw overrides should not repair the IPv4 checksum");
+    
+    refix_ipv4_checksum(&mut frame);
+    
+    let eth = EthernetPacket::new(&frame).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_ttl(), 5);
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4), "IPv4 checksum should validate");
+    
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(tcp.get_window(), 1024);
+    let expected = pnet::packet::tcp::ipv4_checksum(&tcp, &ipv4.get_source(), &ipv4.get_destination());
+    assert_ne!(tcp.get_checksum(), expected, "TCP checksum should be stale after the window changed");
+}
+
+// ==================== Encapsulation Tests ====================
+
+#[test]
+fn test_overrides_swap_and_refix_with_pppoe() {
+    let mut frame = build_from(&["packet_crafter", "--pppoe_session=4660", "--l4_protocol=tcp"]);
+    let start = 14 + PPPOE_HEADER_LENGTH;
+    
+    apply_overrides(&mut frame, &table(&[("ipv4.ttl", 10), ("tcp.window", 0x0400)])).unwrap();
+    swap_field_endianness(&mut frame, "tcp.window").unwrap();
+    
+    let ipv4 = Ipv4Packet::new(&frame[start..]).unwrap();
+    assert_eq!(ipv4.get_ttl(), 10);
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4));
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(tcp.get_window(), 0x0004);
+    let expected = pnet::packet::tcp::ipv4_checksum(&tcp, &ipv4.get_source(), &ipv4.get_destination());
+    assert_eq!(tcp.get_checksum(), expected);
+    
+    apply_overrides(&mut frame, &table(&[("ipv4.checksum", 0)])).unwrap();
+    refix_ipv4_checksum(&mut frame);
+    let ipv4 = Ipv4Packet::new(&frame[start..]).unwrap();
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4), "refix should find the IPv4 header after PPPoE");
+}
+
+#[test]
+fn test_overrides_swap_and_refix_with_vlan_tag() {
+    let mut frame = build_from(&["packet_crafter", "--vlan_id=100", "--l4_protocol=tcp"]);
+    let start = 14 + VLAN_TAG_LENGTH;
+    
+    apply_overrides(&mut frame, &table(&[("ipv4.ttl", 10), ("tcp.window", 0x0400)])).unwrap();
+    swap_field_endianness(&mut frame, "tcp.window").unwrap();
+    
+    let ipv4 = Ipv4Packet::new(&frame[start..]).unwrap();
+    assert_eq!(ipv4.get_ttl(), 10);
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4));
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(tcp.get_window(), 0x0004);
+    let expected = pnet::packet::tcp::ipv4_checksum(&tcp, &ipv4.get_source(), &ipv4.get_destination());
+    assert_eq!(tcp.get_checksum(), expected);
+    
+    apply_overrides(&mut frame, &table(&[("ipv4.checksum", 0)])).unwrap();
+    refix_ipv4_checksum(&mut frame);
+    let ipv4 = Ipv4Packet::new(&frame[start..]).unwrap();
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4), "refix should find the IPv4 header after the tag");
+}
@@ -2,12 +2,16 @@
 //!
 //! Verifies that PacketBuilder correctly constructs Ethernet/IPv4/TCP/UDP packets.
 
-use packet_crafter::{Args, L4Protocol, packet::PacketBuilder, parsing};
+use packet_crafter::{Args, ChecksumAlgo, HexBytes, Ipv6ExtHeader, Parser, TcpOptionOrder, UdpChecksumMode, Conversation, CrafterError, L4Protocol, packet::{PacketBuilder, BuilderConfig, assert_matches_hex, basic_sanity, dscp_to_pcp, layer_ranges, ipv6_solicited_node_mac, ipv4_pseudo_header, would_warn_zero_checksum, enforce_bounds, internet_checksum}, parsing};
 use pnet::packet::Packet;
+use pnet::packet::arp::{ArpOperations, ArpPacket};
 use pnet::packet::ethernet::{EthernetPacket, EtherTypes};
-use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv4::{Ipv4Flags, Ipv4Packet};
+use pnet::packet::ipv6::Ipv6Packet;
 use pnet::packet::tcp::TcpPacket;
 use pnet::packet::udp::UdpPacket;
+use pnet::util::MacAddr;
+use std::sync::{Arc, Mutex};
 
 fn create_test_args(protocol: L4Protocol) -> Args {
     Args {
@@ -21,6 +25,7 @@ fn create_test_args(protocol: L4Protocol) -> Args {
         debug_file: None,
         debug_format: None,
         ip_bitfield: 0,
+        ..Args::default()
     }
 }
 
@@ -31,7 +36,7 @@ fn test_udp_packet_construction() {
     let args = create_test_args(L4Protocol::Udp);
     let mut builder = PacketBuilder::from(&args);
     let payload = b"test payload";
-    let packet = builder.build_packet(payload);
+    let packet = builder.build_packet(payload).unwrap();
     
     // Verify Ethernet header
     let eth = EthernetPacket::new(packet).unwrap();
@@ -56,7 +61,7 @@ fn test_udp_packet_construction() {
 fn test_udp_checksums() {
     let args = create_test_args(L4Protocol::Udp);
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"test");
+    let packet = builder.build_packet(b"test").unwrap();
     
     let eth = EthernetPacket::new(packet).unwrap();
     let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
@@ -66,6 +71,32 @@ fn test_udp_checksums() {
     assert_ne!(udp.get_checksum(), 0, "UDP checksum should be set");
 }
 
+#[test]
+fn test_pseudo_header_reproduces_udp_checksum() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"odd").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let mut segment = ipv4.payload().to_vec();
+    let stored = u16::from_be_bytes([segment[6], segment[7]]);
+    segment[6] = 0;
+    segment[7] = 0;
+    
+    let mut data = ipv4_pseudo_header(args.src_ip, args.dst_ip, 17, segment.len() as u16).to_vec();
+    data.extend_from_slice(&segment);
+    if data.len() % 2 == 1 {
+        data.push(0);
+    }
+    let mut sum: u32 = data.chunks(2).map(|w| u16::from_be_bytes([w[0], w[1]]) as u32).sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    
+    assert_eq!(!(sum as u16), stored, "Manual pseudo-header sum should match build_udp");
+}
+
 // ==================== TCP Packet Tests ====================
 
 #[test]
@@ -73,7 +104,7 @@ fn test_tcp_packet_construction() {
     let args = create_test_args(L4Protocol::Tcp);
     let mut builder = PacketBuilder::from(&args);
     let payload = b"test payload";
-    let packet = builder.build_packet(payload);
+    let packet = builder.build_packet(payload).unwrap();
     
     // Verify Ethernet header
     let eth = EthernetPacket::new(packet).unwrap();
@@ -94,7 +125,7 @@ fn test_tcp_packet_construction() {
 fn test_tcp_checksums() {
     let args = create_test_args(L4Protocol::Tcp);
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"test");
+    let packet = builder.build_packet(b"test").unwrap();
     
     let eth = EthernetPacket::new(packet).unwrap();
     let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
@@ -104,6 +135,329 @@ fn test_tcp_checksums() {
     assert_ne!(tcp.get_checksum(), 0, "TCP checksum should be set");
 }
 
+#[test]
+fn test_tcp_rst_packet() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.rst = true;
+    args.tcp_seq = 0x12345678;
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"ignored payload").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    
+    assert_eq!(tcp.get_flags(), 0x04, "Only the RST flag should be set");
+    assert_eq!(tcp.get_window(), 0);
+    assert_eq!(tcp.get_sequence(), 0x12345678);
+    assert!(tcp.payload().is_empty(), "RST should carry no payload");
+}
+
+#[test]
+fn test_tcp_reserved_bits() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.tcp_reserved = 0b1011;
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let offset_byte = ipv4.payload()[12];
+    assert_eq!(offset_byte >> 4, 5, "Data offset should stay 5 words");
+    assert_eq!(offset_byte & 0x0f, 0b1011, "Reserved bits and NS should be set");
+    
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(tcp.get_flags(), 0x02, "Flags should be unaffected");
+    let expected = pnet::packet::tcp::ipv4_checksum(&tcp, &ipv4.get_source(), &ipv4.get_destination());
+    assert_eq!(tcp.get_checksum(), expected, "Checksum should cover the reserved bits");
+}
+
+#[test]
+fn test_tcp_checksum_override_is_not_recomputed() {
+    use packet_crafter::decode::{decode_packet, L4Info};
+    
+    let mut args = create_test_args(L4Protocol::Tcp);
+    let computed = PacketBuilder::from(&args).build_packet(b"test").unwrap().to_vec();
+    args.tcp_checksum_override = Some(0xbeef);
+    
+    let mut builder = PacketBuilder::from(&args);
+    for _ in 0..2 {
+        let packet = builder.build_packet(b"test").unwrap();
+        let Some(L4Info::Tcp { checksum, .. }) = decode_packet(packet).unwrap().l4 else {
+            panic!("expected a TCP header");
+        };
+        assert_eq!(checksum, 0xbeef, "Decoded checksum should be the override");
+        
+        let checksum_at = 14 + 20 + 16;
+        assert_eq!(packet[..checksum_at], computed[..checksum_at]);
+        assert_eq!(packet[checksum_at + 2..], computed[checksum_at + 2..], "Only the checksum should differ");
+    }
+}
+
+#[test]
+fn test_checksum_target_adjusts_payload() {
+    for (protocol, payload) in [(L4Protocol::Udp, &b"test"[..]), (L4Protocol::Tcp, &b"odd"[..])] {
+        let mut args = create_test_args(protocol.clone());
+        args.checksum_target = Some(0x1234);
+        
+        let mut builder = PacketBuilder::from(&args);
+        let packet = builder.build_packet(payload).unwrap();
+        
+        let eth = EthernetPacket::new(packet).unwrap();
+        let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+        let (checksum, data_len) = match protocol {
+            L4Protocol::Tcp => {
+                let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+                assert_eq!(tcp.get_checksum(), pnet::packet::tcp::ipv4_checksum(&tcp, &args.src_ip, &args.dst_ip));
+                (tcp.get_checksum(), tcp.payload().len())
+            }
+            _ => {
+                let udp = UdpPacket::new(ipv4.payload()).unwrap();
+                assert_eq!(udp.get_checksum(), pnet::packet::udp::ipv4_checksum(&udp, &args.src_ip, &args.dst_ip));
+                (udp.get_checksum(), udp.payload().len())
+            }
+        };
+        assert_eq!(checksum, 0x1234, "{:?} checksum should hit the target", protocol);
+        assert!(data_len <= payload.len() + 2, "Payload should grow by at most two bytes");
+        assert_eq!(&packet[packet.len() - data_len..][..payload.len()], payload, "Original payload should be kept");
+    }
+}
+
+#[test]
+fn test_ipv4_vihl_override() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.ipv4_vihl = Some(0x46);
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    assert_eq!(packet[14], 0x46, "First IPv4 byte should be the override");
+    assert_eq!(internet_checksum(&packet[14..34]), 0, "Checksum should cover the 20 bytes built");
+    assert_eq!(packet.len(), 14 + 20 + 8 + 4, "Layout should be unaffected by the IHL lie");
+}
+
+#[test]
+fn test_arp_gratuitous_reply() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.arp_gratuitous = true;
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"ignored").unwrap();
+    assert_eq!(packet.len(), 14 + 28);
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_ethertype(), EtherTypes::Arp);
+    assert_eq!(eth.get_destination(), MacAddr::broadcast());
+    
+    let arp = ArpPacket::new(eth.payload()).unwrap();
+    assert_eq!(arp.get_operation(), ArpOperations::Reply);
+    assert_eq!(arp.get_sender_proto_addr(), args.src_ip);
+    assert_eq!(arp.get_target_proto_addr(), args.src_ip);
+    assert_eq!(arp.get_sender_hw_addr().octets(), args.src_mac);
+    assert_eq!(arp.get_target_hw_addr(), MacAddr::broadcast());
+}
+
+#[test]
+fn test_mark_ce_sets_ecn() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.mark_ce = true;
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_ecn(), 3, "ECN should be Congestion Experienced");
+    assert_eq!(ipv4.get_dscp(), 0);
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4), "IPv4 checksum should validate");
+}
+
+#[test]
+fn test_tcp_mss_from_mtu() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.tcp_mss_from_mtu = Some(1500);
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    
+    assert_eq!(tcp.get_data_offset(), 6, "MSS option should add one 32-bit word");
+    assert_eq!(tcp.get_options_raw(), &[2, 4, 0x05, 0xb4], "MSS should be 1460");
+    assert_eq!(tcp.payload(), b"test");
+    
+    let expected = pnet::packet::tcp::ipv4_checksum(&tcp, &args.src_ip, &args.dst_ip);
+    assert_eq!(tcp.get_checksum(), expected, "TCP checksum should cover the options");
+}
+
+/// Returns the option kinds in a TCP options region, in order.
+fn tcp_option_kinds(options: &[u8]) -> Vec<u8> {
+    let mut kinds = Vec::new();
+    let mut i = 0;
+    while i < options.len() {
+        let kind = options[i];
+        kinds.push(kind);
+        i += if kind <= 1 { 1 } else { options[i + 1] as usize };
+    }
+    kinds
+}
+
+#[test]
+fn test_linux_option_order() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.option_order = Some(TcpOptionOrder::Linux);
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    
+    assert_eq!(tcp.get_data_offset(), 10, "Linux SYN options should take 20 bytes");
+    assert_eq!(tcp_option_kinds(tcp.get_options_raw()), vec![2, 4, 8, 1, 3]);
+    assert_eq!(&tcp.get_options_raw()[..4], &[2, 4, 0x05, 0xb4]);
+    assert_eq!(&tcp.get_options_raw()[17..], &[3, 3, 7]);
+}
+
+#[test]
+fn test_option_order_follows_os_profile() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.os_profile = Some("windows".to_string());
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    
+    assert_eq!(tcp_option_kinds(tcp.get_options_raw()), vec![2, 1, 3, 1, 1, 4]);
+}
+
+// ==================== Conversation Tests ====================
+
+#[test]
+fn test_with_dest_port_leaves_original_unchanged() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut original = PacketBuilder::from(&args);
+    let mut variant = original.with_dest_port(81);
+    
+    let packet = variant.build_packet(b"test").unwrap();
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(UdpPacket::new(ipv4.payload()).unwrap().get_destination(), 81);
+    
+    let packet = original.build_packet(b"test").unwrap();
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(UdpPacket::new(ipv4.payload()).unwrap().get_destination(), args.dest_port);
+}
+
+#[test]
+fn test_on_built_callback_receives_packets() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    
+    let mut builder = PacketBuilder::from(&create_test_args(L4Protocol::Udp));
+    builder.set_on_built(Box::new(move |packet| sink.lock().unwrap().push(packet.to_vec())));
+    let first = builder.build_packet(b"one").unwrap().to_vec();
+    let second = builder.build_packet(b"second").unwrap().to_vec();
+    
+    let seen = seen.lock().unwrap();
+    assert_eq!(*seen, vec![first, second], "Callback should see exactly the returned bytes");
+    assert_eq!(seen[0].len(), 14 + 20 + 8 + 3);
+    assert_eq!(seen[1].len(), 14 + 20 + 8 + 6);
+}
+
+#[test]
+fn test_on_built_callback_not_shared_with_clones() {
+    fn assert_send<T: Send>(_: &T) {}
+    
+    let seen = Arc::new(Mutex::new(0));
+    let sink = Arc::clone(&seen);
+    
+    let mut builder = PacketBuilder::from(&create_test_args(L4Protocol::Udp));
+    builder.set_on_built(Box::new(move |_| *sink.lock().unwrap() += 1));
+    assert_send(&builder);
+    
+    let mut clone = builder.clone();
+    clone.build_packet(b"clone").unwrap();
+    assert_eq!(*seen.lock().unwrap(), 0, "Clones should start without a callback");
+    builder.build_packet(b"original").unwrap();
+    assert_eq!(*seen.lock().unwrap(), 1);
+}
+
+#[test]
+fn test_syn_synack_ack_conversation() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.tcp_seq = 1000;
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packets = builder.build_conversation(Conversation::SynSynackAck).unwrap();
+    assert_eq!(packets.len(), 3);
+    
+    let segments: Vec<(String, String, u8, u32, u32)> = packets
+        .iter()
+        .map(|packet| {
+            let eth = EthernetPacket::new(packet).unwrap();
+            let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+            let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+            let expected = pnet::packet::tcp::ipv4_checksum(&tcp, &ipv4.get_source(), &ipv4.get_destination());
+            assert_eq!(tcp.get_checksum(), expected);
+            (
+                ipv4.get_source().to_string(),
+                ipv4.get_destination().to_string(),
+                tcp.get_flags(),
+                tcp.get_sequence(),
+                tcp.get_acknowledgement(),
+            )
+        })
+        .collect();
+    
+    let (syn_src, syn_dst, syn_flags, syn_seq, _) = &segments[0];
+    let (sa_src, sa_dst, sa_flags, sa_seq, sa_ack) = &segments[1];
+    let (ack_src, ack_dst, ack_flags, ack_seq, ack_ack) = &segments[2];
+    
+    assert_eq!(*syn_flags, 0x02, "First packet should be SYN");
+    assert_eq!(*sa_flags, 0x12, "Second packet should be SYN-ACK");
+    assert_eq!(*ack_flags, 0x10, "Third packet should be ACK");
+    
+    assert_eq!((sa_src, sa_dst), (syn_dst, syn_src), "SYN-ACK should be reversed");
+    assert_eq!((ack_src, ack_dst), (syn_src, syn_dst));
+    
+    assert_eq!(*syn_seq, 1000);
+    assert_eq!(*sa_ack, syn_seq + 1);
+    assert_eq!(*ack_seq, syn_seq + 1);
+    assert_eq!(*ack_ack, sa_seq + 1);
+}
+
+#[test]
+fn test_conversation_respects_truncate_at() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.truncate_at = Some(20);
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packets = builder.build_conversation(Conversation::SynSynackAck).unwrap();
+    assert_eq!(packets.iter().map(Vec::len).collect::<Vec<_>>(), [20, 20, 20]);
+}
+
+#[test]
+fn test_conversation_fires_on_built_callback() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    
+    let mut builder = PacketBuilder::from(&create_test_args(L4Protocol::Tcp));
+    builder.set_on_built(Box::new(move |packet| sink.lock().unwrap().push(packet.to_vec())));
+    let packets = builder.build_conversation(Conversation::SynSynackAck).unwrap();
+    
+    assert_eq!(*seen.lock().unwrap(), packets, "Callback should see every segment of the conversation");
+}
+
 // ==================== IP Bitfield Tests ====================
 
 #[test]
@@ -112,7 +466,7 @@ fn test_ip_bitfield_flags() {
     args.ip_bitfield = 0x40; // Don't Fragment flag
     
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"test");
+    let packet = builder.build_packet(b"test").unwrap();
     
     let eth = EthernetPacket::new(packet).unwrap();
     let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
@@ -121,6 +475,182 @@ fn test_ip_bitfield_flags() {
     assert_eq!(ipv4.get_flags(), 2);
 }
 
+// ==================== IP Options Tests ====================
+
+#[test]
+fn test_router_alert_option() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.router_alert = true;
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    
+    assert_eq!(ipv4.get_header_length(), 6, "Router Alert should add one 32-bit word");
+    assert_eq!(ipv4.get_options_raw(), &[148, 4, 0, 0]);
+    assert_eq!(ipv4.get_total_length() as usize, 24 + 8 + 4);
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4));
+    
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(udp.get_destination(), args.dest_port);
+    assert_eq!(udp.payload(), b"test");
+}
+
+#[test]
+fn test_ip_timestamp_prespecified_addresses() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    let routers: Vec<std::net::Ipv4Addr> = vec!["10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap()];
+    args.ip_timestamp_addrs = routers.clone();
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    
+    assert_eq!(ipv4.get_header_length(), 10, "4 option bytes plus 2 x 8 should add five words");
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4));
+    
+    let option = ipv4.get_options_raw();
+    assert_eq!(&option[..4], &[68, 20, 5, 3], "Type, length, pointer and flag 3");
+    for (i, router) in routers.iter().enumerate() {
+        let entry = &option[4 + 8 * i..12 + 8 * i];
+        assert_eq!(&entry[..4], &router.octets());
+        assert_eq!(&entry[4..], &[0; 4], "Timestamp slot should be zero");
+    }
+    
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(udp.payload(), b"test");
+}
+
+// ==================== Auto DF Tests ====================
+
+#[test]
+fn test_auto_df_sets_dont_fragment_near_mtu() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.auto_df = true;
+    args.mtu = 1500;
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(&[0u8; 1400]).unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_flags() & Ipv4Flags::DontFragment, Ipv4Flags::DontFragment);
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4));
+}
+
+#[test]
+fn test_auto_df_leaves_small_packet_fragmentable() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.auto_df = true;
+    args.mtu = 1500;
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"small").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_flags() & Ipv4Flags::DontFragment, 0);
+}
+
+#[test]
+fn test_pmtu_probe_icmp_echo_with_df() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.pmtu_probe = Some(1472);
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(&args.payload()).unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_flags() & Ipv4Flags::DontFragment, Ipv4Flags::DontFragment);
+    assert_eq!(ipv4.get_next_level_protocol(), pnet::packet::ip::IpNextHeaderProtocols::Icmp);
+    assert_eq!(ipv4.get_total_length(), 1500, "A 1472-byte probe should fill a 1500-byte MTU");
+    
+    let icmp = pnet::packet::icmp::IcmpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(icmp.get_icmp_type(), pnet::packet::icmp::IcmpTypes::EchoRequest);
+    assert_eq!(icmp.get_checksum(), pnet::packet::icmp::checksum(&icmp));
+    assert_eq!(ipv4.payload().len() - 8, 1472, "ICMP payload should be the requested size");
+}
+
+#[test]
+fn test_icmp_burst_id_and_incrementing_seq() {
+    let mut args = create_test_args(L4Protocol::Icmp);
+    args.icmp_id = Some(0x1234);
+    args.icmp_seq = 500;
+    args.count = 3;
+    let mut builder = PacketBuilder::from(&args);
+    
+    let packets = packet_crafter::run::build_burst(&mut builder, &args).unwrap();
+    
+    for (offset, packet) in (0u16..).zip(&packets) {
+        let eth = EthernetPacket::new(packet).unwrap();
+        let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+        assert_eq!(ipv4.get_next_level_protocol(), pnet::packet::ip::IpNextHeaderProtocols::Icmp);
+        
+        let echo = pnet::packet::icmp::echo_request::EchoRequestPacket::new(ipv4.payload()).unwrap();
+        assert_eq!(echo.get_identifier(), 0x1234);
+        assert_eq!(echo.get_sequence_number(), 500 + offset);
+        let icmp = pnet::packet::icmp::IcmpPacket::new(ipv4.payload()).unwrap();
+        assert_eq!(icmp.get_checksum(), pnet::packet::icmp::checksum(&icmp));
+    }
+}
+
+#[test]
+fn test_icmp_id_defaults_to_src_port() {
+    let args = create_test_args(L4Protocol::Icmp);
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"ping").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let echo = pnet::packet::icmp::echo_request::EchoRequestPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(echo.get_identifier(), args.src_port);
+    assert_eq!(echo.get_sequence_number(), 1);
+}
+
+#[test]
+fn test_ethertype_override_keeps_ipv4_payload() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.ethertype_override = Some(parsing::parse_ethertype("0x86dd").unwrap());
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"mismatch").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_ethertype(), EtherTypes::Ipv6, "EtherType should be overridden");
+    
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_version(), 4, "Payload should still be IPv4");
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4));
+    assert_eq!(ipv4.get_destination(), args.dst_ip);
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(udp.payload(), b"mismatch");
+}
+
+// ==================== Trailer Tests ====================
+
+#[test]
+fn test_udp_trailer_counted_in_ip_length_only() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.trailer_hex = Some(HexBytes(vec![0xde, 0xad, 0xbe, 0xef]));
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    assert_eq!(packet.len(), 14 + 20 + 8 + 4 + 4);
+    assert_eq!(&packet[packet.len() - 4..], &[0xde, 0xad, 0xbe, 0xef]);
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_total_length() as usize, 20 + 8 + 4 + 4, "IP length should include the trailer");
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4));
+    
+    let segment = &ipv4.payload()[..8 + 4];
+    let udp = UdpPacket::new(segment).unwrap();
+    assert_eq!(udp.get_length() as usize, 8 + 4, "UDP length should exclude the trailer");
+    assert_eq!(udp.payload(), b"test");
+    assert_eq!(udp.get_checksum(), pnet::packet::udp::ipv4_checksum(&udp, &args.src_ip, &args.dst_ip));
+}
+
 // ==================== Edge Cases ====================
 
 #[test]
@@ -133,7 +663,7 @@ fn test_custom_addresses_and_ports() {
     args.dst_mac = [0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa];
     
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"test");
+    let packet = builder.build_packet(b"test").unwrap();
     
     let eth = EthernetPacket::new(packet).unwrap();
     assert_eq!(eth.get_source().octets(), [0x00, 0x11, 0x22, 0x33, 0x44, 0x55]);
@@ -151,7 +681,7 @@ fn test_custom_addresses_and_ports() {
 fn test_empty_payload() {
     let args = create_test_args(L4Protocol::Udp);
     let mut builder = PacketBuilder::from(&args);
-    let packet = builder.build_packet(b"");
+    let packet = builder.build_packet(b"").unwrap();
     
     assert!(!packet.is_empty(), "Packet with empty payload should still have headers");
     
@@ -160,3 +690,628 @@ fn test_empty_payload() {
     let udp = UdpPacket::new(ipv4.payload()).unwrap();
     assert_eq!(udp.payload().len(), 0);
 }
+
+// ==================== IPv6 Neighbor Discovery Tests ====================
+
+#[test]
+fn test_solicited_node_mac_mapping() {
+    let target = "2001:db8::21a:2bff:fe3c:4d5e".parse().unwrap();
+    assert_eq!(ipv6_solicited_node_mac(target), [0x33, 0x33, 0xff, 0x3c, 0x4d, 0x5e]);
+}
+
+#[test]
+fn test_neighbor_solicit_auto_l2() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.ns_target = Some("2001:db8::21a:2bff:fe3c:4d5e".parse().unwrap());
+    args.auto_l2 = true;
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_ethertype(), EtherTypes::Ipv6);
+    assert_eq!(eth.get_destination().octets(), [0x33, 0x33, 0xff, 0x3c, 0x4d, 0x5e]);
+    
+    let ipv6 = Ipv6Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv6.get_destination().to_string(), "ff02::1:ff3c:4d5e");
+    assert_eq!(ipv6.get_hop_limit(), 255);
+    assert_eq!(ipv6.payload()[0], 135, "ICMPv6 type should be Neighbor Solicitation");
+    
+    let icmp = pnet::packet::icmpv6::Icmpv6Packet::new(ipv6.payload()).unwrap();
+    let expected = pnet::packet::icmpv6::checksum(&icmp, &ipv6.get_source(), &ipv6.get_destination());
+    assert_eq!(icmp.get_checksum(), expected, "ICMPv6 checksum should validate");
+}
+
+#[test]
+fn test_neighbor_solicit_flow_label() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.ns_target = Some("2001:db8::1".parse().unwrap());
+    args.flow_label = 0xabcde;
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv6 = Ipv6Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv6.get_flow_label(), 0xabcde);
+}
+
+#[test]
+fn test_neighbor_solicit_fragment_ext_header_chain() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.ns_target = Some("2001:db8::1".parse().unwrap());
+    args.ipv6_ext = Some(Ipv6ExtHeader::Fragment);
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"").unwrap().to_vec();
+    assert_eq!(packet.len(), builder.min_frame_size());
+    
+    let eth = EthernetPacket::new(&packet).unwrap();
+    let ipv6 = Ipv6Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv6.get_next_header().0, 44, "IPv6 Next Header should name the Fragment header");
+    assert_eq!(ipv6.get_payload_length() as usize, 8 + 32);
+    
+    let fragment = ipv6.payload();
+    assert_eq!(fragment[0], 58, "Fragment header should chain to ICMPv6");
+    assert_eq!(&fragment[2..4], &[0, 0], "atomic fragment: offset 0, More Fragments clear");
+    
+    let icmp = pnet::packet::icmpv6::Icmpv6Packet::new(&fragment[8..]).unwrap();
+    assert_eq!(icmp.get_icmpv6_type().0, 135, "ICMPv6 should still parse after the extension header");
+    let expected = pnet::packet::icmpv6::checksum(&icmp, &ipv6.get_source(), &ipv6.get_destination());
+    assert_eq!(icmp.get_checksum(), expected, "pseudo-header should use the final upper-layer protocol");
+}
+
+#[test]
+fn test_tcp_over_ipv6_fragment_ext_header_chain() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.ipv6_ext = Some(Ipv6ExtHeader::Fragment);
+    args.dst_ipv6 = "2001:db8::2".parse().unwrap();
+    
+    let mut builder = PacketBuilder::from(&args);
+    let min_frame_size = builder.min_frame_size();
+    let packet = builder.build_packet(b"over v6").unwrap();
+    assert_eq!(packet.len(), min_frame_size + b"over v6".len());
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_ethertype(), EtherTypes::Ipv6);
+    let ipv6 = Ipv6Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv6.get_next_header().0, 44, "IPv6 Next Header should name the Fragment header");
+    assert_eq!(ipv6.get_destination(), args.dst_ipv6);
+    
+    let fragment = ipv6.payload();
+    assert_eq!(fragment[0], 6, "Fragment header should chain to TCP");
+    
+    let tcp = TcpPacket::new(&fragment[8..]).unwrap();
+    assert_eq!(tcp.get_source(), args.src_port);
+    assert_eq!(tcp.get_destination(), args.dest_port);
+    assert_eq!(tcp.payload(), b"over v6", "TCP should still parse after the extension header");
+    let expected = pnet::packet::tcp::ipv6_checksum(&tcp, &ipv6.get_source(), &ipv6.get_destination());
+    assert_eq!(tcp.get_checksum(), expected, "pseudo-header should use the final upper-layer protocol");
+}
+
+#[test]
+fn test_flow_label_must_fit_20_bits() {
+    assert!(Args::try_parse_from(["packet_crafter", "--flow_label=0xFFFFF"]).is_ok());
+    assert!(Args::try_parse_from(["packet_crafter", "--flow_label=0x100000"]).is_err());
+}
+
+#[test]
+fn test_pppoe_session_wraps_ipv4() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.pppoe_session = Some(0x1234);
+    
+    let mut builder = PacketBuilder::from(&args);
+    let min_frame_size = builder.min_frame_size();
+    let packet = builder.build_packet(b"broadband").unwrap();
+    assert_eq!(packet.len(), min_frame_size + b"broadband".len());
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_ethertype(), EtherTypes::PppoeSession);
+    let pppoe = eth.payload();
+    assert_eq!(pppoe[0], 0x11, "PPPoE version 1, type 1");
+    assert_eq!(pppoe[1], 0, "session data code");
+    assert_eq!(u16::from_be_bytes([pppoe[2], pppoe[3]]), 0x1234, "session ID");
+    assert_eq!(u16::from_be_bytes([pppoe[4], pppoe[5]]) as usize, pppoe.len() - 6, "PPPoE length");
+    assert_eq!(&pppoe[6..8], &[0x00, 0x21], "PPP protocol should be IPv4");
+    
+    let ipv4 = Ipv4Packet::new(&pppoe[8..]).unwrap();
+    assert_eq!(ipv4.get_version(), 4);
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4));
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(tcp.get_checksum(), pnet::packet::tcp::ipv4_checksum(&tcp, &args.src_ip, &args.dst_ip));
+    assert_eq!(tcp.payload(), b"broadband");
+}
+
+#[test]
+fn test_map_dscp_to_pcp_in_vlan_tag() {
+    assert_eq!(dscp_to_pcp(46), 5, "EF should map to PCP 5");
+    
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.vlan_id = Some(100);
+    args.dscp = Some(46);
+    args.map_dscp_to_pcp = true;
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"voice").unwrap();
+    assert_eq!(packet.len(), 14 + 4 + 20 + 8 + 5);
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_ethertype(), EtherTypes::Vlan);
+    let tci = u16::from_be_bytes([packet[14], packet[15]]);
+    assert_eq!(tci >> 13, 5, "PCP should come from the DSCP class");
+    assert_eq!(tci & 0x0fff, 100, "VLAN ID");
+    assert_eq!(&packet[16..18], &[0x08, 0x00], "Inner EtherType should be IPv4");
+    
+    let ipv4 = Ipv4Packet::new(&packet[18..]).unwrap();
+    assert_eq!(ipv4.get_dscp(), 46);
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4));
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(udp.get_checksum(), pnet::packet::udp::ipv4_checksum(&udp, &args.src_ip, &args.dst_ip));
+}
+
+#[test]
+fn test_l2_only_frame_has_no_ip_layer() {
+    let args = create_test_args(L4Protocol::Tcp);
+    let mut builder = PacketBuilder::from(&args);
+    let payload = b"raw layer 2 payload";
+    
+    let frame = builder.l2_only(0x88b5).build(payload);
+    assert_eq!(frame.len(), 14 + payload.len());
+    
+    let eth = EthernetPacket::new(frame).unwrap();
+    assert_eq!(eth.get_ethertype().0, 0x88b5);
+    assert_eq!(eth.get_source().octets(), args.src_mac);
+    assert_eq!(eth.get_destination().octets(), args.dst_mac);
+    assert_eq!(eth.payload(), payload);
+}
+
+#[test]
+fn test_basic_sanity_clean_packets() {
+    for protocol in [L4Protocol::Udp, L4Protocol::Tcp] {
+        let mut builder = PacketBuilder::from(&create_test_args(protocol));
+        let packet = builder.build_packet(b"clean").unwrap();
+        assert!(basic_sanity(packet).is_empty(), "unexpected warnings: {:?}", basic_sanity(packet));
+    }
+}
+
+#[test]
+fn test_basic_sanity_reports_broken_packet() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.src_mac = [0x01, 0x00, 0x5e, 0x00, 0x00, 0x01];
+    let mut packet = PacketBuilder::from(&args).build_packet(b"broken").unwrap().to_vec();
+    
+    // IPv4 total length +4 and UDP length -1, checksums left stale
+    let total_length = u16::from_be_bytes([packet[16], packet[17]]) + 4;
+    packet[16..18].copy_from_slice(&total_length.to_be_bytes());
+    packet[14 + 20 + 5] -= 1;
+    
+    assert_eq!(
+        basic_sanity(&packet),
+        [
+            "source MAC is multicast",
+            "IP total length exceeds frame length",
+            "IPv4 checksum invalid",
+            "UDP length mismatch",
+            "UDP checksum invalid",
+        ]
+    );
+}
+
+#[test]
+fn test_basic_sanity_and_layer_ranges_skip_pppoe() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.pppoe_session = Some(0x1234);
+    let mut packet = PacketBuilder::from(&args).build_packet(b"pppoe").unwrap().to_vec();
+    
+    let ranges = layer_ranges(&packet).unwrap();
+    assert_eq!(ranges.ipv4.start, 14 + packet_crafter::packet::PPPOE_HEADER_LENGTH);
+    assert_eq!(&packet[ranges.payload.clone()], b"pppoe");
+    assert!(basic_sanity(&packet).is_empty());
+    
+    packet[ranges.ipv4.start + 8] = 0; // TTL, without fixing the checksum
+    assert_eq!(basic_sanity(&packet), ["IPv4 checksum invalid", "IPv4 TTL is zero"]);
+}
+
+// ==================== Buffer Allocation Tests ====================
+
+#[test]
+fn test_small_capacity_builds_small_packet() {
+    let mut builder = PacketBuilder::with_capacity(64);
+    let packet = builder.build_packet(b"hi").unwrap();
+    
+    assert_eq!(packet.len(), 14 + 20 + 8 + 2);
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(udp.payload(), b"hi");
+}
+
+#[test]
+fn test_large_capacity_builds_jumbo_packet() {
+    let payload = vec![0x5a; 8000];
+    let mut builder = PacketBuilder::with_capacity(9018);
+    let packet = builder.build_packet(&payload).unwrap();
+    
+    assert_eq!(packet.len(), 14 + 20 + 8 + 8000);
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_total_length() as usize, 20 + 8 + 8000);
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(udp.payload(), payload.as_slice());
+}
+
+#[test]
+fn test_buffer_grows_beyond_capacity() {
+    let mut builder = PacketBuilder::with_capacity(0);
+    let packet = builder.build_packet(&[1u8; 2000]).unwrap();
+    assert_eq!(packet.len(), 14 + 20 + 8 + 2000);
+}
+
+// ==================== UDP Checksum Mode Tests ====================
+
+/// Builds a UDP packet and returns its checksum field.
+fn udp_checksum_field(mode: UdpChecksumMode, payload: &[u8]) -> u16 {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.udp_checksum = mode;
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(payload).unwrap();
+    u16::from_be_bytes([packet[40], packet[41]])
+}
+
+/// Returns a 2-byte payload whose UDP checksum computes to zero.
+fn zero_checksum_payload() -> [u8; 2] {
+    // With the payload word zeroed the checksum is !S; using that value as
+    // the payload word makes the ones' complement sum 0xFFFF, i.e. checksum 0
+    udp_checksum_field(UdpChecksumMode::Compute, &[0, 0]).to_be_bytes()
+}
+
+#[test]
+fn test_udp_checksum_modes_regular_packet() {
+    let real = udp_checksum_field(UdpChecksumMode::Force, b"test");
+    assert_ne!(real, 0);
+    assert_eq!(udp_checksum_field(UdpChecksumMode::Compute, b"test"), real);
+    assert_eq!(udp_checksum_field(UdpChecksumMode::Zero, b"test"), 0);
+}
+
+#[test]
+fn test_udp_checksum_modes_computed_zero() {
+    let payload = zero_checksum_payload();
+    assert_eq!(udp_checksum_field(UdpChecksumMode::Compute, &payload), 0, "Compute should leave 0 as 0");
+    assert_eq!(udp_checksum_field(UdpChecksumMode::Force, &payload), 0xffff, "Force should send 0 as 0xFFFF");
+    assert_eq!(udp_checksum_field(UdpChecksumMode::Zero, &payload), 0);
+}
+
+// ==================== Pseudo-Header Spoofing Tests ====================
+
+#[test]
+fn test_pseudo_src_ip_checksum() {
+    for protocol in [L4Protocol::Udp, L4Protocol::Tcp] {
+        let mut args = create_test_args(protocol.clone());
+        let pseudo_src: std::net::Ipv4Addr = "10.9.8.7".parse().unwrap();
+        args.pseudo_src_ip = Some(pseudo_src);
+        let mut builder = PacketBuilder::from(&args);
+        let packet = builder.build_packet(b"spoof").unwrap();
+        
+        let eth = EthernetPacket::new(packet).unwrap();
+        let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+        assert_eq!(ipv4.get_source(), args.src_ip, "IPv4 header should keep the real source");
+        
+        let (checksum, with_pseudo, with_real) = match protocol {
+            L4Protocol::Udp => {
+                let udp = UdpPacket::new(ipv4.payload()).unwrap();
+                (
+                    udp.get_checksum(),
+                    pnet::packet::udp::ipv4_checksum(&udp, &pseudo_src, &args.dst_ip),
+                    pnet::packet::udp::ipv4_checksum(&udp, &args.src_ip, &args.dst_ip),
+                )
+            }
+            L4Protocol::Tcp => {
+                let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+                (
+                    tcp.get_checksum(),
+                    pnet::packet::tcp::ipv4_checksum(&tcp, &pseudo_src, &args.dst_ip),
+                    pnet::packet::tcp::ipv4_checksum(&tcp, &args.src_ip, &args.dst_ip),
+                )
+            }
+            L4Protocol::Icmp => unreachable!("ICMP checksums have no pseudo-header"),
+        };
+        assert_eq!(checksum, with_pseudo, "Checksum should match the pseudo source");
+        assert_ne!(checksum, with_real, "Checksum should not match the real source");
+    }
+}
+
+// ==================== Checksum Warning Tests ====================
+
+#[test]
+fn test_zero_checksum_warning() {
+    assert!(would_warn_zero_checksum(0x0000, 0x1234), "Zero IPv4 checksum should warn");
+    assert!(would_warn_zero_checksum(0x1234, 0x0000), "Zero L4 checksum should warn");
+    assert!(!would_warn_zero_checksum(0xffff, 0x1234), "Non-zero checksums should not warn");
+}
+
+#[test]
+fn test_built_packet_does_not_warn() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    assert!(!would_warn_zero_checksum(ipv4.get_checksum(), udp.get_checksum()));
+}
+
+#[test]
+fn test_ipv4_length_overflow() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut builder = PacketBuilder::from(&args);
+    let payload = vec![0u8; 70000];
+    
+    let result = builder.build_packet(&payload);
+    
+    assert_eq!(result, Err(CrafterError::Ipv4LengthOverflow { length: 20 + 8 + 70000 }));
+}
+
+#[test]
+fn test_ipv4_max_length_accepted() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut builder = PacketBuilder::from(&args);
+    let payload = vec![0u8; 65535 - 20 - 8];
+    
+    let packet = builder.build_packet(&payload).unwrap();
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_total_length(), 65535);
+}
+
+// ==================== Frame Bounds Tests ====================
+
+#[test]
+fn test_enforce_bounds_pads_short_frame() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut builder = PacketBuilder::from(&args);
+    let mut frame = builder.build_packet(b"hi").unwrap().to_vec();
+    assert_eq!(frame.len(), 44);
+    
+    enforce_bounds(&mut frame, 1500, false).unwrap();
+    
+    assert_eq!(frame.len(), 60);
+    assert!(frame[44..].iter().all(|&b| b == 0), "Padding should be zero bytes");
+    let eth = EthernetPacket::new(&frame).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_total_length(), 30, "IP length should exclude the padding");
+}
+
+#[test]
+fn test_enforce_bounds_rejects_oversized_frame() {
+    let mut frame = vec![0u8; 14 + 1501];
+    assert_eq!(
+        enforce_bounds(&mut frame, 1500, false),
+        Err(CrafterError::FrameTooLarge { length: 1515, max: 1514 })
+    );
+    assert!(enforce_bounds(&mut frame, 1500, true).is_ok(), "Jumbo should allow it");
+    
+    let mut jumbo = vec![0u8; 14 + 9001];
+    assert!(enforce_bounds(&mut jumbo, 1500, true).is_err());
+}
+
+// ==================== Dedup Hash Tests ====================
+
+#[test]
+fn test_build_and_hash_identical_configs_match() {
+    let args = create_test_args(L4Protocol::Udp);
+    
+    let mut first = PacketBuilder::from(&args);
+    let (packet, first_hash) = first.build_and_hash(b"dedup").unwrap();
+    let first_packet = packet.to_vec();
+    
+    let mut second = PacketBuilder::from(&args);
+    let (packet, second_hash) = second.build_and_hash(b"dedup").unwrap();
+    
+    assert_eq!(packet, first_packet.as_slice());
+    assert_eq!(first_hash, second_hash, "Identical packets should hash the same");
+}
+
+#[test]
+fn test_build_and_hash_changes_with_dest_port() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut builder = PacketBuilder::from(&args);
+    let (_, original) = builder.build_and_hash(b"dedup").unwrap();
+    
+    let changed = Args { dest_port: 443, ..create_test_args(L4Protocol::Udp) };
+    let mut builder = PacketBuilder::from(&changed);
+    let (_, other) = builder.build_and_hash(b"dedup").unwrap();
+    
+    assert_ne!(original, other, "A different dest_port should change the hash");
+}
+
+// ==================== Checksum Algorithm Tests ====================
+
+#[test]
+fn test_internet_checksum_rfc1071_example() {
+    assert_eq!(internet_checksum(&[0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7]), 0x220d);
+    assert_eq!(internet_checksum(&[0x00, 0x01, 0xf2]), !0xf201);
+}
+
+#[test]
+fn test_checksum_algorithms_agree() {
+    // Small LCG so the "random" packets are the same on every run
+    let mut state: u32 = 0x1234_5678;
+    let mut next = move || {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        state
+    };
+    
+    for round in 0..32 {
+        let protocol = if round % 2 == 0 { L4Protocol::Udp } else { L4Protocol::Tcp };
+        let mut args = create_test_args(protocol);
+        args.src_ip = next().into();
+        args.dst_ip = next().into();
+        args.src_port = next() as u16;
+        args.dest_port = next() as u16;
+        args.tcp_seq = next();
+        args.router_alert = round % 3 == 0;
+        args.tcp_mss_from_mtu = (round % 4 == 1).then_some(1500);
+        let payload: Vec<u8> = (0..next() % 64).map(|_| next() as u8).collect();
+        
+        args.checksum_algo = ChecksumAlgo::Pnet;
+        let pnet = PacketBuilder::from(&args).build_packet(&payload).unwrap().to_vec();
+        args.checksum_algo = ChecksumAlgo::Manual;
+        let manual = PacketBuilder::from(&args).build_packet(&payload).unwrap().to_vec();
+        
+        assert_eq!(pnet, manual, "Checksums should agree for packet {}", round);
+    }
+}
+
+// ==================== Config Dump Tests ====================
+
+#[test]
+fn test_config_json_round_trip() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.dest_port = 8443;
+    let builder = PacketBuilder::from(&args);
+    
+    let config: BuilderConfig = serde_json::from_str(&builder.config_json()).unwrap();
+    
+    assert_eq!(config.dest_port, 8443);
+    assert_eq!(config.src_mac, "aa:bb:cc:dd:ee:ff");
+    assert_eq!(config.dst_mac, "11:22:33:44:55:66");
+    assert_eq!(config.l4_protocol, "tcp");
+    assert_eq!(config.src_ip, args.src_ip);
+}
+
+// ==================== Layer Range Tests ====================
+
+#[test]
+fn test_layer_ranges_udp() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    let ranges = layer_ranges(packet).unwrap();
+    
+    assert_eq!(ranges.ethernet, 0..14);
+    assert_eq!(ranges.ipv4, 14..34);
+    assert_eq!(ranges.l4, 34..42);
+    assert_eq!(ranges.payload, 42..46);
+    assert_eq!(&packet[ranges.payload], b"test");
+}
+
+#[test]
+fn test_layer_ranges_tcp_with_options() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.router_alert = true;
+    args.tcp_mss_from_mtu = Some(1500);
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    let ranges = layer_ranges(packet).unwrap();
+    
+    assert_eq!(ranges.ipv4, 14..38, "IHL should include the Router Alert option");
+    assert_eq!(ranges.l4, 38..62, "TCP header should include the MSS option");
+    assert_eq!(&packet[ranges.payload], b"test");
+}
+
+#[test]
+fn test_layer_ranges_rejects_truncated_frame() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"test").unwrap();
+    
+    assert!(matches!(layer_ranges(&packet[..30]), Err(CrafterError::MalformedFrame { .. })));
+}
+
+// ==================== Hex Fixture Tests ====================
+
+#[test]
+fn test_assert_matches_hex_matching_fixture() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"fixture").unwrap();
+    
+    let fixture = hex::encode(packet);
+    let (header, rest) = fixture.split_at(28);
+    assert_eq!(assert_matches_hex(packet, &format!("{}\n  {}", header, rest)), Ok(()));
+}
+
+#[test]
+fn test_assert_matches_hex_reports_first_difference() {
+    let args = create_test_args(L4Protocol::Udp);
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"fixture").unwrap().to_vec();
+    
+    let mut expected = packet.clone();
+    expected[22] = 1;
+    expected[40] ^= 0xff;
+    
+    let message = assert_matches_hex(&packet, &hex::encode(&expected)).unwrap_err();
+    assert!(message.starts_with("Frames differ at offset 22 (IPv4 header, byte 8): expected 0x01, got 0x40"), "{}", message);
+}
+
+// ==================== Minimum Frame Size Tests ====================
+
+#[test]
+fn test_min_frame_size_plain() {
+    let udp = PacketBuilder::from(&create_test_args(L4Protocol::Udp));
+    assert_eq!(udp.min_frame_size(), 42);
+    
+    let tcp = PacketBuilder::from(&create_test_args(L4Protocol::Tcp));
+    assert_eq!(tcp.min_frame_size(), 54);
+}
+
+#[test]
+fn test_min_frame_size_matches_empty_build() {
+    let mut args = create_test_args(L4Protocol::Tcp);
+    args.router_alert = true;
+    args.option_order = Some(TcpOptionOrder::Linux);
+    args.trailer_hex = Some(HexBytes(vec![0xde, 0xad]));
+    let mut builder = PacketBuilder::from(&args);
+    
+    let expected = builder.min_frame_size();
+    assert_eq!(builder.build_packet(b"").unwrap().len(), expected);
+    assert!(expected > 54, "Options and trailer should add to the minimum");
+}
+
+// ==================== Snapshot Tests ====================
+
+#[test]
+fn test_build_with_snapshots_grows_by_layer() {
+    let args = create_test_args(L4Protocol::Tcp);
+    let mut builder = PacketBuilder::from(&args);
+    
+    let snapshots = builder.build_with_snapshots(b"layers").unwrap();
+    
+    let lengths: Vec<usize> = snapshots.iter().map(Vec::len).collect();
+    assert_eq!(lengths, [14, 34, 54, 60]);
+    let full = builder.build_packet(b"layers").unwrap();
+    for snapshot in &snapshots {
+        assert!(full.starts_with(snapshot), "Each snapshot should be a prefix of the frame");
+    }
+}
+
+// ==================== Multicast Tests ====================
+
+#[test]
+fn test_multicast_sets_ip_mac_and_ttl() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.multicast = Some("239.1.1.1".parse().unwrap());
+    assert!(args.validate().is_ok());
+    
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"group").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_destination().octets(), [0x01, 0x00, 0x5e, 0x01, 0x01, 0x01]);
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_destination(), "239.1.1.1".parse::<std::net::Ipv4Addr>().unwrap());
+    assert_eq!(ipv4.get_ttl(), 1);
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(udp.payload(), b"group");
+}
+
+#[test]
+fn test_multicast_rejects_unicast_group() {
+    let mut args = create_test_args(L4Protocol::Udp);
+    args.multicast = Some("10.1.1.1".parse().unwrap());
+    assert!(args.validate().is_err());
+}
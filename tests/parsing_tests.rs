@@ -2,7 +2,8 @@
 //!
 //! Validates MAC address and bitfield parsing functionality.
 
-use packet_crafter::parsing::{parse_mac, parse_bitfield};
+use packet_crafter::{Args, L4Protocol};
+use packet_crafter::parsing::{parse_mac, parse_mac_spec, parse_bitfield, parse_hexdump, parse_link_speed, parse_flow, parse_flow_label, parse_hex_bytes, initial_ttl_for_os, expand_template, parse_size_sweep, parse_ethertype, parse_repl_command, ReplCommand, ReplSetting};
 
 // ==================== MAC Address Parsing ====================
 
@@ -31,6 +32,39 @@ fn test_parse_mac_invalid_hex() {
     assert!(parse_mac("zz:bb:cc:dd:ee:ff").is_err());
 }
 
+// ==================== MAC Spec Parsing ====================
+
+#[test]
+fn test_parse_mac_spec_fixed_address() {
+    assert_eq!(parse_mac_spec("aa:bb:cc:dd:ee:ff").unwrap(), [0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff]);
+}
+
+#[test]
+fn test_parse_mac_spec_random_keeps_oui() {
+    let macs: Vec<[u8; 6]> = (0..8).map(|_| parse_mac_spec("random:00:11:22").unwrap()).collect();
+    
+    for mac in &macs {
+        assert_eq!(&mac[..3], &[0x00, 0x11, 0x22], "OUI should be retained");
+    }
+    assert!(
+        macs.iter().any(|mac| mac[3..] != macs[0][3..]),
+        "Lower bytes should vary between draws"
+    );
+}
+
+#[test]
+fn test_parse_mac_spec_random_is_local_unicast() {
+    let mac = parse_mac_spec("random").unwrap();
+    assert_eq!(mac[0] & 0x01, 0, "Multicast bit should be clear");
+    assert_eq!(mac[0] & 0x02, 0x02, "Locally administered bit should be set");
+}
+
+#[test]
+fn test_parse_mac_spec_invalid_oui() {
+    assert!(parse_mac_spec("random:00:11").is_err());
+    assert!(parse_mac_spec("random:00:11:zz").is_err());
+}
+
 // ==================== Bitfield Parsing ====================
 
 #[test]
@@ -56,3 +90,208 @@ fn test_parse_bitfield_invalid() {
     assert!(parse_bitfield("0xGG").is_err());
 }
 
+
+
+// ==================== Hex Dump Parsing ====================
+
+#[test]
+fn test_parse_hexdump_xxd() {
+    let dump = "\
+00000000: 1122 3344 5566 aabb ccdd eeff 0800 4500  .\"3DUf........E.
+00000010: 001c                                     ..
+";
+    let bytes = parse_hexdump(dump).unwrap();
+    assert_eq!(bytes.len(), 18);
+    assert_eq!(&bytes[..6], &[0x11, 0x22, 0x33, 0x44, 0x55, 0x66]);
+    assert_eq!(&bytes[12..14], &[0x08, 0x00]);
+    assert_eq!(&bytes[16..], &[0x00, 0x1c]);
+}
+
+#[test]
+fn test_parse_hexdump_tcpdump() {
+    let dump = "\t0x0000:  1122 3344 5566 aabb\n\t0x0008:  ccdd\n";
+    assert_eq!(
+        parse_hexdump(dump).unwrap(),
+        vec![0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0xaa, 0xbb, 0xcc, 0xdd]
+    );
+}
+
+#[test]
+fn test_parse_hexdump_malformed_offset() {
+    assert!(parse_hexdump("0000zz00: 4500 001c  E...\n").is_err());
+    assert!(parse_hexdump("4500 001c\n").is_err());
+    assert!(parse_hexdump("00000000: 4500\n00000008: 001c\n").is_err());
+}
+
+// ==================== Flow Label Parsing ====================
+
+#[test]
+fn test_parse_flow_label() {
+    assert_eq!(parse_flow_label("0").unwrap(), 0);
+    assert_eq!(parse_flow_label("0xFFFFF").unwrap(), 0xFFFFF);
+    assert_eq!(parse_flow_label("1048575").unwrap(), 0xFFFFF);
+    assert!(parse_flow_label("0x100000").is_err(), "21-bit value should fail");
+    assert!(parse_flow_label("1048576").is_err(), "21-bit value should fail");
+    assert!(parse_flow_label("label").is_err());
+}
+
+// ==================== Hex Bytes Parsing ====================
+
+#[test]
+fn test_parse_hex_bytes() {
+    assert_eq!(parse_hex_bytes("deadbeef").unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(parse_hex_bytes("0x00 ff").unwrap(), vec![0x00, 0xff]);
+    assert!(parse_hex_bytes("abc").is_err(), "Odd digit count should fail");
+    assert!(parse_hex_bytes("zz").is_err(), "Non-hex digits should fail");
+}
+
+// ==================== Link Speed Parsing ====================
+
+#[test]
+fn test_parse_link_speed() {
+    assert_eq!(parse_link_speed("1Gbps").unwrap(), 1_000_000_000);
+    assert_eq!(parse_link_speed("100Mbps").unwrap(), 100_000_000);
+    assert_eq!(parse_link_speed("10k").unwrap(), 10_000);
+    assert_eq!(parse_link_speed("9600").unwrap(), 9600);
+    assert!(parse_link_speed("Gbps").is_err());
+    assert!(parse_link_speed("fast").is_err());
+}
+
+// ==================== Flow Parsing ====================
+
+#[test]
+fn test_parse_flow_tcp() {
+    let flow = parse_flow("tcp 10.0.0.1:1234 -> 8.8.8.8:443").unwrap();
+    assert_eq!(flow.protocol, L4Protocol::Tcp);
+    assert_eq!(flow.src_ip.to_string(), "10.0.0.1");
+    assert_eq!(flow.src_port, 1234);
+    assert_eq!(flow.dst_ip.to_string(), "8.8.8.8");
+    assert_eq!(flow.dst_port, 443);
+}
+
+#[test]
+fn test_parse_flow_udp() {
+    let flow = parse_flow("UDP 192.168.1.5:5353->224.0.0.251:5353").unwrap();
+    assert_eq!(flow.protocol, L4Protocol::Udp);
+    assert_eq!(flow.src_port, 5353);
+    assert_eq!(flow.dst_ip.to_string(), "224.0.0.251");
+}
+
+#[test]
+fn test_parse_flow_malformed() {
+    assert!(parse_flow("tcp 10.0.0.1:1234 8.8.8.8:443").is_err(), "Missing arrow should fail");
+    assert!(parse_flow("icmp 10.0.0.1:1 -> 8.8.8.8:2").is_err());
+    assert!(parse_flow("tcp 10.0.0.1 -> 8.8.8.8:443").is_err());
+    assert!(parse_flow("tcp 10.0.0.1:99999 -> 8.8.8.8:443").is_err());
+}
+// ==================== OS TTL Profiles ====================
+
+#[test]
+fn test_initial_ttl_for_known_os() {
+    assert_eq!(initial_ttl_for_os("linux"), 64);
+    assert_eq!(initial_ttl_for_os("macos"), 64);
+    assert_eq!(initial_ttl_for_os("freebsd"), 64);
+    assert_eq!(initial_ttl_for_os("windows"), 128);
+    assert_eq!(initial_ttl_for_os("cisco"), 255);
+    assert_eq!(initial_ttl_for_os("solaris"), 255);
+}
+
+#[test]
+fn test_initial_ttl_is_case_insensitive() {
+    assert_eq!(initial_ttl_for_os("Windows"), 128);
+    assert_eq!(initial_ttl_for_os("CISCO"), 255);
+}
+
+#[test]
+fn test_initial_ttl_for_unknown_os_defaults_to_64() {
+    assert_eq!(initial_ttl_for_os("plan9"), 64);
+    assert_eq!(initial_ttl_for_os(""), 64);
+}
+
+// ==================== Template Expansion ====================
+
+#[test]
+fn test_expand_template_dst_port() {
+    let args = Args { dest_port: 8080, ..Args::default() };
+    
+    let bytes = expand_template("0000 {{dst_port}}\n0001", &args).unwrap();
+    
+    assert_eq!(bytes, vec![0x00, 0x00, 0x1f, 0x90, 0x00, 0x01]);
+}
+
+#[test]
+fn test_expand_template_addresses() {
+    let args = Args { src_ip: "10.0.0.1".parse().unwrap(), ..Args::default() };
+    
+    let bytes = expand_template("{{src_mac}}{{ src_ip }}", &args).unwrap();
+    
+    assert_eq!(bytes, vec![0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 10, 0, 0, 1]);
+}
+
+#[test]
+fn test_expand_template_errors() {
+    let args = Args::default();
+    assert!(expand_template("{{unknown}}", &args).is_err());
+    assert!(expand_template("00 {{dst_port", &args).is_err());
+    assert!(expand_template("0g", &args).is_err());
+}
+
+// ==================== Size Sweep Parsing ====================
+
+#[test]
+fn test_parse_size_sweep() {
+    let sweep = parse_size_sweep("64:1500:256").unwrap();
+    assert_eq!(sweep.sizes().collect::<Vec<_>>(), [64, 320, 576, 832, 1088, 1344]);
+    
+    assert!(parse_size_sweep("100:300").is_err(), "Missing step");
+    assert!(parse_size_sweep("100:300:0").is_err(), "Zero step");
+    assert!(parse_size_sweep("300:100:10").is_err(), "Start after end");
+    assert!(parse_size_sweep("a:300:10").is_err());
+}
+
+// ==================== EtherType Parsing ====================
+
+#[test]
+fn test_parse_ethertype() {
+    assert_eq!(parse_ethertype("0x86dd").unwrap(), 0x86dd);
+    assert_eq!(parse_ethertype("0800").unwrap(), 0x0800);
+    assert!(parse_ethertype("").is_err());
+    assert!(parse_ethertype("0x10000").is_err(), "Wider than 16 bits");
+    assert!(parse_ethertype("ipv6").is_err());
+}
+
+// ==================== Interactive Command Parsing ====================
+
+#[test]
+fn test_parse_repl_set_commands() {
+    assert_eq!(parse_repl_command("set dest_port 443").unwrap(), ReplCommand::Set(ReplSetting::DestPort(443)));
+    assert_eq!(parse_repl_command("  set src_port 1024 ").unwrap(), ReplCommand::Set(ReplSetting::SrcPort(1024)));
+    assert_eq!(parse_repl_command("set dst_ip 10.0.0.9").unwrap(), ReplCommand::Set(ReplSetting::DstIp("10.0.0.9".parse().unwrap())));
+    assert_eq!(parse_repl_command("set dst_mac 02:00:00:00:00:01").unwrap(), ReplCommand::Set(ReplSetting::DstMac([2, 0, 0, 0, 0, 1])));
+    assert_eq!(parse_repl_command("set ttl 1").unwrap(), ReplCommand::Set(ReplSetting::Ttl(1)));
+    assert_eq!(parse_repl_command("set l4_protocol TCP").unwrap(), ReplCommand::Set(ReplSetting::L4Protocol(L4Protocol::Tcp)));
+    assert_eq!(parse_repl_command("set payload hello world").unwrap(), ReplCommand::Set(ReplSetting::Payload("hello world".to_string())));
+    
+    assert!(parse_repl_command("set dest_port 70000").is_err(), "Port out of range");
+    assert!(parse_repl_command("set dest_port").is_err(), "Missing value");
+    assert!(parse_repl_command("set colour blue").is_err(), "Unknown field");
+}
+
+#[test]
+fn test_parse_repl_other_commands() {
+    assert_eq!(parse_repl_command("build").unwrap(), ReplCommand::Build);
+    assert_eq!(parse_repl_command("write out.pcap").unwrap(), ReplCommand::Write("out.pcap".to_string()));
+    assert_eq!(parse_repl_command("show").unwrap(), ReplCommand::Show);
+    assert_eq!(parse_repl_command("help").unwrap(), ReplCommand::Help);
+    assert_eq!(parse_repl_command("quit").unwrap(), ReplCommand::Quit);
+    assert_eq!(parse_repl_command("exit").unwrap(), ReplCommand::Quit);
+    
+    assert!(parse_repl_command("write").is_err(), "Missing path");
+    assert!(parse_repl_command("build now").is_err(), "Unexpected argument");
+}
+
+#[test]
+fn test_parse_repl_unknown_command() {
+    let err = parse_repl_command("launch rockets").unwrap_err();
+    assert!(err.contains("Unknown command: launch"), "unexpected error: {}", err);
+}
@@ -0,0 +1,133 @@
+//! Tests for application-layer payload encoders
+
+use packet_crafter::{Args, L4Protocol, config::parse_args, packet::PacketBuilder};
+use packet_crafter::payload::dhcp::{encode_discover, MAGIC_COOKIE};
+use packet_crafter::payload::igmp::MEMBERSHIP_REPORT_V2;
+use packet_crafter::payload::lldp::{NEAREST_BRIDGE_MAC, TLV_CHASSIS_ID, TLV_END, TLV_PORT_ID, TLV_TTL};
+use pnet::packet::Packet;
+use pnet::packet::ethernet::{EthernetPacket, EtherTypes};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::udp::UdpPacket;
+
+// ==================== DHCP Tests ====================
+
+#[test]
+fn test_dhcp_discover_encoding() {
+    let mac = [0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+    let message = encode_discover(mac);
+    
+    assert_eq!(message[0], 1, "op should be BOOTREQUEST");
+    assert_eq!(message[1], 1, "htype should be Ethernet");
+    assert_eq!(message[2], 6, "hlen should be 6");
+    assert_eq!(&message[28..34], &mac, "chaddr should hold the client MAC");
+    assert_eq!(&message[236..240], &[0x63, 0x82, 0x53, 0x63]);
+    assert_eq!(&message[236..240], &MAGIC_COOKIE);
+    assert_eq!(&message[240..243], &[53, 1, 1], "Option 53 should be DISCOVER");
+    assert_eq!(message[243], 255, "Options should end with 255");
+}
+
+#[test]
+fn test_dhcp_discover_packet_addressing() {
+    let args = Args { dhcp_discover: true, l4_protocol: L4Protocol::Udp, ..Args::default() };
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(&args.payload()).unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_destination().octets(), [0xff; 6]);
+    
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_source().to_string(), "0.0.0.0");
+    assert_eq!(ipv4.get_destination().to_string(), "255.255.255.255");
+    
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(udp.get_source(), 68);
+    assert_eq!(udp.get_destination(), 67);
+    assert_eq!(&udp.payload()[236..240], &MAGIC_COOKIE);
+}
+
+// ==================== IGMP Tests ====================
+
+#[test]
+fn test_igmp_report_uses_group_multicast_mac() {
+    let args = Args { igmp_report: Some("239.1.2.3".parse().unwrap()), ..Args::default() };
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(&[]).unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_destination().octets(), [0x01, 0x00, 0x5e, 0x01, 0x02, 0x03]);
+    
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_destination().to_string(), "239.1.2.3");
+    assert_eq!(ipv4.get_next_level_protocol(), IpNextHeaderProtocols::Igmp);
+    assert_eq!(ipv4.get_ttl(), 1);
+    assert_eq!(ipv4.get_options_raw(), &[148, 4, 0, 0], "Router Alert should be present");
+    
+    let igmp = ipv4.payload();
+    assert_eq!(igmp[0], MEMBERSHIP_REPORT_V2);
+    assert_eq!(&igmp[4..8], &[239, 1, 2, 3]);
+    assert_eq!(pnet::packet::util::checksum(igmp, 1), u16::from_be_bytes([igmp[2], igmp[3]]));
+}
+
+#[test]
+fn test_igmp_report_dst_mac_override() {
+    let args = Args { igmp_report: Some("239.1.2.3".parse().unwrap()), ..Args::default() };
+    let mut builder = PacketBuilder::from(&args);
+    builder.set_dst_mac([0x11, 0x22, 0x33, 0x44, 0x55, 0x66]);
+    let packet = builder.build_packet(&[]).unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_destination().octets(), [0x11, 0x22, 0x33, 0x44, 0x55, 0x66]);
+}
+
+#[test]
+fn test_igmp_report_cli_dst_mac_wins() {
+    let args = parse_args(["packet_crafter", "--igmp_report=239.1.2.3", "--dst_mac=02:00:00:00:00:01"]).unwrap();
+    assert!(args.dst_mac_given);
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(&[]).unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_destination().octets(), [0x02, 0x00, 0x00, 0x00, 0x00, 0x01], "--dst_mac should override the group MAC");
+    
+    let args = parse_args(["packet_crafter", "--igmp_report=239.1.2.3"]).unwrap();
+    assert!(!args.dst_mac_given, "the clap default should not count as given");
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(&[]).unwrap();
+    assert_eq!(&packet[..6], &[0x01, 0x00, 0x5e, 0x01, 0x02, 0x03]);
+}
+
+// ==================== LLDP Tests ====================
+
+#[test]
+fn test_lldp_frame_mandatory_tlvs() {
+    let args = Args {
+        lldp: true,
+        lldp_port_id: "swp7".to_string(),
+        lldp_ttl: 90,
+        ..Args::default()
+    };
+    let mut builder = PacketBuilder::from(&args);
+    let packet = builder.build_packet(b"ignored").unwrap();
+    
+    let eth = EthernetPacket::new(packet).unwrap();
+    assert_eq!(eth.get_ethertype(), EtherTypes::Lldp);
+    assert_eq!(eth.get_destination().octets(), NEAREST_BRIDGE_MAC);
+    
+    let mut tlvs = Vec::new();
+    let mut rest = eth.payload();
+    while rest.len() >= 2 {
+        let header = u16::from_be_bytes([rest[0], rest[1]]);
+        let length = (header & 0x1ff) as usize;
+        tlvs.push(((header >> 9) as u8, rest[2..2 + length].to_vec()));
+        rest = &rest[2 + length..];
+    }
+    assert!(rest.is_empty(), "TLVs should cover the whole LLDPDU");
+    
+    let types: Vec<u8> = tlvs.iter().map(|(tlv_type, _)| *tlv_type).collect();
+    assert_eq!(types, [TLV_CHASSIS_ID, TLV_PORT_ID, TLV_TTL, TLV_END]);
+    assert_eq!(tlvs[0].1, [&[4][..], &args.src_mac].concat(), "Chassis ID should be the source MAC");
+    assert_eq!(tlvs[1].1, b"\x05swp7", "Port ID should be the interface name");
+    assert_eq!(tlvs[2].1, 90u16.to_be_bytes());
+    assert!(tlvs[3].1.is_empty());
+}
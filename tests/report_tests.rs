@@ -0,0 +1,44 @@
+//! Tests for burst summary calculations
+
+use packet_crafter::report::{bucket_length, length_histogram, wire_bytes, estimate_wire_time};
+use std::time::Duration;
+
+// ==================== Length Histogram Tests ====================
+
+#[test]
+fn test_bucket_length() {
+    assert_eq!(bucket_length(0), 0);
+    assert_eq!(bucket_length(42), 0);
+    assert_eq!(bucket_length(63), 0);
+    assert_eq!(bucket_length(64), 1);
+    assert_eq!(bucket_length(127), 1);
+    assert_eq!(bucket_length(128), 2);
+    assert_eq!(bucket_length(1514), 23);
+}
+
+#[test]
+fn test_length_histogram_counts() {
+    let packets = vec![vec![0u8; 42], vec![0u8; 60], vec![0u8; 64], vec![0u8; 1514]];
+    let histogram = length_histogram(&packets);
+    
+    assert_eq!(histogram.get(&0), Some(&2));
+    assert_eq!(histogram.get(&1), Some(&1));
+    assert_eq!(histogram.get(&23), Some(&1));
+    assert_eq!(histogram.len(), 3);
+}
+
+// ==================== Wire Time Tests ====================
+
+#[test]
+fn test_wire_bytes_overhead() {
+    assert_eq!(wire_bytes(42), 60 + 24, "Short frames are padded to 60 bytes");
+    assert_eq!(wire_bytes(1514), 1514 + 24);
+}
+
+#[test]
+fn test_estimate_wire_time_gigabit() {
+    // 1000 full-size frames: 1538 wire bytes each
+    let total_bits = 1000 * 1538 * 8;
+    assert_eq!(estimate_wire_time(total_bits, 1_000_000_000), Duration::from_nanos(12_304_000));
+    assert_eq!(estimate_wire_time(total_bits, 0), Duration::ZERO);
+}
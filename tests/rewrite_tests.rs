@@ -0,0 +1,80 @@
+//! Tests for rewriting the addresses of captured packets
+
+use packet_crafter::{Args, CrafterError, L4Protocol, packet::PacketBuilder, output::write_pcap_session, parsing};
+use packet_crafter::rewrite::{rewrite_packet, rewrite_pcap};
+use pcap_file::pcap::PcapReader;
+use pnet::packet::Packet;
+use pnet::packet::ethernet::EthernetPacket;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::tcp::TcpPacket;
+use std::fs::File;
+use tempfile::TempDir;
+
+fn rewrite_args() -> Args {
+    Args {
+        src_ip: "172.16.0.10".parse().unwrap(),
+        dst_ip: "172.16.0.20".parse().unwrap(),
+        src_mac: parsing::parse_mac("02:00:00:00:00:0a").unwrap(),
+        dst_mac: parsing::parse_mac("02:00:00:00:00:14").unwrap(),
+        ..Args::default()
+    }
+}
+
+/// Asserts the frame carries the `rewrite_args` addresses and valid checksums.
+fn assert_rewritten(frame: &[u8]) {
+    let args = rewrite_args();
+    let eth = EthernetPacket::new(frame).unwrap();
+    assert_eq!(eth.get_source().octets(), args.src_mac);
+    assert_eq!(eth.get_destination().octets(), args.dst_mac);
+
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    assert_eq!(ipv4.get_source(), args.src_ip);
+    assert_eq!(ipv4.get_destination(), args.dst_ip);
+    assert_eq!(ipv4.get_checksum(), pnet::packet::ipv4::checksum(&ipv4));
+
+    let tcp = TcpPacket::new(ipv4.payload()).unwrap();
+    assert_eq!(tcp.get_checksum(), pnet::packet::tcp::ipv4_checksum(&tcp, &args.src_ip, &args.dst_ip));
+}
+
+fn captured_tcp_frame() -> Vec<u8> {
+    let args = Args { l4_protocol: L4Protocol::Tcp, ..Args::default() };
+    PacketBuilder::from(&args).build_packet(b"captured").unwrap().to_vec()
+}
+
+#[test]
+fn test_rewrite_packet_replaces_addresses_and_checksums() {
+    let mut frame = captured_tcp_frame();
+    let original_length = frame.len();
+
+    rewrite_packet(&mut frame, &rewrite_args()).unwrap();
+
+    assert_eq!(frame.len(), original_length);
+    assert_rewritten(&frame);
+}
+
+#[test]
+fn test_rewrite_packet_rejects_runt_frame() {
+    let mut frame = vec![0u8; 10];
+    assert!(matches!(
+        rewrite_packet(&mut frame, &rewrite_args()),
+        Err(CrafterError::MalformedFrame { .. })
+    ));
+}
+
+#[test]
+fn test_rewrite_pcap_rewrites_every_packet() {
+    let temp_dir = TempDir::new().unwrap();
+    let input = temp_dir.path().join("in.pcap");
+    let output = temp_dir.path().join("out.pcap");
+    write_pcap_session(&input, &[captured_tcp_frame(), captured_tcp_frame()]).unwrap();
+
+    assert_eq!(rewrite_pcap(&input, &output, &rewrite_args()).unwrap(), 2);
+
+    let mut reader = PcapReader::new(File::open(&output).unwrap()).unwrap();
+    let mut count = 0;
+    while let Some(packet) = reader.next_packet() {
+        assert_rewritten(&packet.unwrap().data);
+        count += 1;
+    }
+    assert_eq!(count, 2);
+}
@@ -0,0 +1,219 @@
+//! Tests for the multi-packet run modes
+
+use packet_crafter::{Args, packet::PacketBuilder, run::{build_both_protocols, build_burst, build_from_lines, build_size_sweep, run_for_duration, run_repl, run_validate_only, Clock}, DebugFormat};
+use pnet::packet::Packet;
+use pnet::packet::ethernet::EthernetPacket;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::udp::UdpPacket;
+use std::io::Cursor;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn udp_payload(packet: &[u8]) -> Vec<u8> {
+    let eth = EthernetPacket::new(packet).unwrap();
+    let ipv4 = Ipv4Packet::new(eth.payload()).unwrap();
+    let udp = UdpPacket::new(ipv4.payload()).unwrap();
+    udp.payload().to_vec()
+}
+
+// ==================== Stdin Loop Tests ====================
+
+#[test]
+fn test_build_from_lines_one_packet_per_line() {
+    let mut builder = PacketBuilder::from(&Args::default());
+    let input = Cursor::new("first\nsecond\r\nthird");
+    
+    let packets = build_from_lines(&mut builder, input).unwrap();
+    
+    assert_eq!(packets.len(), 3);
+    assert_eq!(udp_payload(&packets[0]), b"first");
+    assert_eq!(udp_payload(&packets[1]), b"second");
+    assert_eq!(udp_payload(&packets[2]), b"third");
+}
+
+// ==================== Validate Only Tests ====================
+
+#[test]
+fn test_validate_only_accepts_valid_args() {
+    let args = Args {
+        debug_file: Some("out.pcap".to_string()),
+        debug_format: Some(DebugFormat::Pcap),
+        validate_only: true,
+        ..Args::default()
+    };
+    
+    assert_eq!(run_validate_only(&args), 0);
+}
+
+#[test]
+fn test_validate_only_rejects_invalid_args() {
+    let args = Args {
+        debug_format: Some(DebugFormat::Json),
+        validate_only: true,
+        ..Args::default()
+    };
+    
+    assert_eq!(run_validate_only(&args), 1, "Format without a file should fail");
+}
+
+#[test]
+fn test_validate_only_rejects_missing_hexdump() {
+    let args = Args {
+        from_hexdump: Some("/nonexistent/packet.hex".to_string()),
+        validate_only: true,
+        ..Args::default()
+    };
+    
+    assert_eq!(run_validate_only(&args), 1, "Missing hex dump file should fail");
+}
+
+// ==================== Burst Tests ====================
+
+#[test]
+fn test_build_burst_payload_counter() {
+    let args = Args { count: 3, payload_counter: Some(16), ..Args::default() };
+    let mut builder = PacketBuilder::from(&args);
+    
+    let packets = build_burst(&mut builder, &args).unwrap();
+    
+    assert_eq!(packets.len(), 3);
+    for (expected, packet) in (0u32..).zip(&packets) {
+        let payload = udp_payload(packet);
+        assert_eq!(payload.len(), 16);
+        assert_eq!(payload[..4], expected.to_be_bytes(), "Counter should be {}", expected);
+        assert!(payload[4..].iter().all(|&b| b == 0), "Rest of the payload should be zero");
+    }
+}
+
+#[test]
+fn test_build_burst_without_counter_repeats_payload() {
+    let args = Args { count: 2, ..Args::default() };
+    let mut builder = PacketBuilder::from(&args);
+    
+    let packets = build_burst(&mut builder, &args).unwrap();
+    
+    assert_eq!(packets[0], packets[1]);
+    assert_eq!(udp_payload(&packets[0]), args.payload());
+}
+
+// ==================== Both Protocols Tests ====================
+
+#[test]
+fn test_build_both_protocols() {
+    let args = Args { dest_port: 8443, ..Args::default() };
+    let mut builder = PacketBuilder::from(&args);
+    
+    let packets = build_both_protocols(&mut builder, b"compare").unwrap();
+    
+    assert_eq!(packets.len(), 2);
+    let ips: Vec<Ipv4Packet> = packets
+        .iter()
+        .map(|packet| Ipv4Packet::owned(EthernetPacket::new(packet).unwrap().payload().to_vec()).unwrap())
+        .collect();
+    assert_eq!(ips[0].get_next_level_protocol().0, 6, "First packet should be TCP");
+    assert_eq!(ips[1].get_next_level_protocol().0, 17, "Second packet should be UDP");
+    for ip in &ips {
+        assert_eq!(ip.get_source(), args.src_ip);
+        assert_eq!(ip.get_destination(), args.dst_ip);
+        // Destination port sits at the same offset in TCP and UDP headers
+        assert_eq!(u16::from_be_bytes([ip.payload()[2], ip.payload()[3]]), 8443);
+    }
+}
+
+// ==================== Size Sweep Tests ====================
+
+#[test]
+fn test_build_size_sweep_payload_sizes() {
+    let sweep = packet_crafter::parsing::parse_size_sweep("100:300:100").unwrap();
+    let mut builder = PacketBuilder::from(&Args::default());
+    
+    let packets = build_size_sweep(&mut builder, &sweep).unwrap();
+    
+    let sizes: Vec<usize> = packets.iter().map(|packet| udp_payload(packet).len()).collect();
+    assert_eq!(sizes, [100, 200, 300]);
+    assert_eq!(udp_payload(&packets[0])[..3], [0, 1, 2]);
+}
+
+/// Clock that only moves when slept on.
+struct MockClock {
+    now: Duration,
+    sleeps: u32,
+}
+
+impl Clock for MockClock {
+    fn elapsed(&self) -> Duration {
+        self.now
+    }
+    
+    fn sleep(&mut self, duration: Duration) {
+        self.now += duration;
+        self.sleeps += 1;
+    }
+}
+
+#[test]
+fn test_run_for_duration_with_mock_clock() {
+    let mut clock = MockClock { now: Duration::ZERO, sleeps: 0 };
+    let mut builder = PacketBuilder::from(&Args::default());
+    let mut emitted_at = Vec::new();
+    
+    let produced = run_for_duration(&mut clock, Duration::from_millis(100), Duration::from_millis(20), |index| {
+        builder.build_packet(&Args::default().burst_payload(index as u32))?;
+        emitted_at.push(index);
+        Ok::<_, packet_crafter::CrafterError>(())
+    })
+    .unwrap();
+    
+    assert_eq!(produced, 5, "100ms at 20ms intervals should produce 5 packets");
+    assert_eq!(emitted_at, [0, 1, 2, 3, 4]);
+    assert_eq!(clock.elapsed(), Duration::from_millis(80), "the loop should not sleep past the last packet");
+}
+
+#[test]
+fn test_run_for_duration_stops_on_error() {
+    let mut clock = MockClock { now: Duration::ZERO, sleeps: 0 };
+    let result = run_for_duration(&mut clock, Duration::from_secs(1), Duration::from_millis(10), |index| {
+        if index == 2 { Err("send failed") } else { Ok(()) }
+    });
+    
+    assert_eq!(result, Err("send failed"));
+    assert_eq!(clock.sleeps, 2);
+}
+
+#[test]
+fn test_repl_applies_commands_to_builder() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("out.pcap");
+    let commands = format!("write early.pcap\nset dest_port 443\nset payload hi\nlaunch\nbuild\nwrite {}\nquit\nbuild\n", path.display());
+    
+    let mut builder = PacketBuilder::from(&Args::default());
+    let mut out = Vec::new();
+    run_repl(&mut builder, Vec::new(), Cursor::new(commands), &mut out).unwrap();
+    
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 6, "one line per command before quit: {:?}", lines);
+    assert!(lines[0].starts_with("error: nothing built yet"));
+    assert!(lines[3].starts_with("error: Unknown command"));
+    assert!(lines[4].starts_with("built 44 bytes"));
+    assert!(path.exists());
+    
+    let packet = builder.build_packet(b"hi").unwrap();
+    assert_eq!(&packet[36..38], &443u16.to_be_bytes(), "set should modify the live builder");
+}
+
+#[test]
+fn test_repl_set_keeps_on_built_callback() {
+    let built = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&built);
+    let mut builder = PacketBuilder::from(&Args::default());
+    builder.set_on_built(Box::new(move |_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }));
+    
+    let commands = "set dest_port 443\nset src_port 1000\nset dst_ip 10.0.0.1\nbuild\n";
+    run_repl(&mut builder, Vec::new(), Cursor::new(commands), &mut Vec::new()).unwrap();
+    assert_eq!(built.load(Ordering::Relaxed), 1, "set should not drop the callback");
+}
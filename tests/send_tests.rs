@@ -0,0 +1,109 @@
+//! Tests for send retries using a mock transport
+
+use packet_crafter::send::{has_raw_socket_privilege, raw_socket_privilege_in_status, read_interface_mtu, send_with_retries, SendOutcome};
+use packet_crafter::traceroute::{ProbeReply, ProbeTransport};
+use std::net::Ipv4Addr;
+use std::path::Path;
+use std::time::Duration;
+
+/// Fails a fixed number of times, then answers with `reply`.
+struct FlakyTransport {
+    failures_left: u32,
+    reply: Option<ProbeReply>,
+    calls: u32,
+}
+
+impl ProbeTransport for FlakyTransport {
+    fn probe(&mut self, _packet: &[u8], _timeout: Duration) -> Result<Option<ProbeReply>, Box<dyn std::error::Error>> {
+        self.calls += 1;
+        if self.failures_left > 0 {
+            self.failures_left -= 1;
+            return Err("transient send failure".into());
+        }
+        Ok(self.reply)
+    }
+}
+
+fn target_reply() -> Option<ProbeReply> {
+    Some(ProbeReply { from: Ipv4Addr::new(10, 0, 0, 9), reached: true })
+}
+
+#[test]
+fn test_fails_twice_then_succeeds() {
+    let mut transport = FlakyTransport { failures_left: 2, reply: target_reply(), calls: 0 };
+    let packet = [0u8; 60];
+    
+    let outcome = send_with_retries(&mut transport, &packet, 3, true, Duration::from_millis(10)).unwrap();
+    
+    assert_eq!(outcome.attempts, 3);
+    assert_eq!(outcome.bytes_sent, 60);
+    assert!(outcome.reply_seen);
+    assert_eq!(transport.calls, 3, "No attempts after the reply");
+}
+
+#[test]
+fn test_retries_exhausted_returns_last_error() {
+    let mut transport = FlakyTransport { failures_left: 2, reply: target_reply(), calls: 0 };
+    
+    let result = send_with_retries(&mut transport, &[0u8; 60], 1, true, Duration::from_millis(10));
+    
+    assert_eq!(result.unwrap_err().to_string(), "transient send failure");
+    assert_eq!(transport.calls, 2);
+}
+
+#[test]
+fn test_missing_reply_uses_all_attempts() {
+    let mut transport = FlakyTransport { failures_left: 0, reply: None, calls: 0 };
+    
+    let outcome = send_with_retries(&mut transport, &[0u8; 42], 2, true, Duration::from_millis(10)).unwrap();
+    
+    assert_eq!(outcome.attempts, 3);
+    assert_eq!(outcome.bytes_sent, 3 * 42);
+    assert!(!outcome.reply_seen);
+}
+
+#[test]
+fn test_without_expect_reply_stops_after_first_write() {
+    let mut transport = FlakyTransport { failures_left: 0, reply: None, calls: 0 };
+    
+    let outcome = send_with_retries(&mut transport, &[0u8; 42], 5, false, Duration::from_millis(10)).unwrap();
+    
+    assert_eq!(
+        SendOutcome { elapsed: Duration::ZERO, ..outcome },
+        SendOutcome { attempts: 1, bytes_sent: 42, reply_seen: false, elapsed: Duration::ZERO }
+    );
+}
+
+#[test]
+fn test_read_interface_mtu_from_sysfs() {
+    let mtu = read_interface_mtu("eth0", |path| {
+        assert_eq!(path, Path::new("/sys/class/net/eth0/mtu"));
+        Ok("9000\n".to_string())
+    });
+    assert_eq!(mtu, Some(9000));
+}
+
+#[test]
+fn test_read_interface_mtu_rejects_bad_input() {
+    assert_eq!(read_interface_mtu("eth0", |_| Ok("jumbo\n".to_string())), None);
+    assert_eq!(read_interface_mtu("eth0", |_| Ok("0".to_string())), None);
+    assert_eq!(read_interface_mtu("missing0", |_| Err(std::io::ErrorKind::NotFound.into())), None);
+    assert_eq!(read_interface_mtu("../eth0", |_| Ok("1500".to_string())), None);
+}
+
+#[test]
+fn test_has_raw_socket_privilege_does_not_panic() {
+    let privileged = has_raw_socket_privilege();
+    if let Ok(status) = std::fs::read_to_string("/proc/self/status") {
+        assert_eq!(privileged, raw_socket_privilege_in_status(&status));
+    }
+}
+
+#[test]
+fn test_raw_socket_privilege_needs_root_or_cap_net_raw() {
+    let status = |uid: &str, cap_eff: &str| format!("Name:\tpacket_crafter\nUid:\t1000\t{}\t1000\t1000\nCapEff:\t{}\n", uid, cap_eff);
+    assert!(raw_socket_privilege_in_status(&status("0", "0000000000000000")));
+    assert!(raw_socket_privilege_in_status(&status("1000", "0000000000002000")));
+    assert!(!raw_socket_privilege_in_status(&status("1000", "0000000000000000")));
+    assert!(!raw_socket_privilege_in_status(""));
+}
@@ -0,0 +1,66 @@
+//! Tests for the traceroute loop using a mock transport
+
+use packet_crafter::{Args, packet::PacketBuilder};
+use packet_crafter::traceroute::{traceroute, Hop, ProbeReply, ProbeTransport};
+use pnet::packet::Packet;
+use pnet::packet::ethernet::EthernetPacket;
+use pnet::packet::ipv4::Ipv4Packet;
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::time::Duration;
+
+/// Answers each TTL from a fixed table and records the TTLs it saw.
+struct MockTransport {
+    replies: HashMap<u8, ProbeReply>,
+    seen_ttls: Vec<u8>,
+}
+
+impl ProbeTransport for MockTransport {
+    fn probe(&mut self, packet: &[u8], _timeout: Duration) -> Result<Option<ProbeReply>, Box<dyn std::error::Error>> {
+        let eth = EthernetPacket::new(packet).unwrap();
+        let ttl = Ipv4Packet::new(eth.payload()).unwrap().get_ttl();
+        self.seen_ttls.push(ttl);
+        Ok(self.replies.get(&ttl).copied())
+    }
+}
+
+#[test]
+fn test_traceroute_three_hops() {
+    let target: Ipv4Addr = "10.0.0.9".parse().unwrap();
+    let router1: Ipv4Addr = "192.168.0.254".parse().unwrap();
+    let router2: Ipv4Addr = "172.16.0.1".parse().unwrap();
+    
+    let mut transport = MockTransport {
+        replies: HashMap::from([
+            (1, ProbeReply { from: router1, reached: false }),
+            (2, ProbeReply { from: router2, reached: false }),
+            (3, ProbeReply { from: target, reached: true }),
+        ]),
+        seen_ttls: Vec::new(),
+    };
+    let args = Args { dst_ip: target, ..Args::default() };
+    let mut builder = PacketBuilder::from(&args);
+    
+    let path = traceroute(&mut builder, &mut transport, b"probe", 30, Duration::from_millis(10)).unwrap();
+    
+    assert_eq!(transport.seen_ttls, vec![1, 2, 3], "Should stop once the target answers");
+    assert_eq!(
+        path,
+        vec![
+            Hop { ttl: 1, responder: Some(router1) },
+            Hop { ttl: 2, responder: Some(router2) },
+            Hop { ttl: 3, responder: Some(target) },
+        ]
+    );
+}
+
+#[test]
+fn test_traceroute_records_timeouts_up_to_max_hops() {
+    let mut transport = MockTransport { replies: HashMap::new(), seen_ttls: Vec::new() };
+    let mut builder = PacketBuilder::from(&Args::default());
+    
+    let path = traceroute(&mut builder, &mut transport, b"probe", 3, Duration::from_millis(10)).unwrap();
+    
+    assert_eq!(path.len(), 3);
+    assert!(path.iter().all(|hop| hop.responder.is_none()));
+}